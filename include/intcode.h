@@ -0,0 +1,56 @@
+/* C declarations for the Intcode VM, matching src/intcode/ffi.rs.
+ *
+ * Regenerate with `cbindgen --config cbindgen.toml --output include/intcode.h` after changing
+ * the functions exported there. */
+
+#ifndef INTCODE_H
+#define INTCODE_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+/* What a machine did when asked to run. */
+typedef enum IntcodeStatus {
+  /* A single instruction was executed without needing input or halting. */
+  IntcodeStatus_Running = 0,
+  /* The program is waiting for input. */
+  IntcodeStatus_NeedsInput = 1,
+  /* The program has halted. */
+  IntcodeStatus_Halted = 2,
+  /* The program executed an invalid instruction. */
+  IntcodeStatus_Error = -1,
+} IntcodeStatus;
+
+/* A machine along with the outputs it has produced which have not been taken yet. */
+typedef struct IntcodeComputer IntcodeComputer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Create a machine from a program in the comma-separated form of a puzzle input. Returns null
+ * if `program` is null, not valid UTF-8 or not a valid program. */
+IntcodeComputer *intcode_new(const char *program);
+
+/* Release a machine. Does nothing if `computer` is null. */
+void intcode_free(IntcodeComputer *computer);
+
+/* Queue a value to be consumed by the next input instruction. */
+void intcode_push_input(IntcodeComputer *computer, int64_t value);
+
+/* Take the oldest output which has not been taken yet, writing it to `value`. Returns false,
+ * leaving `value` alone, if there is none. */
+bool intcode_pop_output(IntcodeComputer *computer, int64_t *value);
+
+/* Execute a single instruction, unless the program is waiting for input or has halted. */
+IntcodeStatus intcode_step(IntcodeComputer *computer);
+
+/* Run until the program needs input which has not been queued, halts or fails. Never returns
+ * `IntcodeStatus_Running`. */
+IntcodeStatus intcode_run(IntcodeComputer *computer);
+
+#ifdef __cplusplus
+}  /* extern "C" */
+#endif
+
+#endif  /* INTCODE_H */
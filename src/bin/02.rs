@@ -1,4 +1,4 @@
-use advent_of_code::intcode::{Memory, ParseMemoryError};
+use advent_of_code::intcode::{self, Computer, ExecutionError, Intcode, Memory, ParseMemoryError};
 use std::str::FromStr;
 
 advent_of_code::main!(2);
@@ -9,18 +9,24 @@ struct GravityAssistProgram {
 }
 
 impl GravityAssistProgram {
-    fn execute(&self, noun: usize, verb: usize) -> usize {
-        let mut memory = self.memory.clone();
-        memory.set_register(1, noun);
-        memory.set_register(2, verb);
-        let closing_mem = memory.run();
-        closing_mem.read_register(0)
+    fn computer(&self, noun: i64, verb: i64) -> Computer {
+        Intcode::from_memory(self.memory.clone())
+            .patch(1, noun)
+            .patch(2, verb)
+            .build()
     }
 
-    fn find_noun_and_verb(&self, target: usize) -> Option<(usize, usize)> {
+    fn execute(&self, noun: i64, verb: i64) -> Result<i64, ExecutionError> {
+        output(&mut self.computer(noun, verb))
+    }
+
+    fn find_noun_and_verb(&self, target: i64) -> Option<(i64, i64)> {
+        let mut computer = Computer::new(self.memory.clone());
         for noun in 0..=100 {
             for verb in 0..=100 {
-                if self.execute(noun, verb) == target {
+                computer.reset();
+                computer.memory_mut().patch(&[(1, noun), (2, verb)]);
+                if output(&mut computer) == Ok(target) {
                     return Some((noun, verb));
                 }
             }
@@ -29,6 +35,12 @@ impl GravityAssistProgram {
     }
 }
 
+/// Run the program to completion and return the value left at address 0.
+fn output(computer: &mut Computer) -> Result<i64, ExecutionError> {
+    computer.run_until_event()?;
+    Ok(computer.memory().read_register(0))
+}
+
 impl FromStr for GravityAssistProgram {
     type Err = ParseMemoryError;
 
@@ -39,16 +51,20 @@ impl FromStr for GravityAssistProgram {
 }
 
 #[must_use]
-pub fn part_one(input: &str) -> Option<usize> {
+pub fn part_one(input: &str) -> Option<i64> {
     if let Ok(program) = input.parse::<GravityAssistProgram>() {
-        Some(program.execute(12, 2))
+        if intcode::debug_requested() {
+            let computer = program.computer(12, 2).debug();
+            return Some(computer.memory().read_register(0));
+        }
+        program.execute(12, 2).ok()
     } else {
         None
     }
 }
 
 #[must_use]
-pub fn part_two(input: &str) -> Option<usize> {
+pub fn part_two(input: &str) -> Option<i64> {
     if let Ok(program) = input.parse::<GravityAssistProgram>() {
         program
             .find_noun_and_verb(19690720)
@@ -67,7 +83,7 @@ mod tests {
         let parsed = advent_of_code::template::read_file("examples", 2)
             .parse::<GravityAssistProgram>()
             .unwrap();
-        let expected: [usize; 12] = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let expected: [i64; 12] = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
 
         for (register, value) in expected.iter().enumerate() {
             assert_eq!(
@@ -0,0 +1,453 @@
+/// An automated player for the day 25 text adventure. It walks every room, picks up each item
+/// which turns out to be safe, then stands at the security checkpoint trying combinations of
+/// items until the pressure-sensitive floor lets it through and the airlock password is revealed.
+use super::adventure_map::{opposite, parse_rooms, AdventureMap, Room};
+use super::ascii::to_ascii;
+use super::{Cell, Computer, ExecutionError, Memory, RunState, Snapshot};
+use std::collections::HashSet;
+use std::fmt::Display;
+
+/// How many instructions a single command may take before the program is assumed to be stuck in
+/// an infinite loop.
+const STEP_LIMIT: usize = 1_000_000;
+
+const CHECKPOINT: &str = "Security Checkpoint";
+
+#[derive(Debug, PartialEq)]
+pub enum AdventureError {
+    Execution(ExecutionError),
+    /// The program stopped responding in the middle of the exploration.
+    Unresponsive,
+    /// No room named "Security Checkpoint" was found, or it had no way onwards.
+    CheckpointNotFound,
+    /// None of the combinations of safe items were accepted by the pressure-sensitive floor.
+    NoCombinationAccepted,
+}
+
+impl Display for AdventureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdventureError::Execution(e) => write!(f, "{e}"),
+            AdventureError::Unresponsive => write!(f, "the program stopped responding"),
+            AdventureError::CheckpointNotFound => write!(f, "could not find the checkpoint"),
+            AdventureError::NoCombinationAccepted => {
+                write!(f, "no combination of items was accepted")
+            }
+        }
+    }
+}
+
+impl From<ExecutionError> for AdventureError {
+    fn from(e: ExecutionError) -> Self {
+        AdventureError::Execution(e)
+    }
+}
+
+/// What the game printed in response to a command.
+#[derive(Debug, PartialEq)]
+enum Response {
+    /// The game is waiting for the next command.
+    Prompt(String),
+    Halted(String),
+    /// The game ran for too long without asking for another command.
+    TimedOut,
+}
+
+/// The game being played, which can be saved and restored so that risky moves can be undone.
+trait Console {
+    type State;
+
+    fn send(&mut self, command: Option<&str>) -> Result<Response, ExecutionError>;
+    fn save(&self) -> Self::State;
+    fn load(&mut self, state: &Self::State);
+}
+
+impl<T: Cell> Console for Computer<T> {
+    type State = Snapshot<T>;
+
+    fn send(&mut self, command: Option<&str>) -> Result<Response, ExecutionError> {
+        if let Some(command) = command {
+            self.send_line(command);
+        }
+
+        let mut text = String::new();
+        for _ in 0..STEP_LIMIT {
+            match self.step()? {
+                Some(RunState::Output(value)) => text.extend(to_ascii(&value)),
+                Some(RunState::NeedsInput) => return Ok(Response::Prompt(text)),
+                Some(RunState::Halted) => return Ok(Response::Halted(text)),
+                None => {}
+            }
+        }
+        Ok(Response::TimedOut)
+    }
+
+    fn save(&self) -> Self::State {
+        self.snapshot()
+    }
+
+    fn load(&mut self, state: &Self::State) {
+        self.restore(state);
+    }
+}
+
+/// Parse the last room description in some output from the game.
+fn parse_room(text: &str) -> Option<Room> {
+    parse_rooms(text).pop()
+}
+
+/// Find the number the game says to type on the airlock keypad, if it has said so.
+fn password(text: &str) -> Option<String> {
+    let (_, rest) = text.split_once("typing ")?;
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    (!digits.is_empty()).then_some(digits)
+}
+
+struct Explorer<C> {
+    console: C,
+    visited: HashSet<String>,
+    inventory: Vec<String>,
+    /// The doors to go through from the start to reach the checkpoint.
+    to_checkpoint: Option<Vec<String>>,
+    /// The door from the checkpoint which leads to the pressure-sensitive floor.
+    floor: Option<String>,
+    map: AdventureMap,
+}
+
+impl<C: Console> Explorer<C> {
+    fn new(console: C) -> Self {
+        Self {
+            console,
+            visited: HashSet::new(),
+            inventory: Vec::new(),
+            to_checkpoint: None,
+            floor: None,
+            map: AdventureMap::new(),
+        }
+    }
+
+    /// Send a command to the game, if given, and wait for its reply, keeping the map up to date.
+    fn send(&mut self, command: Option<&str>) -> Result<Response, ExecutionError> {
+        if let Some(command) = command {
+            self.map.observe_command(command);
+        }
+        let response = self.console.send(command)?;
+        if let Response::Prompt(text) | Response::Halted(text) = &response {
+            self.map.observe_output(text);
+        }
+        Ok(response)
+    }
+
+    fn command(&mut self, command: &str) -> Result<String, AdventureError> {
+        match self.send(Some(command))? {
+            Response::Prompt(text) | Response::Halted(text) => Ok(text),
+            Response::TimedOut => Err(AdventureError::Unresponsive),
+        }
+    }
+
+    fn go(&mut self, direction: &str) -> Result<Room, AdventureError> {
+        let text = self.command(direction)?;
+        parse_room(&text).ok_or(AdventureError::Unresponsive)
+    }
+
+    /// Take an item if doing so is safe: the game must keep running, answer promptly and still
+    /// let us move. Otherwise the game is restored to how it was before.
+    fn try_take(&mut self, item: &str) -> Result<(), ExecutionError> {
+        let saved = self.console.save();
+        match self.send(Some(&format!("take {item}")))? {
+            Response::Prompt(text) if !text.contains("can't move") => {
+                self.inventory.push(item.to_string());
+            }
+            _ => self.console.load(&saved),
+        }
+        Ok(())
+    }
+
+    fn explore(&mut self, room: &Room, path: &mut Vec<String>) -> Result<(), AdventureError> {
+        self.visited.insert(room.name.clone());
+        if room.name == CHECKPOINT {
+            self.to_checkpoint = Some(path.clone());
+        }
+        for item in &room.items {
+            self.try_take(item)?;
+        }
+
+        for door in &room.doors {
+            let next = self.go(door)?;
+            if next.name == room.name {
+                // thrown back by the pressure-sensitive floor.
+                self.floor = Some(door.clone());
+                continue;
+            }
+            if !self.visited.contains(&next.name) {
+                path.push(door.clone());
+                self.explore(&next, path)?;
+                path.pop();
+            }
+            self.go(opposite(door))?;
+        }
+        Ok(())
+    }
+
+    /// Stand on the pressure-sensitive floor with each combination of items in turn, changing
+    /// one item at a time, until one is accepted.
+    fn try_combinations(&mut self, floor: &str) -> Result<String, AdventureError> {
+        let items = self.inventory.clone();
+        let mut holding = vec![true; items.len()];
+
+        for step in 0..1_usize << items.len() {
+            let wanted = step ^ (step >> 1);
+            for (ix, item) in items.iter().enumerate() {
+                let want = wanted & (1 << ix) == 0;
+                if want != holding[ix] {
+                    let verb = if want { "take" } else { "drop" };
+                    self.command(&format!("{verb} {item}"))?;
+                    holding[ix] = want;
+                }
+            }
+
+            let text = self.command(floor)?;
+            if let Some(password) = password(&text) {
+                return Ok(password);
+            }
+        }
+        Err(AdventureError::NoCombinationAccepted)
+    }
+
+    /// Visit every room from the start, picking up whatever is safe.
+    fn explore_ship(&mut self) -> Result<(), AdventureError> {
+        let start = match self.send(None)? {
+            Response::Prompt(text) => parse_room(&text).ok_or(AdventureError::Unresponsive)?,
+            _ => return Err(AdventureError::Unresponsive),
+        };
+        self.explore(&start, &mut Vec::new())
+    }
+
+    fn solve(&mut self) -> Result<String, AdventureError> {
+        self.explore_ship()?;
+
+        let (Some(path), Some(floor)) = (self.to_checkpoint.take(), self.floor.take()) else {
+            return Err(AdventureError::CheckpointNotFound);
+        };
+        for door in &path {
+            self.go(door)?;
+        }
+        self.try_combinations(&floor)
+    }
+}
+
+/// Play the day 25 adventure to completion, returning the password for the main airlock.
+///
+/// # Errors
+///
+/// Will return `AdventureError` if the program fails, or does not behave like the adventure.
+pub fn find_password<T: Cell>(program: &Memory<T>) -> Result<String, AdventureError> {
+    Explorer::new(Computer::new(program.clone())).solve()
+}
+
+/// Walk every room of the day 25 adventure, returning a map of the ship.
+///
+/// # Errors
+///
+/// Will return `AdventureError` if the program fails, or does not behave like the adventure.
+pub fn map_ship<T: Cell>(program: &Memory<T>) -> Result<AdventureMap, AdventureError> {
+    let mut explorer = Explorer::new(Computer::new(program.clone()));
+    explorer.explore_ship()?;
+    Ok(explorer.map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in for the adventure, with the same room layout text and hazards.
+    #[derive(Clone)]
+    struct Game {
+        room: &'static str,
+        items: Vec<(&'static str, &'static str)>,
+        holding: Vec<&'static str>,
+        stuck: bool,
+    }
+
+    const DOORS: [(&str, &str, &str); 5] = [
+        ("Hull Breach", "north", "Kitchen"),
+        ("Hull Breach", "east", CHECKPOINT),
+        ("Kitchen", "south", "Hull Breach"),
+        (CHECKPOINT, "west", "Hull Breach"),
+        (CHECKPOINT, "north", "Pressure-Sensitive Floor"),
+    ];
+
+    fn weight(item: &str) -> usize {
+        match item {
+            "mug" => 1,
+            "food ration" => 2,
+            _ => 4,
+        }
+    }
+
+    impl Game {
+        fn new() -> Self {
+            Self {
+                room: "Hull Breach",
+                items: vec![
+                    ("Hull Breach", "mug"),
+                    ("Kitchen", "molten lava"),
+                    ("Kitchen", "food ration"),
+                    ("Kitchen", "giant electromagnet"),
+                    ("Kitchen", "infinite loop"),
+                    (CHECKPOINT, "spool of cat6"),
+                ],
+                holding: Vec::new(),
+                stuck: false,
+            }
+        }
+
+        fn describe(&self) -> String {
+            let mut text = format!("\n\n\n== {} ==\nA room.\n\nDoors here lead:\n", self.room);
+            for (_, door, _) in DOORS.iter().filter(|(from, _, _)| *from == self.room) {
+                text.push_str(&format!("- {door}\n"));
+            }
+            let items: Vec<_> = self
+                .items
+                .iter()
+                .filter(|(room, _)| *room == self.room)
+                .collect();
+            if !items.is_empty() {
+                text.push_str("\nItems here:\n");
+                for (_, item) in items {
+                    text.push_str(&format!("- {item}\n"));
+                }
+            }
+            text + "\nCommand?\n"
+        }
+
+        fn take(&mut self, item: &str) -> Response {
+            let Some(ix) = self
+                .items
+                .iter()
+                .position(|entry| *entry == (self.room, item))
+            else {
+                return Response::Prompt("\nYou don't see that item here.\n\nCommand?\n".into());
+            };
+            let (_, item) = self.items.remove(ix);
+            match item {
+                "molten lava" => {
+                    Response::Halted("The molten lava is way too hot! You melt!".into())
+                }
+                "infinite loop" => Response::TimedOut,
+                "giant electromagnet" => {
+                    self.stuck = true;
+                    Response::Prompt(
+                        "The giant electromagnet is stuck to you.  You can't move!!\n\nCommand?\n"
+                            .into(),
+                    )
+                }
+                _ => {
+                    self.holding.push(item);
+                    Response::Prompt(format!("\nYou take the {item}.\n\nCommand?\n"))
+                }
+            }
+        }
+
+        fn go(&mut self, direction: &str) -> Response {
+            if self.stuck {
+                return Response::Prompt("You can't move!!\n\nCommand?\n".into());
+            }
+            let Some((_, _, to)) = DOORS
+                .iter()
+                .find(|(from, door, _)| *from == self.room && *door == direction)
+            else {
+                return Response::Prompt("You can't go that way.\n\nCommand?\n".into());
+            };
+            if *to != "Pressure-Sensitive Floor" {
+                self.room = to;
+                return Response::Prompt(self.describe());
+            }
+
+            let floor = "\n\n\n== Pressure-Sensitive Floor ==\nAnalyzing...\n\n";
+            if self.holding.iter().map(|item| weight(item)).sum::<usize>() == 3 {
+                Response::Halted(format!(
+                    "{floor}You should be able to get in by typing 8401920 on the keypad."
+                ))
+            } else {
+                Response::Prompt(format!("{floor}Alert! You are ejected.{}", self.describe()))
+            }
+        }
+    }
+
+    impl Console for Game {
+        type State = Game;
+
+        fn send(&mut self, command: Option<&str>) -> Result<Response, ExecutionError> {
+            Ok(match command {
+                None => Response::Prompt(self.describe()),
+                Some(command) => match command.split_once(' ') {
+                    Some(("take", item)) => self.take(item),
+                    Some(("drop", item)) => {
+                        if let Some(ix) = self.holding.iter().position(|held| *held == item) {
+                            let item = self.holding.remove(ix);
+                            self.items.push((self.room, item));
+                        }
+                        Response::Prompt("\nYou drop it.\n\nCommand?\n".into())
+                    }
+                    _ => self.go(command),
+                },
+            })
+        }
+
+        fn save(&self) -> Self::State {
+            self.clone()
+        }
+
+        fn load(&mut self, state: &Self::State) {
+            *self = state.clone();
+        }
+    }
+
+    #[test]
+    fn test_parse_room() {
+        let text = "\n\n\n== Pressure-Sensitive Floor ==\nAnalyzing...\n\nAlert!\n\n\n\
+            == Security Checkpoint ==\nA room.\n\nDoors here lead:\n- north\n\nCommand?\n";
+        assert_eq!(
+            parse_room(text),
+            Some(Room {
+                name: CHECKPOINT.into(),
+                doors: vec!["north".into()],
+                items: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_password() {
+        assert_eq!(
+            password("get in by typing 2424308736 on the keypad"),
+            Some("2424308736".into())
+        );
+        assert_eq!(password("Alert!"), None);
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut explorer = Explorer::new(Game::new());
+        assert_eq!(explorer.solve(), Ok("8401920".into()));
+
+        let mut inventory = explorer.inventory.clone();
+        inventory.sort();
+        assert_eq!(inventory, ["food ration", "mug", "spool of cat6"]);
+
+        let map = &explorer.map;
+        assert_eq!(map.rooms().len(), 4);
+        assert_eq!(map.exit("Hull Breach", "east"), Some(CHECKPOINT));
+        assert!(map
+            .room("Kitchen")
+            .unwrap()
+            .items
+            .contains(&"molten lava".into()));
+        assert!(!map
+            .room("Kitchen")
+            .unwrap()
+            .items
+            .contains(&"food ration".into()));
+    }
+}
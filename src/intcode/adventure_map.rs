@@ -0,0 +1,360 @@
+/// Maps the ship in the day 25 adventure from the text the game prints, whether it is being
+/// played by hand or by the automated player in `adventure`.
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// The directions the game understands as movement commands.
+pub const DIRECTIONS: [&str; 4] = ["north", "south", "east", "west"];
+
+/// A room as the game describes it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Room {
+    pub name: String,
+    pub doors: Vec<String>,
+    pub items: Vec<String>,
+}
+
+#[derive(Clone, Copy)]
+enum List {
+    Doors,
+    Items,
+}
+
+/// Parse every room description in some output from the game, in order. Moving onto the
+/// pressure-sensitive floor, for example, can describe the floor and then the room the player
+/// was thrown back into.
+#[must_use]
+pub fn parse_rooms(text: &str) -> Vec<Room> {
+    let mut rooms: Vec<Room> = Vec::new();
+    let mut list = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(name) = line
+            .strip_prefix("== ")
+            .and_then(|line| line.strip_suffix(" =="))
+        {
+            rooms.push(Room {
+                name: name.trim().to_string(),
+                ..Room::default()
+            });
+            list = None;
+            continue;
+        }
+        match line {
+            "Doors here lead:" => list = Some(List::Doors),
+            "Items here:" => list = Some(List::Items),
+            line => match (line.strip_prefix("- "), list, rooms.last_mut()) {
+                (Some(entry), Some(List::Doors), Some(room)) => room.doors.push(entry.into()),
+                (Some(entry), Some(List::Items), Some(room)) => room.items.push(entry.into()),
+                _ => list = None,
+            },
+        }
+    }
+    rooms
+}
+
+/// The direction leading back the way `direction` came.
+pub(super) fn opposite(direction: &str) -> &str {
+    match direction {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        _ => "east",
+    }
+}
+
+fn offset(direction: &str) -> Option<(i64, i64)> {
+    match direction {
+        "north" => Some((0, -1)),
+        "south" => Some((0, 1)),
+        "east" => Some((1, 0)),
+        "west" => Some((-1, 0)),
+        _ => None,
+    }
+}
+
+/// The rooms seen so far and the doors found between them. Commands sent to the game and the
+/// text it prints in reply are fed in with `observe_command` and `observe_output`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AdventureMap {
+    /// Rooms in the order they were first seen, as most recently described.
+    rooms: Vec<Room>,
+    /// Where each door leads, keyed by the room it is in and its direction.
+    exits: BTreeMap<(String, String), String>,
+    current: Option<String>,
+    command: Option<String>,
+}
+
+impl AdventureMap {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note a command sent to the game, which the next output is taken to be a reply to.
+    pub fn observe_command(&mut self, command: &str) {
+        self.command = Some(command.trim().to_string());
+    }
+
+    /// Update the map from some output from the game.
+    pub fn observe_output(&mut self, text: &str) {
+        let command = self.command.take().unwrap_or_default();
+        let rooms = parse_rooms(text);
+
+        if let (Some(from), Some(to)) = (&self.current, rooms.first()) {
+            if DIRECTIONS.contains(&command.as_str()) && to.name != *from {
+                let back = opposite(&command);
+                self.exits
+                    .insert((from.clone(), command.clone()), to.name.clone());
+                if rooms.len() == 1 && to.doors.iter().any(|door| door == back) {
+                    self.exits
+                        .insert((to.name.clone(), back.to_string()), from.clone());
+                }
+            }
+        }
+
+        if let Some(last) = rooms.last() {
+            self.current = Some(last.name.clone());
+        } else if let Some(item) = command.strip_prefix("take ") {
+            if text.contains("You take the") {
+                if let Some(room) = self.current_mut() {
+                    room.items.retain(|held| held != item);
+                }
+            }
+        } else if let Some(item) = command.strip_prefix("drop ") {
+            if text.contains("You drop the") {
+                if let Some(room) = self.current_mut() {
+                    room.items.push(item.to_string());
+                }
+            }
+        }
+
+        for room in rooms {
+            match self.rooms.iter_mut().find(|seen| seen.name == room.name) {
+                Some(seen) => *seen = room,
+                None => self.rooms.push(room),
+            }
+        }
+    }
+
+    fn current_mut(&mut self) -> Option<&mut Room> {
+        let name = self.current.as_ref()?;
+        self.rooms.iter_mut().find(|room| room.name == *name)
+    }
+
+    /// Every room seen, in the order they were first seen.
+    #[must_use]
+    pub fn rooms(&self) -> &[Room] {
+        &self.rooms
+    }
+
+    #[must_use]
+    pub fn room(&self, name: &str) -> Option<&Room> {
+        self.rooms.iter().find(|room| room.name == name)
+    }
+
+    /// The room the player was last seen in.
+    #[must_use]
+    pub fn current(&self) -> Option<&Room> {
+        self.room(self.current.as_deref()?)
+    }
+
+    /// The room which the door in `direction` from `room` leads to, if it has been used.
+    #[must_use]
+    pub fn exit(&self, room: &str, direction: &str) -> Option<&str> {
+        self.exits
+            .get(&(room.to_string(), direction.to_string()))
+            .map(String::as_str)
+    }
+
+    /// Place the rooms on a grid, working outwards from the first room seen. A room which
+    /// would overlap one already placed, or which has not been reached through a known door,
+    /// is left off.
+    fn layout(&self) -> HashMap<(i64, i64), usize> {
+        let mut grid = HashMap::new();
+        let mut placed = HashMap::new();
+        if self.rooms.is_empty() {
+            return grid;
+        }
+        grid.insert((0, 0), 0);
+        placed.insert(self.rooms[0].name.as_str(), (0, 0));
+
+        let mut queue = VecDeque::from([0]);
+        while let Some(ix) = queue.pop_front() {
+            let name = self.rooms[ix].name.as_str();
+            let (x, y) = placed[name];
+            for ((_, door), to) in self.exits.iter().filter(|((from, _), _)| from == name) {
+                let Some((dx, dy)) = offset(door) else {
+                    continue;
+                };
+                let position = (x + dx, y + dy);
+                let Some(next) = self.rooms.iter().position(|room| room.name == *to) else {
+                    continue;
+                };
+                if placed.contains_key(to.as_str()) || grid.contains_key(&position) {
+                    continue;
+                }
+                grid.insert(position, next);
+                placed.insert(to.as_str(), position);
+                queue.push_back(next);
+            }
+        }
+        grid
+    }
+
+    /// Whether the room at `ix` and the room at `other` are joined by the door in `direction`
+    /// from the first, or the opposite door from the second.
+    fn joined(&self, ix: usize, direction: &str, other: usize) -> bool {
+        let (a, b) = (&self.rooms[ix].name, &self.rooms[other].name);
+        self.exit(a, direction) == Some(b) || self.exit(b, opposite(direction)) == Some(a)
+    }
+
+    /// Draw the rooms on a grid, numbered in the order they were seen, with the current room
+    /// shown in angle brackets and a key to the numbers beneath.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let grid = self.layout();
+        let current = self
+            .current()
+            .and_then(|room| self.rooms.iter().position(|seen| seen.name == room.name));
+        let mut text = String::new();
+
+        if let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) = (
+            grid.keys().map(|(x, _)| *x).min(),
+            grid.keys().map(|(x, _)| *x).max(),
+            grid.keys().map(|(_, y)| *y).min(),
+            grid.keys().map(|(_, y)| *y).max(),
+        ) {
+            for y in min_y..=max_y {
+                let mut rooms = String::new();
+                let mut doors = String::new();
+                for x in min_x..=max_x {
+                    let here = grid.get(&(x, y)).copied();
+                    match here {
+                        Some(ix) if Some(ix) == current => rooms.push_str(&format!("<{ix:02}>")),
+                        Some(ix) => rooms.push_str(&format!("[{ix:02}]")),
+                        None => rooms.push_str("    "),
+                    }
+                    let east = grid.get(&(x + 1, y)).copied();
+                    let south = grid.get(&(x, y + 1)).copied();
+                    rooms.push_str(match (here, east) {
+                        (Some(ix), Some(other)) if self.joined(ix, "east", other) => "--",
+                        _ => "  ",
+                    });
+                    doors.push_str(match (here, south) {
+                        (Some(ix), Some(other)) if self.joined(ix, "south", other) => " |    ",
+                        _ => "      ",
+                    });
+                }
+                text.push_str(rooms.trim_end());
+                text.push('\n');
+                if y < max_y {
+                    text.push_str(doors.trim_end());
+                    text.push('\n');
+                }
+            }
+            text.push('\n');
+        }
+
+        for (ix, room) in self.rooms.iter().enumerate() {
+            let marker = if Some(ix) == current { '>' } else { ' ' };
+            text.push_str(&format!("{marker}{ix:02} {}", room.name));
+            if !room.items.is_empty() {
+                text.push_str(&format!(": {}", room.items.join(", ")));
+            }
+            if !grid.values().any(|placed| *placed == ix) {
+                text.push_str(" (not shown)");
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(name: &str, doors: &[&str], items: &[&str]) -> String {
+        let mut text = format!("\n\n\n== {name} ==\nA room.\n\nDoors here lead:\n");
+        for door in doors {
+            text.push_str(&format!("- {door}\n"));
+        }
+        if !items.is_empty() {
+            text.push_str("\nItems here:\n");
+            for item in items {
+                text.push_str(&format!("- {item}\n"));
+            }
+        }
+        text + "\nCommand?\n"
+    }
+
+    /// Walks from the hull breach north to the kitchen, back, then east to the checkpoint and
+    /// north onto the floor, which throws the player back.
+    fn explored() -> AdventureMap {
+        let mut map = AdventureMap::new();
+        map.observe_output(&describe("Hull Breach", &["north", "east"], &["mug"]));
+        map.observe_command("take mug");
+        map.observe_output("\nYou take the mug.\n\nCommand?\n");
+        map.observe_command("north");
+        map.observe_output(&describe("Kitchen", &["south"], &["food ration"]));
+        map.observe_command("south");
+        map.observe_output(&describe("Hull Breach", &["north", "east"], &[]));
+        map.observe_command("east");
+        map.observe_output(&describe("Security Checkpoint", &["west", "north"], &[]));
+        map.observe_command("north");
+        map.observe_output(&format!(
+            "\n\n\n== Pressure-Sensitive Floor ==\nAnalyzing...\n\nAlert!{}",
+            describe("Security Checkpoint", &["west", "north"], &[])
+        ));
+        map
+    }
+
+    #[test]
+    fn test_parse_rooms() {
+        let text = "\n\n\n== Hull Breach ==\nA room.\n\nDoors here lead:\n- north\n- east\n\n\
+            Items here:\n- mug\n\nCommand?\n";
+        assert_eq!(
+            parse_rooms(text),
+            [Room {
+                name: "Hull Breach".into(),
+                doors: vec!["north".into(), "east".into()],
+                items: vec!["mug".into()],
+            }]
+        );
+        assert!(parse_rooms("You can't go that way.").is_empty());
+    }
+
+    #[test]
+    fn test_observe() {
+        let map = explored();
+        assert_eq!(map.rooms().len(), 4);
+        assert_eq!(map.exit("Hull Breach", "north"), Some("Kitchen"));
+        assert_eq!(map.exit("Kitchen", "south"), Some("Hull Breach"));
+        assert_eq!(
+            map.exit("Security Checkpoint", "north"),
+            Some("Pressure-Sensitive Floor")
+        );
+        assert_eq!(map.exit("Pressure-Sensitive Floor", "south"), None);
+        assert_eq!(map.current().unwrap().name, "Security Checkpoint");
+        assert!(map.room("Hull Breach").unwrap().items.is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            explored().render(),
+            [
+                "[01]  [03]",
+                " |     |",
+                "[00]--<02>",
+                "",
+                " 00 Hull Breach",
+                " 01 Kitchen: food ration",
+                ">02 Security Checkpoint",
+                " 03 Pressure-Sensitive Floor",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(AdventureMap::new().render(), "");
+    }
+}
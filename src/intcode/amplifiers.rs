@@ -0,0 +1,142 @@
+/// Runners for chains of amplifiers, each a copy of the same program given its own phase setting
+/// as its first input.
+use super::{Cell, Computer, ExecutionError, Memory, RunState};
+
+fn amplifiers<T: Cell>(program: &Memory<T>, phases: &[T]) -> Vec<Computer<T>> {
+    phases
+        .iter()
+        .map(|phase| {
+            let mut computer = Computer::new(program.clone());
+            computer.push_input(phase.clone());
+            computer
+        })
+        .collect()
+}
+
+/// Pass a signal of zero through one amplifier per phase setting in turn, returning the signal
+/// produced by the last of them.
+///
+/// # Errors
+///
+/// Will return `ExecutionError` if an amplifier fails, asks for more input than it is given, or
+/// halts without producing a signal.
+pub fn series<T: Cell>(program: &Memory<T>, phases: &[T]) -> Result<T, ExecutionError> {
+    let mut signal = T::default();
+    for mut amplifier in amplifiers(program, phases) {
+        amplifier.push_input(signal);
+        signal = match amplifier.run_until_event()? {
+            RunState::Output(value) => value,
+            RunState::NeedsInput => return Err(ExecutionError::InputExhausted),
+            RunState::Halted => return Err(ExecutionError::MissingOutput),
+        };
+    }
+    Ok(signal)
+}
+
+/// Pass a signal of zero through one amplifier per phase setting, feeding the output of the last
+/// amplifier back into the first, until the amplifiers halt. Returns the last signal produced by
+/// the final amplifier.
+///
+/// # Errors
+///
+/// Will return `ExecutionError` if an amplifier fails or asks for more input than it is given,
+/// or if the final amplifier never produces a signal, as there is none without any phases.
+pub fn feedback_loop<T: Cell>(program: &Memory<T>, phases: &[T]) -> Result<T, ExecutionError> {
+    if phases.is_empty() {
+        return Err(ExecutionError::MissingOutput);
+    }
+
+    let mut amplifiers = amplifiers(program, phases);
+    let mut signal = T::default();
+    let mut thrusters = None;
+
+    loop {
+        for (ix, amplifier) in amplifiers.iter_mut().enumerate() {
+            amplifier.push_input(signal.clone());
+            match amplifier.run_until_event()? {
+                RunState::Output(value) => signal = value,
+                RunState::NeedsInput => return Err(ExecutionError::InputExhausted),
+                RunState::Halted => return thrusters.ok_or(ExecutionError::MissingOutput),
+            }
+            if ix + 1 == phases.len() {
+                thrusters = Some(signal.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(text: &str) -> Memory {
+        text.parse().expect("Could not parse program")
+    }
+
+    #[test]
+    fn test_series() {
+        let examples = [
+            (
+                "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0",
+                [4, 3, 2, 1, 0],
+                43210,
+            ),
+            (
+                "3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0",
+                [0, 1, 2, 3, 4],
+                54321,
+            ),
+            (
+                "3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,\
+                32,31,31,4,31,99,0,0,0",
+                [1, 0, 4, 3, 2],
+                65210,
+            ),
+        ];
+        for (text, phases, signal) in examples {
+            assert_eq!(series(&program(text), &phases), Ok(signal));
+        }
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        let examples = [
+            (
+                "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,\
+                99,0,0,5",
+                [9, 8, 7, 6, 5],
+                139_629_729,
+            ),
+            (
+                "3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,\
+                1,12,1,53,54,53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,\
+                56,6,99,0,0,0,0,10",
+                [9, 7, 8, 5, 6],
+                18216,
+            ),
+        ];
+        for (text, phases, signal) in examples {
+            assert_eq!(feedback_loop(&program(text), &phases), Ok(signal));
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            series(&program("3,0,99"), &[1, 2]),
+            Err(ExecutionError::MissingOutput)
+        );
+        assert_eq!(
+            series(&program("3,0,3,0,3,0,99"), &[1]),
+            Err(ExecutionError::InputExhausted)
+        );
+        assert_eq!(
+            feedback_loop(&program("3,0,3,0,99"), &[1, 2]),
+            Err(ExecutionError::MissingOutput)
+        );
+        assert_eq!(
+            feedback_loop(&program("3,0,4,0,99"), &[]),
+            Err(ExecutionError::MissingOutput)
+        );
+    }
+}
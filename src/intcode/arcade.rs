@@ -0,0 +1,302 @@
+/// The day 13 arcade cabinet: a program which draws a game of Breakout as `(x, y, tile)`
+/// triples and reads the joystick position whenever it wants input.
+use super::{Computer, ExecutionError, Memory, RunState, ScreenBuffer};
+
+/// The address which holds the number of quarters inserted. Setting it to 2 plays for free.
+const QUARTERS_ADDRESS: usize = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl Tile {
+    #[must_use]
+    pub fn from_value(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(Tile::Empty),
+            1 => Some(Tile::Wall),
+            2 => Some(Tile::Block),
+            3 => Some(Tile::Paddle),
+            4 => Some(Tile::Ball),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn value(self) -> i64 {
+        match self {
+            Tile::Empty => 0,
+            Tile::Wall => 1,
+            Tile::Block => 2,
+            Tile::Paddle => 3,
+            Tile::Ball => 4,
+        }
+    }
+
+    #[must_use]
+    pub fn glyph(self) -> char {
+        match self {
+            Tile::Empty => ' ',
+            Tile::Wall => '█',
+            Tile::Block => '#',
+            Tile::Paddle => '=',
+            Tile::Ball => 'o',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Joystick {
+    Left,
+    #[default]
+    Neutral,
+    Right,
+}
+
+impl Joystick {
+    fn value(self) -> i64 {
+        match self {
+            Joystick::Left => -1,
+            Joystick::Neutral => 0,
+            Joystick::Right => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Arcade {
+    computer: Computer,
+    screen: ScreenBuffer,
+}
+
+impl Arcade {
+    /// Load the game as given, which draws the screen once and halts.
+    #[must_use]
+    pub fn new(program: &Memory) -> Self {
+        Self {
+            computer: Computer::new(program.clone()),
+            screen: ScreenBuffer::new(),
+        }
+    }
+
+    /// Load the game with quarters inserted, so that it can be played.
+    #[must_use]
+    pub fn free_play(program: &Memory) -> Self {
+        Self::new(&program.clone().with_patches(&[(QUARTERS_ADDRESS, 2)]))
+    }
+
+    #[must_use]
+    pub fn screen(&self) -> &ScreenBuffer {
+        &self.screen
+    }
+
+    /// The current score, or zero if none has been drawn yet.
+    #[must_use]
+    pub fn score(&self) -> i64 {
+        self.screen.score().copied().unwrap_or_default()
+    }
+
+    /// The position of the first tile of this kind found on screen, if any.
+    #[must_use]
+    pub fn find(&self, tile: Tile) -> Option<(i64, i64)> {
+        self.screen.find(&tile.value())
+    }
+
+    /// Run the game until it wants the joystick moved or is over. Returns `true` once the game
+    /// is over.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program fails.
+    pub fn run(&mut self) -> Result<bool, ExecutionError> {
+        loop {
+            match self.computer.run_until_event()? {
+                RunState::Output(value) => self.screen.push(value),
+                RunState::NeedsInput => return Ok(false),
+                RunState::Halted => return Ok(true),
+            }
+        }
+    }
+
+    pub fn tilt(&mut self, joystick: Joystick) {
+        self.computer.push_input(joystick.value());
+    }
+
+    /// Which way to move the joystick to keep the paddle under the ball.
+    #[must_use]
+    pub fn follow_ball(&self) -> Joystick {
+        match (self.find(Tile::Ball), self.find(Tile::Paddle)) {
+            (Some((ball, _)), Some((paddle, _))) if ball < paddle => Joystick::Left,
+            (Some((ball, _)), Some((paddle, _))) if ball > paddle => Joystick::Right,
+            _ => Joystick::Neutral,
+        }
+    }
+
+    /// Play until the game is over, always moving the paddle towards the ball, and return the
+    /// final score.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program fails.
+    pub fn autoplay(&mut self) -> Result<i64, ExecutionError> {
+        while !self.run()? {
+            self.tilt(self.follow_ball());
+        }
+        Ok(self.score())
+    }
+
+    #[must_use]
+    pub fn render(&self) -> String {
+        self.screen
+            .render(|value| Tile::from_value(*value).map_or('?', Tile::glyph))
+    }
+}
+
+/// Insert quarters and let the paddle AI play the game to the end, returning the final score.
+///
+/// # Errors
+///
+/// Will return `ExecutionError` if the program fails.
+pub fn winning_score(program: &Memory) -> Result<i64, ExecutionError> {
+    Arcade::free_play(program).autoplay()
+}
+
+/// Play the game in the terminal, steering with the arrow keys; `q` or Escape quits. The game
+/// advances a frame every `frame` whether or not a key is pressed. Returns the final score.
+///
+/// # Errors
+///
+/// Will return `io::Error` if the terminal cannot be used, or the program fails.
+#[cfg(feature = "tui")]
+pub fn play_in_terminal(program: &Memory, frame: std::time::Duration) -> std::io::Result<i64> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use crossterm::{cursor, execute, terminal};
+    use std::io::{self, Write};
+
+    let mut arcade = Arcade::free_play(program);
+    let mut out = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let result = (|| -> io::Result<i64> {
+        loop {
+            let over = arcade.run().map_err(io::Error::other)?;
+            execute!(
+                out,
+                cursor::MoveTo(0, 0),
+                terminal::Clear(terminal::ClearType::All)
+            )?;
+            write!(out, "score: {}\r\n", arcade.score())?;
+            write!(out, "{}\r\n", arcade.render().replace('\n', "\r\n"))?;
+            out.flush()?;
+            if over {
+                return Ok(arcade.score());
+            }
+
+            let mut joystick = Joystick::Neutral;
+            if event::poll(frame)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        match key.code {
+                            KeyCode::Left => joystick = Joystick::Left,
+                            KeyCode::Right => joystick = Joystick::Right,
+                            KeyCode::Char('q') | KeyCode::Esc => return Ok(arcade.score()),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            arcade.tilt(joystick);
+        }
+    })();
+
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    /// Draws a wall, a paddle and a ball. Without quarters it then halts; with them it reads the
+    /// joystick once, moves the paddle and scores ten plus the joystick value before halting.
+    const GAME: &str = "
+                add  #1 #1 mode     ; with quarters in address 0 this multiplies [1] by [1]
+                out  #0
+                out  #0
+                out  #1
+                out  #2
+                out  #1
+                out  #3
+                out  #1
+                out  #0
+                out  #4
+                eq   mode #2 flag
+                jnz  flag #end
+                in   stick
+                out  #2
+                out  #1
+                out  #0
+                add  stick #2 paddle
+                out  paddle
+                out  #1
+                out  #3
+                out  #-1
+                out  #0
+                add  stick #10 stick
+                out  stick
+        end:    hlt
+        mode:   data 0
+        flag:   data 0
+        stick:  data 0
+        paddle: data 0
+    ";
+
+    fn program() -> Memory {
+        assemble(GAME).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn test_attract_mode() {
+        let mut arcade = Arcade::new(&program());
+        assert_eq!(arcade.run(), Ok(true));
+        assert_eq!(arcade.find(Tile::Paddle), Some((2, 1)));
+        assert_eq!(arcade.find(Tile::Ball), Some((1, 0)));
+        assert_eq!(arcade.render(), "█o \n  =");
+        assert_eq!(arcade.score(), 0);
+    }
+
+    #[test]
+    fn test_free_play() {
+        let mut arcade = Arcade::free_play(&program());
+        assert_eq!(arcade.run(), Ok(false));
+        arcade.tilt(Joystick::Right);
+        assert_eq!(arcade.run(), Ok(true));
+        assert_eq!(arcade.find(Tile::Paddle), Some((3, 1)));
+        assert_eq!(arcade.render(), "█o  \n   =");
+        assert_eq!(arcade.score(), 11);
+    }
+
+    #[test]
+    fn test_autoplay() {
+        let mut arcade = Arcade::free_play(&program());
+        arcade.run().unwrap();
+        assert_eq!(arcade.follow_ball(), Joystick::Left);
+        assert_eq!(winning_score(&program()), Ok(9));
+    }
+
+    #[test]
+    fn test_tiles() {
+        for value in 0..5 {
+            assert_eq!(Tile::from_value(value).map(Tile::value), Some(value));
+        }
+        assert_eq!(Tile::from_value(5), None);
+    }
+}
@@ -0,0 +1,127 @@
+/// Helpers for programs which communicate in lines of ASCII text.
+use super::{Cell, Computer, ExecutionError, RunState};
+use alloc::string::String;
+
+/// The character represented by an output value, if it is in the ASCII range.
+pub(super) fn to_ascii<T: Cell>(value: &T) -> Option<char> {
+    value
+        .to_i64()
+        .and_then(|code| u8::try_from(code).ok())
+        .filter(u8::is_ascii)
+        .map(char::from)
+}
+
+impl<T: Cell> Computer<T> {
+    /// Queue a line of ASCII input, terminated by a newline.
+    pub fn send_line(&mut self, line: &str) {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        for byte in line.bytes().chain(core::iter::once(b'\n')) {
+            self.push_input(T::from_i64(i64::from(byte)));
+        }
+    }
+
+    /// Read one line of ASCII output, without its trailing newline. Returns whatever text was
+    /// produced if the program stops before finishing the line, or `None` if there was none.
+    ///
+    /// A non-ASCII output (such as a puzzle answer) also ends the line; the value can be
+    /// collected with `take_non_ascii`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program executes an invalid instruction.
+    pub fn read_line(&mut self) -> Result<Option<String>, ExecutionError> {
+        let mut line = String::new();
+        while let RunState::Output(value) = self.run_until_event()? {
+            match to_ascii(&value) {
+                Some('\n') => return Ok(Some(line)),
+                Some(ch) => line.push(ch),
+                None => {
+                    self.set_non_ascii(value);
+                    break;
+                }
+            }
+        }
+        Ok((!line.is_empty()).then_some(line))
+    }
+
+    /// Read all ASCII output until the program asks for input or halts. Any non-ASCII outputs
+    /// are skipped; the last of them can be collected with `take_non_ascii`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program executes an invalid instruction.
+    pub fn read_until_prompt(&mut self) -> Result<String, ExecutionError> {
+        let mut text = String::new();
+        while let RunState::Output(value) = self.run_until_event()? {
+            match to_ascii(&value) {
+                Some(ch) => text.push(ch),
+                None => self.set_non_ascii(value),
+            }
+        }
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Echoes each input value back as output until it reads a zero.
+    const ECHO: &str = "3,100,1005,100,9,104,10,99,99,4,100,1105,1,0";
+
+    fn ascii_program(text: &str, trailer: &[i64]) -> Computer {
+        let values: Vec<String> = text
+            .bytes()
+            .map(i64::from)
+            .chain(trailer.iter().copied())
+            .map(|value| format!("104,{value}"))
+            .collect();
+        format!("{},99", values.join(","))
+            .parse()
+            .expect("Could not parse program")
+    }
+
+    #[test]
+    fn test_send_line() {
+        let mut computer: Computer = ECHO.parse().unwrap();
+        computer.send_line("NOT A J");
+        computer.push_input(0);
+        assert_eq!(computer.read_line(), Ok(Some("NOT A J".to_string())));
+    }
+
+    #[test]
+    fn test_send_line_with_newline() {
+        let mut computer: Computer = ECHO.parse().unwrap();
+        computer.send_line("WALK\n");
+        computer.push_input(0);
+        assert_eq!(computer.read_until_prompt(), Ok("WALK\n\n".to_string()));
+    }
+
+    #[test]
+    fn test_read_line() {
+        let mut computer = ascii_program("#.#\n..#\nend", &[]);
+        assert_eq!(computer.read_line(), Ok(Some("#.#".to_string())));
+        assert_eq!(computer.read_line(), Ok(Some("..#".to_string())));
+        assert_eq!(computer.read_line(), Ok(Some("end".to_string())));
+        assert_eq!(computer.read_line(), Ok(None));
+    }
+
+    #[test]
+    fn test_read_line_non_ascii() {
+        let mut computer = ascii_program("ok\n", &[19_348_359]);
+        assert_eq!(computer.read_line(), Ok(Some("ok".to_string())));
+        assert_eq!(computer.take_non_ascii(), None);
+        assert_eq!(computer.read_line(), Ok(None));
+        assert_eq!(computer.take_non_ascii(), Some(19_348_359));
+    }
+
+    #[test]
+    fn test_read_until_prompt() {
+        let mut computer = ascii_program("Input instructions:\n", &[1_141_896_219]);
+        assert_eq!(
+            computer.read_until_prompt(),
+            Ok("Input instructions:\n".to_string())
+        );
+        assert_eq!(computer.take_non_ascii(), Some(1_141_896_219));
+    }
+}
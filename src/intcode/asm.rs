@@ -0,0 +1,319 @@
+/// A small assembler for writing Intcode programs by hand.
+///
+/// Each line holds an optional `label:`, then either an instruction mnemonic followed by its
+/// operands or a `data` directive followed by raw values. Everything after a `;` is a comment.
+///
+/// Operands are written as `12` (position mode), `#12` (immediate mode) or `@12` (relative
+/// mode). A label name may be used in place of any number and stands for the address of the
+/// labelled line.
+///
+/// ```text
+/// loop:  in   value
+///        jz   value #end
+///        out  value
+///        jnz  #1 #loop
+/// end:   hlt
+/// value: data 0
+/// ```
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+#[derive(Debug, PartialEq)]
+pub enum AssembleError {
+    UnknownMnemonic(usize, String),
+    WrongOperandCount(usize, String),
+    InvalidOperand(usize, String),
+    UndefinedLabel(usize, String),
+    DuplicateLabel(usize, String),
+    WriteToImmediate(usize, String),
+}
+
+impl Display for AssembleError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AssembleError::UnknownMnemonic(line, name) => {
+                write!(f, "line {line}: unknown mnemonic \"{name}\"")
+            }
+            AssembleError::WrongOperandCount(line, name) => {
+                write!(f, "line {line}: wrong number of operands for \"{name}\"")
+            }
+            AssembleError::InvalidOperand(line, operand) => {
+                write!(f, "line {line}: invalid operand \"{operand}\"")
+            }
+            AssembleError::UndefinedLabel(line, label) => {
+                write!(f, "line {line}: undefined label \"{label}\"")
+            }
+            AssembleError::DuplicateLabel(line, label) => {
+                write!(f, "line {line}: label \"{label}\" is already defined")
+            }
+            AssembleError::WriteToImmediate(line, operand) => {
+                write!(
+                    f,
+                    "line {line}: cannot write to immediate operand \"{operand}\""
+                )
+            }
+        }
+    }
+}
+
+/// Each mnemonic with its opcode and number of operands, and whether its final operand is
+/// written.
+const MNEMONICS: [(&str, i64, usize, bool); 10] = [
+    ("add", 1, 3, true),
+    ("mul", 2, 3, true),
+    ("in", 3, 1, true),
+    ("out", 4, 1, false),
+    ("jnz", 5, 2, false),
+    ("jz", 6, 2, false),
+    ("lt", 7, 3, true),
+    ("eq", 8, 3, true),
+    ("arb", 9, 1, false),
+    ("hlt", 99, 0, false),
+];
+
+/// The opcode and number of operands for a mnemonic, and whether its final operand is written.
+fn lookup(mnemonic: &str) -> Option<(i64, usize, bool)> {
+    MNEMONICS
+        .iter()
+        .find(|(name, _, _, _)| *name == mnemonic)
+        .map(|(_, opcode, count, writes)| (*opcode, *count, *writes))
+}
+
+struct Line<'a> {
+    number: usize,
+    mnemonic: &'a str,
+    operands: Vec<&'a str>,
+}
+
+impl Line<'_> {
+    fn len(&self) -> usize {
+        match self.mnemonic {
+            "data" => self.operands.len(),
+            _ => 1 + self.operands.len(),
+        }
+    }
+}
+
+fn parse_lines(source: &str) -> Result<(Vec<Line<'_>>, BTreeMap<&str, usize>), AssembleError> {
+    let mut lines = Vec::new();
+    let mut labels = BTreeMap::new();
+    let mut address = 0;
+
+    for (ix, text) in source.lines().enumerate() {
+        let number = ix + 1;
+        let mut text = text.split(';').next().unwrap_or_default().trim();
+
+        if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if labels.insert(label, address).is_some() {
+                return Err(AssembleError::DuplicateLabel(number, label.into()));
+            }
+            text = rest.trim();
+        }
+
+        let mut words = text.split_whitespace();
+        let Some(mnemonic) = words.next() else {
+            continue;
+        };
+        let line = Line {
+            number,
+            mnemonic,
+            operands: words.collect(),
+        };
+
+        if mnemonic != "data" {
+            let (_, count, _) =
+                lookup(mnemonic).ok_or(AssembleError::UnknownMnemonic(number, mnemonic.into()))?;
+            if line.operands.len() != count {
+                return Err(AssembleError::WrongOperandCount(number, mnemonic.into()));
+            }
+        }
+
+        address += line.len();
+        lines.push(line);
+    }
+
+    Ok((lines, labels))
+}
+
+fn resolve_value(
+    text: &str,
+    labels: &BTreeMap<&str, usize>,
+    number: usize,
+) -> Result<i64, AssembleError> {
+    if let Ok(value) = text.parse() {
+        return Ok(value);
+    }
+    if !text.starts_with(|ch: char| ch.is_alphabetic() || ch == '_') {
+        return Err(AssembleError::InvalidOperand(number, text.into()));
+    }
+    labels
+        .get(text)
+        .map(|address| i64::try_from(*address).unwrap_or(i64::MAX))
+        .ok_or_else(|| AssembleError::UndefinedLabel(number, text.into()))
+}
+
+/// Assemble a program into the comma-separated form accepted by `Memory::from_str`.
+///
+/// # Errors
+///
+/// Will return `AssembleError` if a line uses an unknown mnemonic, has the wrong number of
+/// operands, refers to a label which is not defined, or writes to an immediate operand.
+pub fn assemble(source: &str) -> Result<String, AssembleError> {
+    let (lines, labels) = parse_lines(source)?;
+    let mut values: Vec<i64> = Vec::new();
+
+    for line in lines {
+        if line.mnemonic == "data" {
+            for operand in &line.operands {
+                values.push(resolve_value(operand, &labels, line.number)?);
+            }
+            continue;
+        }
+
+        let (mut opcode, _, writes) = lookup(line.mnemonic).unwrap_or_default();
+        let mut operands = Vec::new();
+        for (ix, operand) in line.operands.iter().enumerate() {
+            let (mode, text) = match operand.chars().next() {
+                Some('#') => (1, &operand[1..]),
+                Some('@') => (2, &operand[1..]),
+                _ => (0, *operand),
+            };
+            if mode == 1 && writes && ix + 1 == line.operands.len() {
+                return Err(AssembleError::WriteToImmediate(
+                    line.number,
+                    (*operand).into(),
+                ));
+            }
+            opcode += mode * 10_i64.pow(u32::try_from(ix).unwrap_or_default() + 2);
+            operands.push(resolve_value(text, &labels, line.number)?);
+        }
+
+        values.push(opcode);
+        values.extend(operands);
+    }
+
+    Ok(values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+/// Disassemble the instruction held in `cells`, which starts with its opcode, into the syntax
+/// accepted by `assemble`. Returns the text along with the number of cells the instruction
+/// occupies, or `None` if the cells do not hold a valid instruction.
+pub(super) fn disassemble(cells: &[i64]) -> Option<(String, usize)> {
+    let (&opcode, rest) = cells.split_first()?;
+    let &(mnemonic, _, count, writes) = MNEMONICS
+        .iter()
+        .find(|(_, code, _, _)| opcode >= 0 && *code == opcode % 100)?;
+    let count_u32 = u32::try_from(count).unwrap_or_default();
+    if opcode / 10_i64.pow(count_u32 + 2) != 0 {
+        return None;
+    }
+
+    let mut operands = Vec::new();
+    for (ix, value) in rest.get(..count)?.iter().enumerate() {
+        let written = writes && ix + 1 == count;
+        let prefix = match opcode / 10_i64.pow(u32::try_from(ix).unwrap_or_default() + 2) % 10 {
+            0 => "",
+            1 if !written => "#",
+            2 => "@",
+            _ => return None,
+        };
+        operands.push(format!("{prefix}{value}"));
+    }
+
+    let text = format!("{mnemonic:<4} {}", operands.join(" "));
+    Some((text.trim_end().to_string(), count + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::Computer;
+
+    const ECHO: &str = "
+        loop:  in   value       ; read a value
+               jz   value #end  ; stop at zero
+               out  value
+               jnz  #1 #loop
+        end:   hlt
+        value: data 0
+    ";
+
+    #[test]
+    fn test_assemble() {
+        assert_eq!(
+            assemble(ECHO),
+            Ok("3,11,1006,11,10,4,11,1105,1,0,99,0".into())
+        );
+    }
+
+    #[test]
+    fn test_assembled_program_runs() {
+        let mut computer: Computer = assemble(ECHO).unwrap().parse().unwrap();
+        for value in [4, 5, 0] {
+            computer.push_input(value);
+        }
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [4, 5]);
+    }
+
+    #[test]
+    fn test_relative_operands() {
+        assert_eq!(
+            assemble("arb #10\nout @-2\nhlt"),
+            Ok("109,10,204,-2,99".into())
+        );
+    }
+
+    #[test]
+    fn test_disassemble() {
+        assert_eq!(
+            disassemble(&[1006, 11, 10]),
+            Some(("jz   11 #10".into(), 3))
+        );
+        assert_eq!(
+            disassemble(&[21101, 2, 3, -4]),
+            Some(("add  #2 #3 @-4".into(), 4))
+        );
+        assert_eq!(disassemble(&[99]), Some(("hlt".into(), 1)));
+        assert_eq!(disassemble(&[11101, 2, 3, 4]), None);
+        assert_eq!(disassemble(&[1, 2, 3]), None);
+        assert_eq!(disassemble(&[304, 0]), None);
+        assert_eq!(disassemble(&[-1]), None);
+        assert_eq!(disassemble(&[0]), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            assemble("nop"),
+            Err(AssembleError::UnknownMnemonic(1, "nop".into()))
+        );
+        assert_eq!(
+            assemble("add 1 2"),
+            Err(AssembleError::WrongOperandCount(1, "add".into()))
+        );
+        assert_eq!(
+            assemble("out missing"),
+            Err(AssembleError::UndefinedLabel(1, "missing".into()))
+        );
+        assert_eq!(
+            assemble("a: hlt\na: hlt"),
+            Err(AssembleError::DuplicateLabel(2, "a".into()))
+        );
+        assert_eq!(
+            assemble("add 1 2 #3"),
+            Err(AssembleError::WriteToImmediate(1, "#3".into()))
+        );
+        assert_eq!(
+            assemble("out #1x"),
+            Err(AssembleError::InvalidOperand(1, "1x".into()))
+        );
+    }
+}
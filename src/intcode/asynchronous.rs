@@ -0,0 +1,141 @@
+/// Async adapters, so that computers can be driven from async tasks: input is awaited from a
+/// `Stream` and output is sent to a `Sink` or yielded as a `Stream`, in place of the manual
+/// scheduling or threads otherwise needed to run several machines at once.
+///
+/// The computer only yields to the executor while waiting for input or for its output to be
+/// accepted, so a long stretch of computation will hold up other tasks on the same thread.
+use super::{Cell, Computer, ExecutionError, RunState};
+use futures::stream::{self, Stream, StreamExt};
+use futures::{Sink, SinkExt};
+
+impl<T: Cell> Computer<T> {
+    /// Run until the program halts, awaiting values from `input` whenever the queued inputs run
+    /// out and sending every output to `output`. Outputs sent after `output` has closed are
+    /// discarded.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program executes an invalid instruction, or
+    /// `ExecutionError::InputExhausted` if `input` ends before the program halts.
+    pub async fn run_async(
+        &mut self,
+        mut input: impl Stream<Item = T> + Unpin,
+        mut output: impl Sink<T> + Unpin,
+    ) -> Result<(), ExecutionError> {
+        loop {
+            match self.run_until_event()? {
+                RunState::Output(value) => {
+                    let _ = output.send(value).await;
+                }
+                RunState::NeedsInput => match input.next().await {
+                    Some(value) => self.push_input(value),
+                    None => return Err(ExecutionError::InputExhausted),
+                },
+                RunState::Halted => return Ok(()),
+            }
+        }
+    }
+
+    /// Turn the computer into a stream of its outputs, awaiting values from `input` whenever the
+    /// queued inputs run out. The stream ends when the program halts, or after yielding an
+    /// error if it fails or `input` ends first.
+    pub fn into_stream(
+        self,
+        input: impl Stream<Item = T> + Unpin,
+    ) -> impl Stream<Item = Result<T, ExecutionError>> {
+        stream::unfold(Some((self, input)), |state| async move {
+            let (mut computer, mut input) = state?;
+            loop {
+                match computer.run_until_event() {
+                    Ok(RunState::Output(value)) => {
+                        return Some((Ok(value), Some((computer, input))))
+                    }
+                    Ok(RunState::NeedsInput) => match input.next().await {
+                        Some(value) => computer.push_input(value),
+                        None => return Some((Err(ExecutionError::InputExhausted), None)),
+                    },
+                    Ok(RunState::Halted) => return None,
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+    use futures::executor::block_on;
+    use futures::future::join_all;
+
+    const FEEDBACK: &str = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,\
+        28,1005,28,6,99,0,0,5";
+
+    fn computer(program: &str) -> Computer {
+        program.parse().expect("Could not parse program")
+    }
+
+    #[test]
+    fn test_into_stream() {
+        let program = "3,0,4,0,3,0,4,0,99";
+        let outputs = computer(program).into_stream(stream::iter([5, 7]));
+        assert_eq!(block_on(outputs.collect::<Vec<_>>()), [Ok(5), Ok(7)]);
+
+        let outputs = computer(program).into_stream(stream::iter([5]));
+        assert_eq!(
+            block_on(outputs.collect::<Vec<_>>()),
+            [Ok(5), Err(ExecutionError::InputExhausted)]
+        );
+    }
+
+    #[test]
+    fn test_chained_streams() {
+        // each amplifier adds its phase to the value it is given.
+        let program = "3,11,3,12,1,11,12,12,4,12,99,0,0";
+        let phases = [1, 2, 3];
+        let mut signal = stream::iter([10]).boxed_local();
+        for phase in phases {
+            signal = computer(program)
+                .into_stream(stream::iter([phase]).chain(signal))
+                .map(Result::unwrap)
+                .boxed_local();
+        }
+        assert_eq!(block_on(signal.collect::<Vec<_>>()), [16]);
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        let phases = [9, 8, 7, 6, 5];
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            phases.iter().map(|_| mpsc::unbounded()).unzip();
+        for (sender, phase) in senders.iter().zip(phases) {
+            sender.unbounded_send(phase).unwrap();
+        }
+        senders[0].unbounded_send(0).unwrap();
+
+        let (result_tx, mut result_rx) = mpsc::unbounded();
+        let mut outputs = senders.iter().skip(1).cloned().collect::<Vec<_>>();
+        outputs.push(result_tx);
+        let mut amplifiers: Vec<_> = phases.iter().map(|_| computer(FEEDBACK)).collect();
+        let runs = amplifiers
+            .iter_mut()
+            .zip(receivers)
+            .zip(outputs)
+            .map(|((amplifier, input), output)| amplifier.run_async(input, output));
+
+        // feed the last amplifier's output back into the first until the loop halts.
+        let feedback = async {
+            let mut last = None;
+            while let Some(value) = result_rx.next().await {
+                last = Some(value);
+                let _ = senders[0].unbounded_send(value);
+            }
+            last
+        };
+        let (results, last) = block_on(futures::future::join(join_all(runs), feedback));
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(last, Some(139_629_729));
+    }
+}
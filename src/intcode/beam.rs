@@ -0,0 +1,177 @@
+/// Queries the day 19 drone program, which reports whether a point is pulled by the tractor beam
+/// and halts after every query, so that the computer must be reset before each query.
+///
+/// The beam is assumed to be a cone spreading out from the origin into positive x and y, so each
+/// row holds at most one contiguous run of pulled points which starts no further left than the
+/// run in the row above. Scanning rows in order, each edge only ever moves rightwards.
+use super::{Backend, Computer, ExecutionError, Memory, RunState};
+use std::cell::RefCell;
+
+/// Rows with no pulled points are only searched as far as `MAX_SLOPE * (y + 1)`.
+const MAX_SLOPE: i64 = 10;
+
+/// How many rows `find_square` looks through before giving up.
+const ROW_LIMIT: i64 = 100_000;
+
+pub struct Beam {
+    drone: RefCell<Computer>,
+}
+
+/// The extent of the beam on one row.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Row {
+    start: i64,
+    end: i64,
+}
+
+impl Beam {
+    /// Prepare to query the program. Its memory is paged so that resetting it between queries
+    /// shares the program's pages, and each query only copies those it writes to.
+    #[must_use]
+    pub fn new(program: &Memory) -> Self {
+        Self {
+            drone: RefCell::new(Computer::new(program.clone().with_backend(Backend::Paged))),
+        }
+    }
+
+    /// Whether the point at `(x, y)` is pulled by the beam.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program fails or produces no output.
+    pub fn is_pulled(&self, x: i64, y: i64) -> Result<bool, ExecutionError> {
+        let mut computer = self.drone.borrow_mut();
+        computer.reset();
+        computer.push_input(x);
+        computer.push_input(y);
+        match computer.run_until_event()? {
+            RunState::Output(value) => Ok(value != 0),
+            RunState::NeedsInput => Err(ExecutionError::InputExhausted),
+            RunState::Halted => Err(ExecutionError::MissingOutput),
+        }
+    }
+
+    /// Find the left edge of the beam on row `y`, starting the search at `from`. Points at or
+    /// beyond `width` are not examined.
+    fn row_start(&self, y: i64, from: i64, width: i64) -> Result<Option<i64>, ExecutionError> {
+        let limit = width.min(MAX_SLOPE * (y + 1));
+        for x in from..limit {
+            if self.is_pulled(x, y)? {
+                return Ok(Some(x));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the beam on row `y`, given the beam on the row above (if it had one). Points at or
+    /// beyond `width` are not examined.
+    fn row(&self, y: i64, above: Option<Row>, width: i64) -> Result<Option<Row>, ExecutionError> {
+        let Some(start) = self.row_start(y, above.map_or(0, |row| row.start), width)? else {
+            return Ok(None);
+        };
+        let mut end = above.map_or(start, |row| row.end.max(start));
+        while end + 1 < width && self.is_pulled(end + 1, y)? {
+            end += 1;
+        }
+        Ok(Some(Row { start, end }))
+    }
+
+    /// Count the points pulled by the beam in the `size` by `size` area closest to the emitter.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program fails or produces no output.
+    pub fn count_pulled(&self, size: i64) -> Result<i64, ExecutionError> {
+        let mut above = None;
+        let mut count = 0;
+        for y in 0..size {
+            if let Some(row) = self.row(y, above, size)? {
+                count += row.end - row.start + 1;
+                above = Some(row);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Find the top-left corner of the first `size` by `size` square which fits entirely within
+    /// the beam, searching row by row from the emitter. Returns `None` if no such square is
+    /// found within a reasonable distance.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program fails or produces no output.
+    pub fn find_square(&self, size: i64) -> Result<Option<(i64, i64)>, ExecutionError> {
+        let mut from = 0;
+        for y in 0..ROW_LIMIT {
+            // only the left edge is needed, so there's no need to scan along the row.
+            let Some(start) = self.row_start(y, from, i64::MAX)? else {
+                continue;
+            };
+            from = start;
+
+            let top = y - size + 1;
+            if top >= 0 && self.is_pulled(start + size - 1, top)? {
+                return Ok(Some((start, top)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    /// A beam covering the points where `y / 2 <= x <= y`.
+    const BEAM: &str = "
+                in   x
+                in   y
+                lt   y x bad
+                jnz  bad #no
+                mul  x #2 double
+                lt   double y bad
+                jnz  bad #no
+                out  #1
+                hlt
+        no:     out  #0
+                hlt
+        x:      data 0
+        y:      data 0
+        bad:    data 0
+        double: data 0
+    ";
+
+    fn beam() -> Beam {
+        Beam::new(&assemble(BEAM).unwrap().parse().unwrap())
+    }
+
+    fn pulled(x: i64, y: i64) -> bool {
+        2 * x >= y && x <= y
+    }
+
+    #[test]
+    fn test_is_pulled() {
+        let beam = beam();
+        for (x, y) in [(0, 0), (1, 1), (3, 5), (5, 5), (2, 5), (6, 5)] {
+            assert_eq!(beam.is_pulled(x, y), Ok(pulled(x, y)), "({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn test_count_pulled() {
+        for size in [1, 10, 25] {
+            let expected = (0..size)
+                .flat_map(|y| (0..size).filter(move |x| pulled(*x, y)))
+                .count();
+            assert_eq!(beam().count_pulled(size), Ok(expected as i64));
+        }
+    }
+
+    #[test]
+    fn test_find_square() {
+        assert_eq!(beam().find_square(1), Ok(Some((0, 0))));
+        assert_eq!(beam().find_square(3), Ok(Some((4, 6))));
+        assert_eq!(beam().find_square(10), Ok(Some((18, 27))));
+    }
+}
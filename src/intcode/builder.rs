@@ -0,0 +1,153 @@
+/// A fluent way of setting up a `Computer`, so that the parse, patch and seed steps which start
+/// most puzzles read like the puzzle statement.
+use super::{Backend, Cell, Computer, Decoding, Engine, Memory, ParseMemoryError};
+use alloc::vec::Vec;
+
+/// Collects a program and the settings for running it, then creates a `Computer` with `build`.
+///
+/// ```
+/// # use advent_of_code::intcode::{Computer, Intcode};
+/// let mut computer: Computer = Intcode::from_input("1,0,0,0,3,0,99")
+///     .unwrap()
+///     .patch(1, 5)
+///     .with_inputs([7])
+///     .build();
+/// # computer.run_until_event().unwrap();
+/// # assert_eq!(computer.memory().read_register(0), 7);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Intcode<T = i64> {
+    memory: Memory<T>,
+    patches: Vec<(usize, T)>,
+    inputs: Vec<T>,
+    backend: Option<Backend>,
+    engine: Engine,
+    decoding: Decoding,
+    step_limit: Option<usize>,
+}
+
+impl<T: Cell> Intcode<T> {
+    /// Start from a program in the comma-separated form of a puzzle input.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseMemoryError` if the input is not a valid program.
+    pub fn from_input(input: &str) -> Result<Self, ParseMemoryError> {
+        input.parse().map(Self::from_memory)
+    }
+
+    #[must_use]
+    pub fn from_memory(memory: Memory<T>) -> Self {
+        Self {
+            memory,
+            patches: Vec::new(),
+            inputs: Vec::new(),
+            backend: None,
+            engine: Engine::default(),
+            decoding: Decoding::default(),
+            step_limit: None,
+        }
+    }
+
+    /// Write `value` to `address` before the program starts. Patches are applied in order.
+    #[must_use]
+    pub fn patch(mut self, address: usize, value: T) -> Self {
+        self.patches.push((address, value));
+        self
+    }
+
+    /// Queue input values, after any queued already.
+    #[must_use]
+    pub fn with_inputs(mut self, inputs: impl IntoIterator<Item = T>) -> Self {
+        self.inputs.extend(inputs);
+        self
+    }
+
+    /// Move memory into the given backend; by default it is left where it is.
+    #[must_use]
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    #[must_use]
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    #[must_use]
+    pub fn with_decoding(mut self, decoding: Decoding) -> Self {
+        self.decoding = decoding;
+        self
+    }
+
+    #[must_use]
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Computer<T> {
+        let mut memory = self.memory.with_patches(&self.patches);
+        if let Some(backend) = self.backend {
+            memory = memory.with_backend(backend);
+        }
+
+        let mut computer = Computer::new(memory);
+        computer.set_engine(self.engine);
+        computer.set_decoding(self.decoding);
+        computer.set_step_limit(self.step_limit);
+        for value in self.inputs {
+            computer.push_input(value);
+        }
+        computer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::{ExecutionError, RunState};
+
+    #[test]
+    fn test_build() {
+        let mut computer: Computer = Intcode::from_input("1,9,10,3,2,3,11,0,99,30,40,50")
+            .unwrap()
+            .patch(1, 1)
+            .patch(2, 2)
+            .with_backend(Backend::Sparse)
+            .with_engine(Engine::Compiled)
+            .build();
+        assert_eq!(computer.memory().backend(), Backend::Sparse);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Halted));
+        assert_eq!(computer.memory().read_register(0), 150);
+    }
+
+    #[test]
+    fn test_inputs_and_limits() {
+        let mut computer: Computer = Intcode::from_input("3,0,4,0,3,0,4,0,99")
+            .unwrap()
+            .with_inputs([3])
+            .with_inputs([4])
+            .build();
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [3, 4]);
+
+        let mut computer: Computer = Intcode::from_input("1105,1,0")
+            .unwrap()
+            .with_step_limit(10)
+            .build();
+        assert_eq!(
+            computer.run_until_event(),
+            Err(ExecutionError::StepLimitExceeded)
+        );
+
+        let mut computer: Computer = Intcode::from_input("42")
+            .unwrap()
+            .with_decoding(Decoding::Lenient)
+            .build();
+        assert_eq!(computer.run_until_event(), Ok(RunState::Halted));
+        assert!(Intcode::<i64>::from_input("1,x").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+/// A cache of decoded instructions, keyed by address, so that loops do not pay to decode the
+/// same instruction on every iteration.
+use super::{AddressMap, Cell, Decoding, ExecutionError, Instruction, Memory};
+use alloc::vec::Vec;
+
+/// The longest instruction, in cells: an opcode and three parameters.
+const MAX_LENGTH: usize = 4;
+
+/// Addresses below this are kept in a vector, grown as needed to cover them, which is well beyond
+/// the length of any puzzle program. Addresses past it, which only a program jumping far from its
+/// own cells reaches, are kept in a map, so that one such jump does not allocate out to it.
+const NEAR_LIMIT: usize = 1 << 16;
+
+/// Values kept by address, such as the decoded or compiled instruction at each address.
+#[derive(Clone, Debug)]
+pub(super) struct Slots<V> {
+    near: Vec<Option<V>>,
+    far: AddressMap<V>,
+}
+
+impl<V> Default for Slots<V> {
+    fn default() -> Self {
+        Self {
+            near: Vec::new(),
+            far: AddressMap::new(),
+        }
+    }
+}
+
+impl<V: Clone> Slots<V> {
+    pub fn get(&self, pos: usize) -> Option<&V> {
+        if pos < NEAR_LIMIT {
+            self.near.get(pos).and_then(Option::as_ref)
+        } else {
+            self.far.get(&pos)
+        }
+    }
+
+    pub fn insert(&mut self, pos: usize, value: V) {
+        if pos < NEAR_LIMIT {
+            if pos >= self.near.len() {
+                self.near.resize(pos + 1, None);
+            }
+            self.near[pos] = Some(value);
+        } else {
+            self.far.insert(pos, value);
+        }
+    }
+
+    pub fn remove(&mut self, pos: usize) {
+        if pos < NEAR_LIMIT {
+            if let Some(slot) = self.near.get_mut(pos) {
+                *slot = None;
+            }
+        } else {
+            self.far.remove(&pos);
+        }
+    }
+
+    /// How many addresses hold a value.
+    pub fn len(&self) -> usize {
+        self.near.iter().filter(|slot| slot.is_some()).count() + self.far.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.near.clear();
+        self.far.clear();
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    instruction: Instruction,
+    /// The relative base the instruction was decoded against, if any of its parameters are in
+    /// relative mode. Other instructions do not depend on it.
+    relative_base: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DecodeCache(Slots<Entry>);
+
+fn uses_relative_base(opcode: i64) -> bool {
+    (2..2 + MAX_LENGTH as u32).any(|digit| opcode / 10_i64.pow(digit) % 10 == 2)
+}
+
+impl DecodeCache {
+    /// Decode the instruction at `pos`, reusing the previous decoding if nothing it depends on
+    /// has changed since.
+    pub fn decode<T: Cell>(
+        &mut self,
+        memory: &Memory<T>,
+        pos: usize,
+        relative_base: i64,
+        decoding: Decoding,
+    ) -> Result<Instruction, ExecutionError> {
+        if let Some(entry) = self.0.get(pos) {
+            if entry.relative_base.is_none_or(|base| base == relative_base) {
+                return Ok(entry.instruction);
+            }
+        }
+
+        let instruction = memory.decode_with(pos, relative_base, decoding)?;
+        let opcode = memory.read_register(pos).to_i64().unwrap_or_default();
+        self.0.insert(
+            pos,
+            Entry {
+                instruction,
+                relative_base: uses_relative_base(opcode).then_some(relative_base),
+            },
+        );
+        Ok(instruction)
+    }
+
+    /// Forget any instruction which the cell at `address` is part of, because it is about to be
+    /// written to.
+    pub fn invalidate(&mut self, address: usize) {
+        for pos in address.saturating_sub(MAX_LENGTH - 1)..=address {
+            if self
+                .0
+                .get(pos)
+                .is_some_and(|entry| pos + entry.instruction.get_register_change().max(1) > address)
+            {
+                self.0.remove(pos);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::Backend;
+
+    #[test]
+    fn test_cached_until_written() {
+        let mut mem: Memory = "1001,0,1,4,99".parse().unwrap();
+        let mut cache = DecodeCache::default();
+        let decode = |cache: &mut DecodeCache, mem: &Memory| {
+            cache.decode(mem, 0, 0, Decoding::Strict).unwrap()
+        };
+
+        assert_eq!(decode(&mut cache, &mem), Instruction::Add(0, 2, 4));
+        mem.set_register(3, 5);
+        assert_eq!(decode(&mut cache, &mem), Instruction::Add(0, 2, 4));
+
+        cache.invalidate(4);
+        assert_eq!(decode(&mut cache, &mem), Instruction::Add(0, 2, 4));
+        cache.invalidate(3);
+        assert_eq!(decode(&mut cache, &mem), Instruction::Add(0, 2, 5));
+    }
+
+    #[test]
+    fn test_far_address() {
+        let far = 1_000_000_000_000;
+        let mut mem: Memory = Memory::new(Backend::Sparse);
+        mem.patch(&[(far, 104), (far + 1, 7)]);
+        let mut cache = DecodeCache::default();
+        assert_eq!(
+            cache.decode(&mem, far, 0, Decoding::Strict),
+            Ok(Instruction::Output(far + 1))
+        );
+        assert!(cache.0.near.is_empty());
+
+        cache.invalidate(far + 1);
+        assert!(cache.0.get(far).is_none());
+    }
+
+    #[test]
+    fn test_relative_base_changes() {
+        let mem: Memory = "204,1,99".parse().unwrap();
+        let mut cache = DecodeCache::default();
+        assert_eq!(
+            cache.decode(&mem, 0, 0, Decoding::Strict),
+            Ok(Instruction::Output(1))
+        );
+        assert_eq!(
+            cache.decode(&mem, 0, 10, Decoding::Strict),
+            Ok(Instruction::Output(11))
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use core::fmt::{Debug, Display};
+use core::ops::{Add, Mul};
+use core::str::FromStr;
+
+/// An integer type which can be stored in the cells of an Intcode `Memory`.
+///
+/// Cells are only required to be `Clone` (rather than `Copy`) so that heap-allocated big
+/// integers can be used for programs which would overflow a machine integer.
+pub trait Cell:
+    Clone
+    + Debug
+    + Default
+    + Display
+    + FromStr
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Mul<Output = Self>
+    + Send
+    + Sync
+    + 'static
+{
+    fn from_i64(value: i64) -> Self;
+
+    /// The value of this cell as an `i64`, if it fits.
+    fn to_i64(&self) -> Option<i64>;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The value of this cell as a memory address, if it is non-negative and fits in `usize`.
+    fn to_address(&self) -> Option<usize> {
+        self.to_i64().and_then(|value| usize::try_from(value).ok())
+    }
+}
+
+impl Cell for i64 {
+    fn from_i64(value: i64) -> Self {
+        value
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        Some(*self)
+    }
+}
+
+impl Cell for i128 {
+    fn from_i64(value: i64) -> Self {
+        Self::from(value)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(*self).ok()
+    }
+}
+
+/// Arbitrary-precision cells, for programs whose values outgrow even an `i128`. Only available
+/// with the `bigint` feature, so that the default `i64` cells stay free of allocation.
+#[cfg(feature = "bigint")]
+impl Cell for num_bigint::BigInt {
+    fn from_i64(value: i64) -> Self {
+        Self::from(value)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_address() {
+        assert_eq!(12_i64.to_address(), Some(12));
+        assert_eq!((-1_i64).to_address(), None);
+        assert_eq!(i128::MAX.to_address(), None);
+    }
+
+    #[test]
+    fn test_is_zero() {
+        assert!(0_i64.is_zero());
+        assert!(!7_i128.is_zero());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_bigint() {
+        use crate::intcode::Computer;
+        use num_bigint::BigInt;
+
+        let huge: BigInt = "1000000000000000000000000".parse().unwrap();
+        assert_eq!(huge.to_i64(), None);
+        assert_eq!(BigInt::from_i64(-5).to_address(), None);
+        assert_eq!(BigInt::from_i64(42).to_address(), Some(42));
+
+        let mut computer: Computer<BigInt> =
+            format!("1102,{huge},{huge},7,4,7,99,0").parse().unwrap();
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [&huge * &huge]);
+    }
+}
@@ -0,0 +1,618 @@
+/// An experimental execution engine which compiles each instruction into a closure the first time
+/// it is reached, so that later visits skip decoding and parameter-mode dispatch altogether.
+use super::cache::Slots;
+use super::{AddressMap, Cell, Decoding, ExecutionError, Memory, RunState};
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+/// How a `Computer` executes instructions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Decode and execute each instruction in turn.
+    #[default]
+    Interpreted,
+    /// Run compiled closures. Programs which modify their own code are still supported, but each
+    /// write into compiled code forces the affected instruction to be compiled again, so this is
+    /// only faster for programs which mostly leave their code alone. Falls back to interpreting
+    /// while watchpoints, tracing or profiling are active.
+    Compiled,
+    /// Run compiled closures, with some peephole optimizations applied while compiling:
+    /// arithmetic and comparisons on two immediate values are folded into constants, jumps on an
+    /// immediate condition drop the branch which can never be taken, and jumps which land on an
+    /// unconditional jump go straight to its target instead. The results are the same as the
+    /// other engines, including the step count.
+    Optimized,
+}
+
+/// The parts of a `Computer` which compiled instructions operate on.
+pub struct State<'a, T> {
+    pub memory: &'a mut Memory<T>,
+    pub instruction_pointer: &'a mut usize,
+    pub relative_base: &'a mut i64,
+    pub inputs: &'a mut VecDeque<T>,
+    pub steps: &'a mut usize,
+    pub step_limit: Option<usize>,
+    /// The address written by the most recent instruction, if any.
+    pub written: Option<usize>,
+}
+
+type Outcome<T> = Result<Option<RunState<T>>, ExecutionError>;
+type Op<T> = dyn Fn(&mut State<'_, T>) -> Outcome<T> + Send + Sync;
+
+#[derive(Clone)]
+enum Operand<T> {
+    Position(usize),
+    /// An immediate value, along with the address it is stored at.
+    Immediate(T, usize),
+    Relative(i64),
+}
+
+impl<T: Cell> Operand<T> {
+    fn address(&self, pos: usize, relative_base: i64) -> Result<usize, ExecutionError> {
+        match self {
+            Operand::Position(address) | Operand::Immediate(_, address) => Ok(*address),
+            Operand::Relative(offset) => relative_base
+                .checked_add(*offset)
+                .and_then(|address| usize::try_from(address).ok())
+                .ok_or(ExecutionError::AddressOutOfRange(pos)),
+        }
+    }
+
+    fn read(&self, pos: usize, state: &State<'_, T>) -> Result<T, ExecutionError> {
+        match self {
+            Operand::Immediate(value, _) => Ok(value.clone()),
+            _ => Ok(state
+                .memory
+                .read_register(self.address(pos, *state.relative_base)?)),
+        }
+    }
+}
+
+fn operand<T: Cell>(
+    memory: &Memory<T>,
+    pos: usize,
+    offset: u32,
+    opcode: i64,
+) -> Result<Operand<T>, ExecutionError> {
+    let param_pos = pos + offset as usize;
+    match opcode / 10_i64.pow(offset + 1) % 10 {
+        0 => memory
+            .read_address(param_pos)
+            .map(Operand::Position)
+            .map_err(|_| ExecutionError::AddressOutOfRange(pos)),
+        1 => Ok(Operand::Immediate(
+            memory.read_register(param_pos),
+            param_pos,
+        )),
+        2 => memory
+            .read_register(param_pos)
+            .to_i64()
+            .map(Operand::Relative)
+            .ok_or(ExecutionError::AddressOutOfRange(pos)),
+        mode => Err(ExecutionError::InvalidMode(pos, mode)),
+    }
+}
+
+fn arithmetic<T: Cell>(
+    pos: usize,
+    [a, b, c]: [Operand<T>; 3],
+    combine: fn(T, T) -> T,
+) -> Box<Op<T>> {
+    Box::new(move |state| {
+        let target = c.address(pos, *state.relative_base)?;
+        let value = combine(a.read(pos, state)?, b.read(pos, state)?);
+        state.memory.set_register(target, value);
+        state.written = Some(target);
+        *state.instruction_pointer = pos + 4;
+        Ok(None)
+    })
+}
+
+fn jump<T: Cell>(pos: usize, [a, b]: [Operand<T>; 2], when_zero: bool) -> Box<Op<T>> {
+    Box::new(move |state| {
+        // the target is resolved even if the jump isn't taken, just as the interpreter decodes
+        // every parameter up front.
+        let address = b.address(pos, *state.relative_base)?;
+        if a.read(pos, state)?.is_zero() == when_zero {
+            *state.instruction_pointer = state
+                .memory
+                .read_address(address)
+                .map_err(|_| ExecutionError::AddressOutOfRange(pos))?;
+        } else {
+            *state.instruction_pointer = pos + 3;
+        }
+        Ok(None)
+    })
+}
+
+/// Like `arithmetic`, but with a result worked out in advance.
+fn constant<T: Cell>(pos: usize, target: Operand<T>, value: T) -> Box<Op<T>> {
+    Box::new(move |state| {
+        let address = target.address(pos, *state.relative_base)?;
+        state.memory.set_register(address, value.clone());
+        state.written = Some(address);
+        *state.instruction_pointer = pos + 4;
+        Ok(None)
+    })
+}
+
+/// A jump to a known address, `target`, which goes on to `address` through `skipped` other
+/// jumps when taken. If counting those jumps would take the step count past the limit, only the
+/// first jump is made.
+fn direct_jump<T: Cell>(
+    pos: usize,
+    condition: Option<Operand<T>>,
+    when_zero: bool,
+    (target, address): (usize, usize),
+    skipped: usize,
+) -> Box<Op<T>> {
+    Box::new(move |state| {
+        let taken = match &condition {
+            Some(a) => a.read(pos, state)?.is_zero() == when_zero,
+            None => true,
+        };
+        if !taken {
+            *state.instruction_pointer = pos + 3;
+        } else if state
+            .step_limit
+            .is_some_and(|limit| *state.steps + 1 + skipped > limit)
+        {
+            *state.instruction_pointer = target;
+        } else {
+            *state.instruction_pointer = address;
+            *state.steps += skipped;
+        }
+        Ok(None)
+    })
+}
+
+/// The most unconditional jumps followed when threading a jump, which also stops the
+/// optimizer going round in circles when jumps lead to each other.
+const MAX_THREADED_JUMPS: usize = 8;
+
+/// Where the instruction at `pos` jumps to, if it is a jump which is always taken to a fixed
+/// address.
+fn unconditional_target<T: Cell>(memory: &Memory<T>, pos: usize) -> Option<usize> {
+    let condition = memory.read_register(pos + 1);
+    let taken = match memory.read_register(pos).to_i64()? {
+        1105 => !condition.is_zero(),
+        1106 => condition.is_zero(),
+        _ => false,
+    };
+    taken.then(|| memory.read_address(pos + 2).ok()).flatten()
+}
+
+/// Follow any unconditional jumps starting at `address`, returning where they lead along with
+/// the addresses of the jumps followed.
+fn thread<T: Cell>(memory: &Memory<T>, mut address: usize) -> (usize, Vec<usize>) {
+    let mut followed = Vec::new();
+    while followed.len() < MAX_THREADED_JUMPS {
+        let Some(next) = unconditional_target(memory, address) else {
+            break;
+        };
+        followed.push(address);
+        address = next;
+    }
+    (address, followed)
+}
+
+/// Compile an arithmetic or comparison instruction, folding it into a constant if both of its
+/// inputs are immediate values.
+fn optimized_arithmetic<T: Cell>(
+    pos: usize,
+    [a, b, c]: [Operand<T>; 3],
+    combine: fn(T, T) -> T,
+) -> Box<Op<T>> {
+    match (&a, &b) {
+        (Operand::Immediate(x, _), Operand::Immediate(y, _)) => {
+            constant(pos, c, combine(x.clone(), y.clone()))
+        }
+        _ => arithmetic(pos, [a, b, c], combine),
+    }
+}
+
+/// Compile a jump, returning the addresses of any other jumps it now depends on.
+fn optimized_jump<T: Cell>(
+    memory: &Memory<T>,
+    pos: usize,
+    [a, b]: [Operand<T>; 2],
+    when_zero: bool,
+) -> (Box<Op<T>>, Vec<usize>) {
+    // a relative target has to be resolved every time, even if the jump is never taken.
+    if matches!(b, Operand::Relative(_)) {
+        return (jump(pos, [a, b], when_zero), Vec::new());
+    }
+    let condition = match &a {
+        Operand::Immediate(value, _) if value.is_zero() != when_zero => {
+            return (
+                Box::new(move |state| {
+                    *state.instruction_pointer = pos + 3;
+                    Ok(None)
+                }),
+                Vec::new(),
+            );
+        }
+        Operand::Immediate(..) => None,
+        _ => Some(a.clone()),
+    };
+    let Operand::Immediate(target, _) = &b else {
+        return (jump(pos, [a, b], when_zero), Vec::new());
+    };
+    let Some(target) = target.to_i64().and_then(|t| usize::try_from(t).ok()) else {
+        return (jump(pos, [a, b], when_zero), Vec::new());
+    };
+
+    let (address, followed) = thread(memory, target);
+    let op = direct_jump(pos, condition, when_zero, (target, address), followed.len());
+    (op, followed)
+}
+
+fn flag<T: Cell>(value: bool) -> T {
+    T::from_i64(i64::from(value))
+}
+
+struct Compiled<T> {
+    length: usize,
+    op: Box<Op<T>>,
+    /// The addresses of any other instructions the closure was optimized for, which must not
+    /// change while it is in use.
+    depends_on: Vec<usize>,
+}
+
+/// Compile the instruction at `pos`, optimizing it if asked to.
+fn compile<T: Cell>(
+    memory: &Memory<T>,
+    pos: usize,
+    decoding: Decoding,
+    optimize: bool,
+) -> Result<Compiled<T>, ExecutionError> {
+    let opcode = memory.read_register(pos).to_i64().unwrap_or_default();
+    let param = |offset| operand(memory, pos, offset, opcode);
+    let target = |offset: u32| match param(offset)? {
+        Operand::Immediate(..) => Err(ExecutionError::WriteToImmediate(pos)),
+        operand => Ok(operand),
+    };
+    let arithmetic = |pos, operands, combine: fn(T, T) -> T| {
+        if optimize {
+            optimized_arithmetic(pos, operands, combine)
+        } else {
+            arithmetic(pos, operands, combine)
+        }
+    };
+    let mut depends_on = Vec::new();
+    let mut jump = |when_zero| -> Result<Box<Op<T>>, ExecutionError> {
+        let operands = [param(1)?, param(2)?];
+        if !optimize {
+            return Ok(jump(pos, operands, when_zero));
+        }
+        let (op, followed) = optimized_jump(memory, pos, operands, when_zero);
+        depends_on = followed;
+        Ok(op)
+    };
+
+    let (length, op): (usize, Box<Op<T>>) = match opcode % 100 {
+        1 => (
+            4,
+            arithmetic(pos, [param(1)?, param(2)?, target(3)?], |a, b| a + b),
+        ),
+        2 => (
+            4,
+            arithmetic(pos, [param(1)?, param(2)?, target(3)?], |a, b| a * b),
+        ),
+        3 => {
+            let a = target(1)?;
+            let op: Box<Op<T>> = Box::new(move |state| {
+                let address = a.address(pos, *state.relative_base)?;
+                let Some(value) = state.inputs.pop_front() else {
+                    return Ok(Some(RunState::NeedsInput));
+                };
+                state.memory.set_register(address, value);
+                state.written = Some(address);
+                *state.instruction_pointer = pos + 2;
+                Ok(None)
+            });
+            (2, op)
+        }
+        4 => {
+            let a = param(1)?;
+            let op: Box<Op<T>> = Box::new(move |state| {
+                let value = a.read(pos, state)?;
+                *state.instruction_pointer = pos + 2;
+                Ok(Some(RunState::Output(value)))
+            });
+            (2, op)
+        }
+        5 => (3, jump(false)?),
+        6 => (3, jump(true)?),
+        7 => (
+            4,
+            arithmetic(pos, [param(1)?, param(2)?, target(3)?], |a, b| flag(a < b)),
+        ),
+        8 => (
+            4,
+            arithmetic(pos, [param(1)?, param(2)?, target(3)?], |a, b| flag(a == b)),
+        ),
+        9 => {
+            let a = param(1)?;
+            let op: Box<Op<T>> = Box::new(move |state| {
+                *state.relative_base = a
+                    .read(pos, state)?
+                    .to_i64()
+                    .and_then(|value| state.relative_base.checked_add(value))
+                    .ok_or(ExecutionError::AddressOutOfRange(pos))?;
+                *state.instruction_pointer = pos + 2;
+                Ok(None)
+            });
+            (2, op)
+        }
+        99 => (1, Box::new(|_| Ok(Some(RunState::Halted)))),
+        _ => match decoding {
+            Decoding::Strict => return Err(ExecutionError::InvalidOpcode(pos)),
+            Decoding::Lenient => (1, Box::new(|_| Ok(Some(RunState::Halted)))),
+        },
+    };
+    Ok(Compiled {
+        length,
+        op,
+        depends_on,
+    })
+}
+
+/// The instructions compiled so far, keyed by address.
+pub struct Program<T> {
+    ops: Slots<(usize, Arc<Op<T>>)>,
+    optimize: bool,
+    /// For each address of an instruction which compiled code was optimized for, the
+    /// positions of that compiled code.
+    dependents: AddressMap<Vec<usize>>,
+}
+
+impl<T> Default for Program<T> {
+    fn default() -> Self {
+        Self {
+            ops: Slots::default(),
+            optimize: false,
+            dependents: AddressMap::new(),
+        }
+    }
+}
+
+impl<T> Clone for Program<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ops: self.ops.clone(),
+            optimize: self.optimize,
+            dependents: self.dependents.clone(),
+        }
+    }
+}
+
+impl<T> Debug for Program<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let compiled = self.ops.len();
+        write!(f, "Program({compiled} instructions compiled)")
+    }
+}
+
+impl<T: Cell> Program<T> {
+    /// An empty program for `engine`, which optimizes instructions as it compiles them if the
+    /// engine is `Engine::Optimized`.
+    pub fn new(engine: Engine) -> Self {
+        Self {
+            optimize: engine == Engine::Optimized,
+            ..Self::default()
+        }
+    }
+
+    /// Execute the instruction at the instruction pointer, compiling it first if necessary. Any
+    /// address written is left in `state.written`.
+    pub fn step(&mut self, state: &mut State<'_, T>, decoding: Decoding) -> Outcome<T> {
+        let pos = *state.instruction_pointer;
+        if self.ops.get(pos).is_none() {
+            let compiled = compile(state.memory, pos, decoding, self.optimize)?;
+            self.ops
+                .insert(pos, (compiled.length, Arc::from(compiled.op)));
+            for jump in compiled.depends_on {
+                for address in jump..jump + 3 {
+                    self.dependents.entry(address).or_default().push(pos);
+                }
+            }
+        }
+
+        state.written = None;
+        let event = match self.ops.get(pos) {
+            Some((_, op)) => op(state)?,
+            None => unreachable!("instruction was compiled above"),
+        };
+        if !matches!(event, Some(RunState::NeedsInput | RunState::Halted)) {
+            *state.steps += 1;
+        }
+        if let Some(address) = state.written {
+            self.invalidate(address);
+        }
+        Ok(event)
+    }
+
+    /// Execute instructions until one of them causes an event, or until `state.step_limit`
+    /// instructions have been executed in total, in which case `None` is returned.
+    pub fn run(&mut self, state: &mut State<'_, T>, decoding: Decoding) -> Outcome<T> {
+        let step_limit = state.step_limit;
+        loop {
+            if step_limit.is_some_and(|limit| *state.steps >= limit) {
+                return Ok(None);
+            }
+            if let Some(event) = self.step(state, decoding)? {
+                return Ok(Some(event));
+            }
+        }
+    }
+
+    /// Forget any compiled instruction which the cell at `address` is part of, or which was
+    /// optimized on the assumption that it would not change.
+    pub fn invalidate(&mut self, address: usize) {
+        for pos in address.saturating_sub(3)..=address {
+            if matches!(self.ops.get(pos), Some((length, _)) if pos + length > address) {
+                self.ops.remove(pos);
+            }
+        }
+        if let Some(positions) = self.dependents.remove(&address) {
+            for pos in positions {
+                self.ops.remove(pos);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.ops.clear();
+        self.dependents.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+    use crate::intcode::Computer;
+
+    fn run(
+        program: &str,
+        engine: Engine,
+        inputs: &[i64],
+    ) -> (Vec<i64>, Result<RunState, ExecutionError>, usize) {
+        let mut computer: Computer = program.parse().unwrap();
+        computer.set_engine(engine);
+        for value in inputs {
+            computer.push_input(*value);
+        }
+        let mut outputs = Vec::new();
+        loop {
+            match computer.run_until_event() {
+                Ok(RunState::Output(value)) => outputs.push(value),
+                result => return (outputs, result, computer.steps()),
+            }
+        }
+    }
+
+    fn assert_engines_agree(program: &str, inputs: &[i64]) {
+        let expected = run(program, Engine::Interpreted, inputs);
+        assert_eq!(run(program, Engine::Compiled, inputs), expected);
+        assert_eq!(run(program, Engine::Optimized, inputs), expected);
+    }
+
+    #[test]
+    fn test_matches_interpreter() {
+        const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        const COMPARE: &str = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+            1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+            999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+
+        assert_engines_agree(QUINE, &[]);
+        assert_engines_agree("104,1125899906842624,99", &[]);
+        for input in [7, 8, 9] {
+            assert_engines_agree(COMPARE, &[input]);
+        }
+        assert_engines_agree(COMPARE, &[]);
+    }
+
+    #[test]
+    fn test_far_jump() {
+        let far: usize = 1_000_000_000_000;
+        let mut memory: Memory = Memory::new(crate::intcode::Backend::Sparse);
+        memory.patch(&[
+            (0, 1105),
+            (1, 1),
+            (2, i64::try_from(far).unwrap()),
+            (far, 104),
+            (far + 1, 7),
+            (far + 2, 99),
+        ]);
+        let mut computer = Computer::new(memory);
+        computer.set_engine(Engine::Compiled);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(7)));
+        assert_eq!(computer.run_until_event(), Ok(RunState::Halted));
+    }
+
+    #[test]
+    fn test_self_modifying_code() {
+        let program = "104,0,1001,1,1,1,1008,1,3,14,1006,14,0,99,0";
+        assert_eq!(
+            run(program, Engine::Compiled, &[]),
+            (vec![0, 1, 2], Ok(RunState::Halted), 12)
+        );
+        assert_engines_agree(program, &[]);
+    }
+
+    #[test]
+    fn test_optimizations() {
+        let folding = "
+                    add  #2 #3 x
+                    mul  #4 #-1 y
+                    lt   #1 #2 z
+                    eq   x #5 w
+                    out  x
+                    out  y
+                    out  z
+                    out  w
+                    hlt
+            x:      data 0
+            y:      data 0
+            z:      data 0
+            w:      data 0
+        ";
+        let threading = "
+                    in   n
+            loop:   jz   n #done
+                    add  n #-1 n
+                    out  n
+                    jnz  #1 #first
+            first:  jz   #0 #second
+            second: jnz  #7 #loop
+            done:   jz   #1 #loop
+                    hlt
+            n:      data 0
+        ";
+        for program in [folding, threading] {
+            let program = assemble(program).unwrap();
+            assert_engines_agree(&program, &[3]);
+        }
+    }
+
+    #[test]
+    fn test_optimized_self_modifying_jumps() {
+        // goes round a loop through a chain of jumps, then rewrites a jump in the chain so that
+        // it leads out of the loop.
+        let program = assemble(
+            "
+            loop:   out  count
+                    add  count #1 count
+                    eq   count #3 flag
+                    jz   flag #via
+                    add  #exit #0 target
+            via:    jnz  #1 #hop
+            hop:    data 1105 1
+            target: data loop
+            exit:   out  #99
+                    hlt
+            count:  data 0
+            flag:   data 0
+            ",
+        )
+        .unwrap();
+        assert_eq!(run(&program, Engine::Optimized, &[]).0, vec![0, 1, 2, 99]);
+        assert_engines_agree(&program, &[]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_engines_agree("104,1,42", &[]);
+        assert_engines_agree("1101,1,1,-1,99", &[]);
+        assert_engines_agree("1105,1,-5", &[]);
+        assert_engines_agree("204,-1,99", &[]);
+        assert_engines_agree("11101,1,1,0,99", &[]);
+        assert_engines_agree("2006,0,-1,99", &[]);
+        assert_engines_agree("2106,1,-1,99", &[]);
+    }
+}
@@ -0,0 +1,872 @@
+use super::cache::DecodeCache;
+use super::compile::{Engine, Program, State};
+use super::hang::HangDetector;
+use super::profile::{Coverage, MemoryStats, Profile};
+#[cfg(feature = "std")]
+use super::trace::Tracer;
+#[cfg(feature = "std")]
+use super::watch::Watchpoints;
+use super::{
+    Cell, Decoding, ExecutionError, InputSource, Instruction, Memory, OutputSink, ParseMemoryError,
+};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{Receiver, Sender};
+#[cfg(feature = "std")]
+use std::thread::{self, JoinHandle};
+
+/// The reason a `Computer` stopped running.
+#[derive(Debug, PartialEq)]
+pub enum RunState<T = i64> {
+    /// The program is waiting on an input value and none are queued.
+    NeedsInput,
+    /// The program produced an output value.
+    Output(T),
+    /// The program has halted and will not run any further.
+    Halted,
+}
+
+/// The execution state of a `Computer` at one moment, which can later be restored to branch
+/// execution without re-running the program from the start.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot<T = i64> {
+    memory: Memory<T>,
+    instruction_pointer: usize,
+    relative_base: i64,
+    inputs: VecDeque<T>,
+    non_ascii: Option<T>,
+}
+
+impl<T> Snapshot<T> {
+    #[must_use]
+    pub fn memory(&self) -> &Memory<T> {
+        &self.memory
+    }
+
+    #[must_use]
+    pub fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    #[must_use]
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+}
+
+/// An Intcode machine which can be paused whenever it needs input or produces output, then
+/// resumed from exactly where it left off.
+///
+/// Computers are `Send` and `Sync`, so they can be moved into other threads or shared between
+/// them; watchpoint callbacks and trace writers must be `Send` for the same reason.
+#[derive(Clone, Debug)]
+pub struct Computer<T = i64> {
+    memory: Memory<T>,
+    /// The program as loaded, for `reset`. Copies of a computer share it.
+    initial: Arc<Memory<T>>,
+    instruction_pointer: usize,
+    relative_base: i64,
+    inputs: VecDeque<T>,
+    non_ascii: Option<T>,
+    #[cfg(feature = "std")]
+    watchpoints: Watchpoints<T>,
+    #[cfg(feature = "std")]
+    tracer: Option<Tracer>,
+    decoding: Decoding,
+    cache: DecodeCache,
+    engine: Engine,
+    program: Program<T>,
+    steps: usize,
+    step_limit: Option<usize>,
+    profile: Option<Profile>,
+    memory_stats: Option<MemoryStats>,
+    coverage: Option<Coverage>,
+    hang_detector: Option<HangDetector<T>>,
+}
+
+impl<T: Cell> Computer<T> {
+    #[must_use]
+    pub fn new(memory: Memory<T>) -> Self {
+        Self {
+            initial: Arc::new(memory.clone()),
+            memory,
+            instruction_pointer: 0,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+            non_ascii: None,
+            #[cfg(feature = "std")]
+            watchpoints: Watchpoints::default(),
+            #[cfg(feature = "std")]
+            tracer: None,
+            decoding: Decoding::default(),
+            cache: DecodeCache::default(),
+            engine: Engine::default(),
+            program: Program::default(),
+            steps: 0,
+            step_limit: None,
+            profile: None,
+            memory_stats: None,
+            coverage: None,
+            hang_detector: None,
+        }
+    }
+
+    #[must_use]
+    pub fn memory(&self) -> &Memory<T> {
+        &self.memory
+    }
+
+    /// Mutable access to memory. Any cached instruction decodings are discarded, since they may
+    /// no longer match what is stored.
+    pub fn memory_mut(&mut self) -> &mut Memory<T> {
+        self.cache.clear();
+        self.program.clear();
+        self.reset_hang_detector();
+        &mut self.memory
+    }
+
+    #[must_use]
+    pub fn into_memory(self) -> Memory<T> {
+        self.memory
+    }
+
+    #[must_use]
+    pub fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    #[must_use]
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    /// A listing of memory with the row at the instruction pointer marked; see `Memory::dump`.
+    #[must_use]
+    pub fn dump(&self) -> String {
+        self.memory.dump(Some(self.instruction_pointer))
+    }
+
+    /// Choose how unknown opcodes are treated. Decoding is strict unless this is called with
+    /// `Decoding::Lenient`.
+    pub fn set_decoding(&mut self, decoding: Decoding) {
+        self.decoding = decoding;
+        self.cache.clear();
+        self.program.clear();
+    }
+
+    /// Choose how instructions are executed. See `Engine` for the trade-offs.
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+        self.program = Program::new(engine);
+    }
+
+    /// Capture the memory, instruction pointer, relative base and pending I/O of this computer.
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot<T> {
+        Snapshot {
+            memory: self.memory.clone(),
+            instruction_pointer: self.instruction_pointer,
+            relative_base: self.relative_base,
+            inputs: self.inputs.clone(),
+            non_ascii: self.non_ascii.clone(),
+        }
+    }
+
+    /// Return to the state captured by `snapshot`. Watchpoints, tracing and the decoding mode
+    /// are configuration rather than state, so they are left as they are.
+    pub fn restore(&mut self, snapshot: &Snapshot<T>) {
+        self.memory.clone_from(&snapshot.memory);
+        self.instruction_pointer = snapshot.instruction_pointer;
+        self.relative_base = snapshot.relative_base;
+        self.inputs.clone_from(&snapshot.inputs);
+        self.non_ascii.clone_from(&snapshot.non_ascii);
+        self.cache.clear();
+        self.program.clear();
+        self.reset_hang_detector();
+    }
+
+    /// Return to the program as it was loaded when this computer was created, without parsing
+    /// it again: the instruction pointer and relative base go back to zero, queued input is
+    /// discarded and the count of instructions executed starts again. Configuration such as the
+    /// engine, step limit and diagnostics is kept.
+    pub fn reset(&mut self) {
+        self.memory.clone_from(&self.initial);
+        self.instruction_pointer = 0;
+        self.relative_base = 0;
+        self.inputs.clear();
+        self.non_ascii = None;
+        self.steps = 0;
+        self.cache.clear();
+        self.program.clear();
+        self.reset_hang_detector();
+    }
+
+    /// The number of instructions executed so far.
+    #[must_use]
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Stop with `ExecutionError::StepLimitExceeded` rather than execute more than `limit`
+    /// instructions in total, or run without limit if `None`. Waiting for input and halting do
+    /// not count as instructions executed.
+    pub fn set_step_limit(&mut self, limit: Option<usize>) {
+        self.step_limit = limit;
+    }
+
+    /// Queue a value to be consumed by the next input instruction.
+    pub fn push_input(&mut self, value: T) {
+        self.inputs.push_back(value);
+    }
+
+    /// The number of queued input values which have not been consumed yet.
+    #[must_use]
+    pub fn pending_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Take the most recent non-ASCII value encountered by one of the ASCII reading helpers.
+    pub fn take_non_ascii(&mut self) -> Option<T> {
+        self.non_ascii.take()
+    }
+
+    pub(super) fn set_non_ascii(&mut self, value: T) {
+        self.non_ascii = Some(value);
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn watchpoints_mut(&mut self) -> &mut Watchpoints<T> {
+        &mut self.watchpoints
+    }
+
+    #[cfg(feature = "std")]
+    pub(super) fn tracer_mut(&mut self) -> &mut Option<Tracer> {
+        &mut self.tracer
+    }
+
+    pub(super) fn profile_mut(&mut self) -> &mut Option<Profile> {
+        &mut self.profile
+    }
+
+    pub(super) fn memory_stats_ref(&self) -> Option<&MemoryStats> {
+        self.memory_stats.as_ref()
+    }
+
+    pub(super) fn memory_stats_mut(&mut self) -> &mut Option<MemoryStats> {
+        &mut self.memory_stats
+    }
+
+    pub(super) fn coverage_ref(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    pub(super) fn coverage_mut(&mut self) -> &mut Option<Coverage> {
+        &mut self.coverage
+    }
+
+    pub(super) fn hang_detector_mut(&mut self) -> &mut Option<HangDetector<T>> {
+        &mut self.hang_detector
+    }
+
+    fn reset_hang_detector(&mut self) {
+        if let Some(detector) = &mut self.hang_detector {
+            *detector = HangDetector::default();
+        }
+    }
+
+    /// Decode the instruction which will be executed next.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the instruction cannot be decoded.
+    pub fn next_instruction(&self) -> Result<Instruction, ExecutionError> {
+        self.memory
+            .decode_with(self.instruction_pointer, self.relative_base, self.decoding)
+    }
+
+    /// Execute a single instruction. Returns the event it caused, if any: `NeedsInput` and
+    /// `Halted` leave the instruction pointer where it is.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the instruction is invalid. The computer is left
+    /// unchanged, pointing at the failing instruction.
+    pub fn step(&mut self) -> Result<Option<RunState<T>>, ExecutionError> {
+        if self.runs_compiled() && !self.at_step_limit() {
+            return self.step_compiled();
+        }
+
+        let instruction = self.cache.decode(
+            &self.memory,
+            self.instruction_pointer,
+            self.relative_base,
+            self.decoding,
+        )?;
+        let pointer = self.instruction_pointer;
+        let mut event = None;
+
+        match instruction {
+            Instruction::Halt => {
+                // the program counts as having reached a halt, though it never steps past it.
+                if let Some(coverage) = &mut self.coverage {
+                    coverage.record(&instruction, pointer);
+                }
+                return Ok(Some(RunState::Halted));
+            }
+            Instruction::Input(_) if self.inputs.is_empty() => {
+                return Ok(Some(RunState::NeedsInput))
+            }
+            _ => {}
+        }
+        if self.at_step_limit() {
+            return Err(ExecutionError::StepLimitExceeded);
+        }
+
+        let jump = instruction
+            .get_jump_target(&self.memory)
+            .map_err(|_| ExecutionError::AddressOutOfRange(pointer))?;
+        let adjustment = match instruction {
+            Instruction::AdjustRelativeBase(register) => self
+                .memory
+                .read_register(register)
+                .to_i64()
+                .and_then(|value| self.relative_base.checked_add(value))
+                .ok_or(ExecutionError::AddressOutOfRange(pointer))
+                .map(Some)?,
+            _ => None,
+        };
+
+        #[cfg(feature = "std")]
+        let watched = (!self.watchpoints.is_empty())
+            .then(|| self.watchpoints.before(&instruction, &self.memory, pointer));
+        #[cfg(feature = "std")]
+        let traced = self
+            .tracer
+            .as_mut()
+            .and_then(|tracer| tracer.before(&instruction, &self.memory, pointer, self.steps));
+        let hang_written = self
+            .hang_detector
+            .as_ref()
+            .map(|_| HangDetector::before(&instruction, &self.memory));
+
+        if let Some(register) = instruction.get_written_register() {
+            self.cache.invalidate(register);
+            self.program.invalidate(register);
+        }
+        match instruction {
+            Instruction::Input(register) => {
+                if let Some(value) = self.inputs.pop_front() {
+                    self.memory.set_register(register, value);
+                }
+            }
+            Instruction::Output(register) => {
+                event = Some(RunState::Output(self.memory.read_register(register)));
+            }
+            _ => self.memory.execute(&instruction),
+        }
+        if let Some(relative_base) = adjustment {
+            self.relative_base = relative_base;
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(old) = watched {
+            self.watchpoints
+                .after(&instruction, &self.memory, pointer, old);
+        }
+        #[cfg(feature = "std")]
+        if let (Some(tracer), Some(line)) = (&self.tracer, traced) {
+            tracer.after(line, &instruction, &self.memory, jump);
+        }
+
+        self.instruction_pointer = jump.unwrap_or(pointer + instruction.get_register_change());
+        self.steps += 1;
+        if let Some(profile) = &mut self.profile {
+            profile.record(&instruction, pointer);
+        }
+        if let Some(stats) = &mut self.memory_stats {
+            stats.record(&instruction, pointer);
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record(&instruction, pointer);
+        }
+        if let (Some(detector), Some(written)) = (&mut self.hang_detector, hang_written) {
+            detector.after(
+                &instruction,
+                &self.memory,
+                written,
+                jump,
+                self.relative_base,
+            )?;
+        }
+        Ok(event)
+    }
+
+    fn at_step_limit(&self) -> bool {
+        self.step_limit.is_some_and(|limit| self.steps >= limit)
+    }
+
+    fn runs_compiled(&self) -> bool {
+        #[cfg(feature = "std")]
+        if !self.watchpoints.is_empty() || self.tracer.is_some() {
+            return false;
+        }
+        self.engine != Engine::Interpreted
+            && self.profile.is_none()
+            && self.memory_stats.is_none()
+            && self.coverage.is_none()
+            && self.hang_detector.is_none()
+    }
+
+    /// Execute a single compiled instruction. The decode cache is not kept up to date with
+    /// writes made by compiled code, so it is discarded.
+    fn step_compiled(&mut self) -> Result<Option<RunState<T>>, ExecutionError> {
+        self.cache.clear();
+        let mut state = State {
+            memory: &mut self.memory,
+            instruction_pointer: &mut self.instruction_pointer,
+            relative_base: &mut self.relative_base,
+            inputs: &mut self.inputs,
+            steps: &mut self.steps,
+            step_limit: self.step_limit,
+            written: None,
+        };
+        self.program.step(&mut state, self.decoding)
+    }
+
+    /// Run until the program produces output, needs input which has not been queued, or halts.
+    /// Calling this again after `NeedsInput` (having queued some input) or `Output` resumes the
+    /// program from where it stopped.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program executes an invalid instruction.
+    pub fn run_until_event(&mut self) -> Result<RunState<T>, ExecutionError> {
+        if self.runs_compiled() {
+            self.cache.clear();
+            let mut state = State {
+                memory: &mut self.memory,
+                instruction_pointer: &mut self.instruction_pointer,
+                relative_base: &mut self.relative_base,
+                inputs: &mut self.inputs,
+                steps: &mut self.steps,
+                step_limit: self.step_limit,
+                written: None,
+            };
+            if let Some(event) = self.program.run(&mut state, self.decoding)? {
+                return Ok(event);
+            }
+        }
+
+        loop {
+            if let Some(event) = self.step()? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Lazily run the program, yielding each value it outputs. The iterator ends when the program
+    /// halts, when it needs input which has not been queued, or when it fails; in the last case
+    /// `run_until_event` will report the error.
+    pub fn outputs(&mut self) -> impl Iterator<Item = T> + '_ {
+        core::iter::from_fn(move || match self.run_until_event() {
+            Ok(RunState::Output(value)) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Run until the program halts, taking values from `input` whenever the queued inputs run
+    /// out and passing every output to `output`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program executes an invalid instruction, or
+    /// `ExecutionError::InputExhausted` if `input` runs dry before the program halts.
+    pub fn run_with(
+        &mut self,
+        input: &mut impl InputSource<T>,
+        output: &mut impl OutputSink<T>,
+    ) -> Result<(), ExecutionError> {
+        loop {
+            match self.run_until_event()? {
+                RunState::Output(value) => output.write_output(value),
+                RunState::NeedsInput => match input.next_input() {
+                    Some(value) => self.push_input(value),
+                    None => return Err(ExecutionError::InputExhausted),
+                },
+                RunState::Halted => return Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Cell + Send + 'static> Computer<T> {
+    /// Run this computer on its own thread, reading input from `input` and sending output to
+    /// `output`. Chaining several computers together with channels forms a pipeline; the thread
+    /// finishes when the program halts, fails or `input` disconnects, handing back the computer
+    /// along with the result of the run.
+    #[must_use]
+    pub fn spawn(
+        mut self,
+        mut input: Receiver<T>,
+        mut output: Sender<T>,
+    ) -> JoinHandle<(Self, Result<(), ExecutionError>)> {
+        thread::spawn(move || {
+            let result = self.run_with(&mut input, &mut output);
+            (self, result)
+        })
+    }
+}
+
+impl<T: Cell> FromStr for Computer<T> {
+    type Err = ParseMemoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::{Engine, IterInput};
+
+    fn computer(program: &str) -> Computer {
+        program.parse().expect("Could not parse program")
+    }
+
+    fn run_with_inputs(program: &str, inputs: &[i64]) -> Vec<i64> {
+        let mut computer = computer(program);
+        for input in inputs {
+            computer.push_input(*input);
+        }
+
+        let mut outputs = Vec::new();
+        while let Ok(RunState::Output(value)) = computer.run_until_event() {
+            outputs.push(value);
+        }
+        outputs
+    }
+
+    #[test]
+    fn test_needs_input_then_resumes() {
+        let mut computer = computer("3,0,4,0,99");
+        assert_eq!(computer.run_until_event(), Ok(RunState::NeedsInput));
+        assert_eq!(computer.run_until_event(), Ok(RunState::NeedsInput));
+
+        computer.push_input(42);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(42)));
+        assert_eq!(computer.run_until_event(), Ok(RunState::Halted));
+        assert_eq!(computer.run_until_event(), Ok(RunState::Halted));
+    }
+
+    #[test]
+    fn test_outputs() {
+        let mut computer = computer("104,1,104,2,104,3,104,4,104,5,104,6,99");
+        let tiles: Vec<Vec<i64>> = computer
+            .outputs()
+            .collect::<Vec<_>>()
+            .chunks(3)
+            .map(<[i64]>::to_vec)
+            .collect();
+        assert_eq!(tiles, [[1, 2, 3], [4, 5, 6]]);
+    }
+
+    #[test]
+    fn test_outputs_is_lazy() {
+        let mut computer = computer("104,1,3,0,104,2,99");
+        assert_eq!(computer.outputs().next(), Some(1));
+        assert_eq!(computer.outputs().next(), None);
+
+        computer.push_input(0);
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn test_run_with_vec_output() {
+        let mut computer = computer("3,0,3,1,4,1,4,0,99");
+        let mut input = VecDeque::from([7, 8]);
+        let mut output = Vec::new();
+        assert_eq!(computer.run_with(&mut input, &mut output), Ok(()));
+        assert_eq!(output, [8, 7]);
+    }
+
+    #[test]
+    fn test_run_with_exhausted_input() {
+        let mut computer = computer("3,0,3,1,99");
+        let mut input = IterInput([1].into_iter());
+        let mut output = Vec::new();
+        assert_eq!(
+            computer.run_with(&mut input, &mut output),
+            Err(ExecutionError::InputExhausted)
+        );
+    }
+
+    #[test]
+    fn test_run_with_closures() {
+        let mut computer = computer("3,0,4,0,3,0,4,0,99");
+        let mut next = 10;
+        let mut total = 0;
+        let state = computer.run_with(
+            &mut || {
+                next += 1;
+                Some(next)
+            },
+            &mut |value| total += value,
+        );
+        assert_eq!(state, Ok(()));
+        assert_eq!(total, 23);
+    }
+
+    #[test]
+    fn test_spawn_feedback_loop() {
+        let program = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,\
+            6,99,0,0,5";
+        let phases = [9, 8, 7, 6, 5];
+
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            phases.iter().map(|_| std::sync::mpsc::channel()).unzip();
+        for (sender, phase) in senders.iter().zip(phases) {
+            sender.send(phase).unwrap();
+        }
+        senders[0].send(0).unwrap();
+
+        let (result_tx, result_rx) = std::sync::mpsc::channel();
+        let mut outputs = senders.iter().skip(1).cloned().collect::<Vec<_>>();
+        outputs.push(result_tx);
+        let handles: Vec<_> = receivers
+            .into_iter()
+            .zip(outputs)
+            .map(|(input, output)| computer(program).spawn(input, output))
+            .collect();
+
+        // feed the last amplifier's output back into the first until the loop halts.
+        let mut last = None;
+        while let Ok(value) = result_rx.recv() {
+            last = Some(value);
+            let _ = senders[0].send(value);
+        }
+        drop(senders);
+        for handle in handles {
+            let (_, result) = handle.join().unwrap();
+            assert_eq!(result, Ok(()));
+        }
+        assert_eq!(last, Some(139_629_729));
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Computer>();
+        assert_send_sync::<Computer<i128>>();
+        assert_send_sync::<Snapshot<i64>>();
+        assert_send_sync::<Memory>();
+        assert_send_sync::<crate::intcode::Debugger>();
+        assert_send_sync::<crate::intcode::ScreenBuffer>();
+        assert_send_sync::<crate::intcode::network::Network>();
+        assert_send_sync::<IterInput<std::vec::IntoIter<i64>>>();
+    }
+
+    #[test]
+    fn test_concurrent_computers() {
+        let program = "3,9,8,9,10,9,4,9,99,-1,8";
+        let mut template = computer(program);
+        let reads = std::sync::Arc::new(std::sync::Mutex::new(0));
+        let counter = reads.clone();
+        template.on_read(9, move |_| *counter.lock().unwrap() += 1);
+
+        // every thread borrows the same template and runs its own clone of it.
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|input| {
+                    let template = &template;
+                    scope.spawn(move || {
+                        let mut computer = template.clone();
+                        computer.push_input(input + 4);
+                        computer.outputs().collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let expected: Vec<_> = (0..8)
+            .map(|input| run_with_inputs(program, &[input + 4]))
+            .collect();
+        assert_eq!(results, expected);
+        assert_eq!(*reads.lock().unwrap(), 16);
+    }
+
+    #[test]
+    fn test_errors_leave_computer_unchanged() {
+        let mut computer = computer("104,1,1,-1,0,0,99");
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(1)));
+        assert_eq!(
+            computer.run_until_event(),
+            Err(ExecutionError::AddressOutOfRange(2))
+        );
+        assert_eq!(computer.instruction_pointer(), 2);
+
+        let mut jumping = self::computer("104,1,1105,1,-5");
+        assert_eq!(jumping.run_until_event(), Ok(RunState::Output(1)));
+        assert_eq!(
+            jumping.run_until_event(),
+            Err(ExecutionError::AddressOutOfRange(2))
+        );
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut computer = computer("3,13,1,13,13,13,4,13,109,5,1105,1,0,0");
+        computer.push_input(3);
+        computer.push_input(10);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(6)));
+
+        let snapshot = computer.snapshot();
+        assert_eq!(snapshot.instruction_pointer(), 8);
+        assert_eq!(snapshot.memory().read_register(13), 6);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(20)));
+        assert_eq!(computer.relative_base(), 5);
+
+        computer.restore(&snapshot);
+        assert_eq!(computer.relative_base(), 0);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(20)));
+
+        computer.restore(&snapshot);
+        computer.inputs.clear();
+        computer.push_input(1);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(2)));
+        assert_eq!(computer.run_until_event(), Ok(RunState::NeedsInput));
+    }
+
+    #[test]
+    fn test_reset() {
+        // adds its two inputs, storing the sum over the first.
+        let mut computer = computer("3,11,3,12,1,11,12,11,4,11,99,0,0");
+        computer.set_step_limit(Some(5));
+        for (a, b) in [(2, 3), (5, 1)] {
+            computer.reset();
+            computer.push_input(a);
+            computer.push_input(b);
+            assert_eq!(computer.run_until_event(), Ok(RunState::Output(a + b)));
+            assert_eq!(computer.steps(), 4);
+        }
+
+        computer.push_input(7);
+        computer.reset();
+        assert_eq!(computer.pending_inputs(), 0);
+        assert_eq!(computer.instruction_pointer(), 0);
+        assert_eq!(computer.memory().read_register(11), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_serde() {
+        let mut original = computer("3,13,1,13,13,13,4,13,109,5,1105,1,0,0");
+        original.push_input(3);
+        original.push_input(10);
+        assert_eq!(original.run_until_event(), Ok(RunState::Output(6)));
+
+        let json = serde_json::to_string(&original.snapshot()).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        let mut restored = computer("99");
+        restored.restore(&snapshot);
+        assert_eq!(restored.run_until_event(), Ok(RunState::Output(20)));
+    }
+
+    #[test]
+    fn test_self_modifying_code() {
+        let mut computer = computer("104,0,1001,1,1,1,1008,1,3,14,1006,14,0,99,0");
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        for engine in [Engine::Interpreted, Engine::Compiled, Engine::Optimized] {
+            let mut looping = computer("1105,1,0");
+            looping.set_engine(engine);
+            looping.set_step_limit(Some(1000));
+            assert_eq!(
+                looping.run_until_event(),
+                Err(ExecutionError::StepLimitExceeded)
+            );
+            assert_eq!(looping.steps(), 1000);
+
+            let mut halting = computer("104,1,104,2,99");
+            halting.set_engine(engine);
+            halting.set_step_limit(Some(2));
+            assert_eq!(halting.outputs().collect::<Vec<_>>(), [1, 2]);
+            assert_eq!(halting.run_until_event(), Ok(RunState::Halted));
+        }
+    }
+
+    #[test]
+    fn test_unknown_opcode() {
+        let mut computer = computer("104,1,0");
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(1)));
+        assert_eq!(
+            computer.run_until_event(),
+            Err(ExecutionError::InvalidOpcode(2))
+        );
+
+        computer.set_decoding(Decoding::Lenient);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Halted));
+    }
+
+    #[test]
+    fn test_immediate_mode_and_negative_values() {
+        let mut computer = computer("1101,100,-1,4,0");
+        assert_eq!(computer.run_until_event(), Ok(RunState::Halted));
+        assert_eq!(computer.memory().read_register(4), 99);
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let equal_to_8 = "3,9,8,9,10,9,4,9,99,-1,8";
+        assert_eq!(run_with_inputs(equal_to_8, &[8]), [1]);
+        assert_eq!(run_with_inputs(equal_to_8, &[7]), [0]);
+
+        let less_than_8 = "3,3,1107,-1,8,3,4,3,99";
+        assert_eq!(run_with_inputs(less_than_8, &[5]), [1]);
+        assert_eq!(run_with_inputs(less_than_8, &[9]), [0]);
+    }
+
+    #[test]
+    fn test_jumps() {
+        let is_non_zero = "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9";
+        assert_eq!(run_with_inputs(is_non_zero, &[0]), [0]);
+        assert_eq!(run_with_inputs(is_non_zero, &[3]), [1]);
+
+        let compare_to_8 = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,\
+            1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+        assert_eq!(run_with_inputs(compare_to_8, &[7]), [999]);
+        assert_eq!(run_with_inputs(compare_to_8, &[8]), [1000]);
+        assert_eq!(run_with_inputs(compare_to_8, &[9]), [1001]);
+    }
+
+    #[test]
+    fn test_relative_base_quine() {
+        let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let expected: Vec<i64> = quine.split(',').map(|v| v.parse().unwrap()).collect();
+        assert_eq!(run_with_inputs(quine, &[]), expected);
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        assert_eq!(
+            run_with_inputs("104,1125899906842624,99", &[]),
+            [1_125_899_906_842_624]
+        );
+        assert_eq!(
+            run_with_inputs("1102,34915192,34915192,7,4,7,99,0", &[]),
+            [1_219_070_632_396_864]
+        );
+    }
+}
@@ -0,0 +1,337 @@
+/// Static analysis of a program's control flow: the code reachable from address 0 is split into
+/// basic blocks joined by the jumps between them, which can be drawn with Graphviz.
+///
+/// Only immediate-mode jump targets can be followed. A jump whose target is read from memory is
+/// recorded as indirect; in compiled programs these are mostly returns from functions. So that
+/// the code after a call is not lost, an unconditional jump is treated as a call if the
+/// instruction before it stores the address following the jump (the usual way of pushing a
+/// return address), and that address is explored too.
+use super::asm::disassemble;
+use super::{Cell, Memory};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Where control can go after the last instruction of a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    /// Running on into the block which starts at the next address.
+    Fallthrough(usize),
+    /// A jump to an immediate-mode target.
+    Jump(usize),
+    /// A jump whose target is read from memory, so cannot be known in advance.
+    Indirect,
+    /// The return from a call, to the address after the jump which made it.
+    AfterCall(usize),
+}
+
+impl Edge {
+    #[must_use]
+    pub fn target(self) -> Option<usize> {
+        match self {
+            Edge::Fallthrough(target) | Edge::Jump(target) | Edge::AfterCall(target) => {
+                Some(target)
+            }
+            Edge::Indirect => None,
+        }
+    }
+}
+
+/// A run of instructions which is only ever entered at its start and left at its end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    start: usize,
+    /// The address and disassembly of each instruction, or of the cell which could not be
+    /// decoded if the block ends with one.
+    instructions: Vec<(usize, String)>,
+    edges: Vec<Edge>,
+}
+
+impl Block {
+    #[must_use]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    #[must_use]
+    pub fn instructions(&self) -> &[(usize, String)] {
+        &self.instructions
+    }
+
+    #[must_use]
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+}
+
+/// A decoded instruction, or an undecodable cell.
+struct Decoded {
+    text: String,
+    length: usize,
+    /// Where control goes other than on to the next instruction.
+    edges: Vec<Edge>,
+    falls_through: bool,
+    immediates: Vec<i64>,
+}
+
+fn decode<T: Cell>(memory: &Memory<T>, pos: usize) -> Decoded {
+    let values: Vec<i64> = (pos..pos + 4)
+        .map_while(|address| memory.read_register(address).to_i64())
+        .collect();
+    let Some((text, length)) = disassemble(&values) else {
+        return Decoded {
+            text: format!("{} ; invalid", memory.read_register(pos)),
+            length: 1,
+            edges: Vec::new(),
+            falls_through: false,
+            immediates: Vec::new(),
+        };
+    };
+
+    let opcode = values[0];
+    let mode = |ix: u32| opcode / 10_i64.pow(ix + 1) % 10;
+    let immediates = (1..length)
+        .filter(|ix| mode(u32::try_from(*ix).unwrap_or_default()) == 1)
+        .map(|ix| values[ix])
+        .collect();
+    let (edges, falls_through) = match opcode % 100 {
+        99 => (Vec::new(), false),
+        jump @ (5 | 6) => {
+            let target = match usize::try_from(values[2]) {
+                Ok(target) if mode(2) == 1 => Edge::Jump(target),
+                _ => Edge::Indirect,
+            };
+            match (mode(1) == 1).then(|| (values[1] != 0) == (jump == 5)) {
+                Some(true) => (Vec::from([target]), false),
+                Some(false) => (Vec::new(), true),
+                None => (Vec::from([target]), true),
+            }
+        }
+        _ => (Vec::new(), true),
+    };
+    Decoded {
+        text,
+        length,
+        edges,
+        falls_through,
+        immediates,
+    }
+}
+
+/// The basic blocks of a program, keyed by their first address.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    blocks: BTreeMap<usize, Block>,
+}
+
+impl ControlFlowGraph {
+    /// Find the code reachable from address 0 and split it into blocks. The contents of memory
+    /// are taken as they are, so code which is modified while the program runs is analysed in
+    /// its initial form.
+    #[must_use]
+    pub fn analyze<T: Cell>(memory: &Memory<T>) -> Self {
+        let mut decoded: BTreeMap<usize, Decoded> = BTreeMap::new();
+        let mut leaders = BTreeSet::from([0]);
+        let mut queue = Vec::from([0]);
+
+        while let Some(start) = queue.pop() {
+            let mut pos = start;
+            let mut previous: Vec<i64> = Vec::new();
+            while !decoded.contains_key(&pos) {
+                let mut instruction = decode(memory, pos);
+                let next = pos + instruction.length;
+                let returns_to_next =
+                    i64::try_from(next).is_ok_and(|next| previous.contains(&next));
+                if !instruction.falls_through
+                    && returns_to_next
+                    && matches!(instruction.edges[..], [Edge::Jump(_) | Edge::Indirect])
+                {
+                    instruction.edges.push(Edge::AfterCall(next));
+                }
+
+                for target in instruction.edges.iter().filter_map(|edge| edge.target()) {
+                    leaders.insert(target);
+                    queue.push(target);
+                }
+                if !instruction.edges.is_empty() && instruction.falls_through {
+                    leaders.insert(next);
+                }
+
+                let falls_through = instruction.falls_through;
+                previous = core::mem::take(&mut instruction.immediates);
+                decoded.insert(pos, instruction);
+                if !falls_through {
+                    break;
+                }
+                pos = next;
+            }
+        }
+
+        let blocks = leaders
+            .iter()
+            .filter(|leader| decoded.contains_key(leader))
+            .map(|&start| {
+                let mut block = Block {
+                    start,
+                    instructions: Vec::new(),
+                    edges: Vec::new(),
+                };
+                let mut pos = start;
+                while let Some(instruction) = decoded.get(&pos) {
+                    block.instructions.push((pos, instruction.text.clone()));
+                    let next = pos + instruction.length;
+                    let ends = !instruction.edges.is_empty()
+                        || !instruction.falls_through
+                        || leaders.contains(&next);
+                    if ends {
+                        block.edges.clone_from(&instruction.edges);
+                        if instruction.falls_through {
+                            block.edges.push(Edge::Fallthrough(next));
+                        }
+                        break;
+                    }
+                    pos = next;
+                }
+                (start, block)
+            })
+            .collect();
+        Self { blocks }
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.values()
+    }
+
+    /// The block starting at `start`, if there is one.
+    #[must_use]
+    pub fn block(&self, start: usize) -> Option<&Block> {
+        self.blocks.get(&start)
+    }
+
+    /// The graph in Graphviz's DOT language, with a node per block listing its instructions.
+    /// Jumps taken are labelled, returns from calls are dashed and indirect jumps lead to a
+    /// single node marked `?`.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot =
+            String::from("digraph intcode {\n    node [shape=box, fontname=\"monospace\"];\n");
+        let mut indirect = false;
+        for block in self.blocks() {
+            let label: String = block
+                .instructions
+                .iter()
+                .map(|(pos, text)| format!("{pos:04}: {text}\\l"))
+                .collect();
+            dot.push_str(&format!("    b{} [label=\"{label}\"];\n", block.start));
+
+            for edge in &block.edges {
+                let (target, attributes) = match edge {
+                    Edge::Fallthrough(target) => (format!("b{target}"), ""),
+                    Edge::Jump(target) => (format!("b{target}"), " [label=\"jump\"]"),
+                    Edge::AfterCall(target) => (format!("b{target}"), " [style=dashed]"),
+                    Edge::Indirect => {
+                        indirect = true;
+                        ("indirect".into(), " [style=dotted]")
+                    }
+                };
+                dot.push_str(&format!("    b{} -> {target}{attributes};\n", block.start));
+            }
+        }
+        if indirect {
+            dot.push_str("    indirect [label=\"?\", shape=circle];\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    fn graph(source: &str) -> ControlFlowGraph {
+        ControlFlowGraph::analyze(&assemble(source).unwrap().parse::<Memory>().unwrap())
+    }
+
+    #[test]
+    fn test_loop() {
+        // counts down, then outputs and halts.
+        let graph = graph(
+            "
+                    in   n
+            loop:   add  n #-1 n
+                    jnz  n #loop
+                    out  n
+                    hlt
+            n:      data 0
+            ",
+        );
+        let starts: Vec<usize> = graph.blocks().map(Block::start).collect();
+        assert_eq!(starts, [0, 2, 9]);
+        assert_eq!(graph.block(0).unwrap().edges(), [Edge::Fallthrough(2)]);
+        assert_eq!(
+            graph.block(2).unwrap().edges(),
+            [Edge::Jump(2), Edge::Fallthrough(9)]
+        );
+        assert_eq!(
+            graph.block(9).unwrap().instructions(),
+            [(9, "out  12".into()), (11, "hlt".into())]
+        );
+        assert!(graph.block(9).unwrap().edges().is_empty());
+    }
+
+    #[test]
+    fn test_calls() {
+        let graph = graph(
+            "
+                    arb  #100
+                    add  #back #0 @0
+                    jnz  #1 #double
+            back:   out  value
+                    hlt
+            double: mul  value #2 value
+                    jz   #0 @0
+            value:  data 21
+            ",
+        );
+        let starts: Vec<usize> = graph.blocks().map(Block::start).collect();
+        assert_eq!(starts, [0, 9, 12]);
+        assert_eq!(
+            graph.block(0).unwrap().edges(),
+            [Edge::Jump(12), Edge::AfterCall(9)]
+        );
+        assert_eq!(graph.block(12).unwrap().edges(), [Edge::Indirect]);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph intcode {\n"));
+        assert!(dot.contains(
+            "    b0 [label=\"0000: arb  #100\\l0002: add  #9 #0 @0\\l0006: jnz  #1 #12\\l\"];\n"
+        ));
+        assert!(dot.contains("    b0 -> b12 [label=\"jump\"];\n    b0 -> b9 [style=dashed];\n"));
+        assert!(dot.contains("    b12 -> indirect [style=dotted];\n"));
+        assert!(dot.ends_with("    indirect [label=\"?\", shape=circle];\n}\n"));
+    }
+
+    #[test]
+    fn test_constant_conditions_and_invalid_code() {
+        // the first jump is never taken, and the second always is, into a cell which cannot
+        // be decoded.
+        let graph = graph(
+            "
+                    jz   #1 #0
+                    jnz  #1 #bad
+                    hlt
+            bad:    data 42
+            ",
+        );
+        let starts: Vec<usize> = graph.blocks().map(Block::start).collect();
+        assert_eq!(starts, [0, 7]);
+        assert_eq!(graph.block(0).unwrap().edges(), [Edge::Jump(7)]);
+        assert_eq!(
+            graph.block(7).unwrap().instructions(),
+            [(7, "42 ; invalid".into())]
+        );
+    }
+}
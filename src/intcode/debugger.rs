@@ -0,0 +1,488 @@
+/// An interactive debugger which wraps a `Computer` with breakpoints and a command prompt.
+use super::{Cell, Computer, ExecutionError, OutputSink, RunState};
+use signal_hook::consts::SIGINT;
+use signal_hook::SigId;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+const HELP: &str = "\
+commands:
+  s, step [n]          execute the next n instructions (default 1)
+  c, continue          run until a breakpoint, input is needed or the program halts
+  b, break [addr]      set a breakpoint at addr, or list breakpoints
+  d, delete <addr>     remove the breakpoint at addr
+  r, regs              show the instruction pointer, relative base and next instruction
+  m, mem <addr> [n]    show n cells of memory starting at addr (default 8)
+  l, list              show all of memory, decoding instructions and marking the next one
+  set <addr> <value>   write value to memory at addr
+  i, input <values..>  queue input values
+  q, quit              leave the debugger
+  a, abort             leave the debugger and stop the program";
+
+/// Returns true if the `--debug-intcode` flag was passed to the current binary.
+#[must_use]
+pub fn debug_requested() -> bool {
+    std::env::args().any(|x| x == "--debug-intcode")
+}
+
+/// How many `Interrupts` are alive, and the flag which lets Ctrl-C end the process while there
+/// are none. Removing a signal action does not bring back the default one, so once Ctrl-C has
+/// been caught this shutdown action stands in for it.
+struct Catching {
+    count: usize,
+    released: Arc<AtomicBool>,
+}
+
+static CATCHING: Mutex<Option<Catching>> = Mutex::new(None);
+
+fn catching() -> MutexGuard<'static, Option<Catching>> {
+    CATCHING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Ctrl-C caught by `catch_interrupts`. Dropping this removes its handlers, and once no others
+/// are alive Ctrl-C ends the process straight away again.
+pub struct Interrupts {
+    flag: Arc<AtomicBool>,
+    handlers: [SigId; 2],
+}
+
+impl Interrupts {
+    /// The flag which is set each time Ctrl-C is pressed.
+    #[must_use]
+    pub fn flag(&self) -> &Arc<AtomicBool> {
+        &self.flag
+    }
+}
+
+impl Drop for Interrupts {
+    fn drop(&mut self) {
+        for handler in self.handlers {
+            signal_hook::low_level::unregister(handler);
+        }
+        if let Some(catching) = catching().as_mut() {
+            catching.count -= 1;
+            if catching.count == 0 {
+                catching.released.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Catch Ctrl-C rather than letting it end the process, until the returned `Interrupts` is
+/// dropped. Its flag is set each time Ctrl-C is pressed; pressing it again before the flag has
+/// been cleared ends the process as usual, so a program which never checks the flag can still
+/// be stopped.
+///
+/// # Errors
+///
+/// Will return `io::Error` if the signal handler cannot be installed.
+pub fn catch_interrupts() -> io::Result<Interrupts> {
+    let mut state = catching();
+    let released = match state.as_ref() {
+        Some(catching) => Arc::clone(&catching.released),
+        None => {
+            let released = Arc::new(AtomicBool::new(true));
+            signal_hook::flag::register_conditional_shutdown(SIGINT, 130, Arc::clone(&released))?;
+            *state = Some(Catching {
+                count: 0,
+                released: Arc::clone(&released),
+            });
+            released
+        }
+    };
+
+    let flag = Arc::new(AtomicBool::new(false));
+    let shutdown =
+        signal_hook::flag::register_conditional_shutdown(SIGINT, 130, Arc::clone(&flag))?;
+    let set_flag = signal_hook::flag::register(SIGINT, Arc::clone(&flag)).inspect_err(|_| {
+        signal_hook::low_level::unregister(shutdown);
+    })?;
+    if let Some(catching) = state.as_mut() {
+        catching.count += 1;
+    }
+    released.store(false, Ordering::Relaxed);
+    Ok(Interrupts {
+        flag,
+        handlers: [shutdown, set_flag],
+    })
+}
+
+/// Why a debugged program stopped running.
+#[derive(Debug, PartialEq)]
+pub enum Stop {
+    Breakpoint(usize),
+    /// The interrupt flag was set while running; see `Debugger::break_on_interrupt`.
+    Interrupted(usize),
+    NeedsInput,
+    Halted,
+    Error(ExecutionError),
+}
+
+pub struct Debugger<T = i64> {
+    computer: Computer<T>,
+    breakpoints: BTreeSet<usize>,
+    interrupt: Option<Arc<AtomicBool>>,
+    aborted: bool,
+}
+
+impl<T: Cell> Debugger<T> {
+    #[must_use]
+    pub fn new(computer: Computer<T>) -> Self {
+        Self {
+            computer,
+            breakpoints: BTreeSet::new(),
+            interrupt: None,
+            aborted: false,
+        }
+    }
+
+    #[must_use]
+    pub fn computer(&self) -> &Computer<T> {
+        &self.computer
+    }
+
+    #[must_use]
+    pub fn into_computer(self) -> Computer<T> {
+        self.computer
+    }
+
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.remove(&address)
+    }
+
+    /// Stop running whenever `flag` is set (see `catch_interrupts`), clearing it again.
+    pub fn break_on_interrupt(&mut self, flag: Arc<AtomicBool>) {
+        self.interrupt = Some(flag);
+    }
+
+    /// Whether the user left the debugger with the `abort` command.
+    #[must_use]
+    pub fn aborted(&self) -> bool {
+        self.aborted
+    }
+
+    /// Run until a breakpoint is reached, the program needs input or it halts, or until
+    /// interrupted. Execution always moves at least one instruction, so continuing from a
+    /// breakpoint does not stop there again immediately.
+    pub fn continue_execution(&mut self, output: &mut impl OutputSink<T>) -> Stop {
+        // an interrupt from before this run, such as at the prompt, is ignored.
+        if let Some(flag) = &self.interrupt {
+            flag.store(false, Ordering::Relaxed);
+        }
+        loop {
+            match self.computer.step() {
+                Ok(Some(RunState::Output(value))) => output.write_output(value),
+                Ok(Some(RunState::NeedsInput)) => return Stop::NeedsInput,
+                Ok(Some(RunState::Halted)) => return Stop::Halted,
+                Ok(None) => {}
+                Err(e) => return Stop::Error(e),
+            }
+
+            let pointer = self.computer.instruction_pointer();
+            if self.breakpoints.contains(&pointer) {
+                return Stop::Breakpoint(pointer);
+            }
+            if self
+                .interrupt
+                .as_ref()
+                .is_some_and(|flag| flag.swap(false, Ordering::Relaxed))
+            {
+                return Stop::Interrupted(pointer);
+            }
+        }
+    }
+
+    fn describe_next(&self) -> String {
+        match self.computer.next_instruction() {
+            Ok(instruction) => instruction.describe(self.computer.memory()),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn print_registers(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "ip={} rb={} next: {}",
+            self.computer.instruction_pointer(),
+            self.computer.relative_base(),
+            self.describe_next()
+        )
+    }
+
+    fn print_stop(stop: &Stop, out: &mut impl Write) -> io::Result<()> {
+        match stop {
+            Stop::Breakpoint(address) => writeln!(out, "breakpoint at {address}"),
+            Stop::Interrupted(address) => writeln!(out, "interrupted at {address}"),
+            Stop::NeedsInput => writeln!(out, "waiting for input"),
+            Stop::Halted => writeln!(out, "halted"),
+            Stop::Error(e) => writeln!(out, "error: {e}"),
+        }
+    }
+
+    /// Carry out one debugger command, writing any results to `out`. Returns `false` once the
+    /// user has asked to quit.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if writing to `out` fails.
+    pub fn execute(&mut self, command: &str, out: &mut impl Write) -> io::Result<bool> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        let number = |ix: usize| args.get(ix).and_then(|arg| arg.parse::<usize>().ok());
+
+        match name {
+            "" => {}
+            "s" | "step" => {
+                for _ in 0..number(0).unwrap_or(1) {
+                    let description = self.describe_next();
+                    let pointer = self.computer.instruction_pointer();
+                    let stop = match self.computer.step() {
+                        Ok(Some(RunState::Output(value))) => {
+                            writeln!(out, "{pointer}: {description}")?;
+                            writeln!(out, "output: {value}")?;
+                            continue;
+                        }
+                        Ok(None) => {
+                            writeln!(out, "{pointer}: {description}")?;
+                            continue;
+                        }
+                        Ok(Some(RunState::NeedsInput)) => Stop::NeedsInput,
+                        Ok(Some(RunState::Halted)) => Stop::Halted,
+                        Err(e) => Stop::Error(e),
+                    };
+                    Self::print_stop(&stop, out)?;
+                    break;
+                }
+            }
+            "c" | "continue" => {
+                let mut outputs = Vec::new();
+                let stop = self.continue_execution(&mut outputs);
+                for value in outputs {
+                    writeln!(out, "output: {value}")?;
+                }
+                Self::print_stop(&stop, out)?;
+            }
+            "b" | "break" => match number(0) {
+                Some(address) => self.add_breakpoint(address),
+                None => {
+                    for address in &self.breakpoints {
+                        writeln!(out, "breakpoint at {address}")?;
+                    }
+                }
+            },
+            "d" | "delete" => {
+                if !number(0).is_some_and(|address| self.remove_breakpoint(address)) {
+                    writeln!(out, "no such breakpoint")?;
+                }
+            }
+            "r" | "regs" => self.print_registers(out)?,
+            "m" | "mem" => {
+                let start = number(0).unwrap_or(self.computer.instruction_pointer());
+                let cells: Vec<String> = (start..start + number(1).unwrap_or(8))
+                    .map(|address| self.computer.memory().read_register(address).to_string())
+                    .collect();
+                writeln!(out, "{start:04}: {}", cells.join(" "))?;
+            }
+            "l" | "list" => write!(out, "{}", self.computer.dump())?,
+            "set" => match (number(0), args.get(1).and_then(|arg| arg.parse().ok())) {
+                (Some(address), Some(value)) => {
+                    self.computer.memory_mut().set_register(address, value);
+                }
+                _ => writeln!(out, "usage: set <addr> <value>")?,
+            },
+            "i" | "input" => {
+                for arg in args {
+                    match arg.parse() {
+                        Ok(value) => self.computer.push_input(value),
+                        Err(_) => writeln!(out, "invalid input value: {arg}")?,
+                    }
+                }
+            }
+            "q" | "quit" => return Ok(false),
+            "a" | "abort" => {
+                self.aborted = true;
+                return Ok(false);
+            }
+            _ => writeln!(out, "{HELP}")?,
+        }
+
+        Ok(true)
+    }
+
+    /// Read commands from `input` until the user quits or the input ends.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if reading from `input` or writing to `out` fails.
+    pub fn repl(&mut self, input: impl BufRead, mut out: impl Write) -> io::Result<()> {
+        self.print_registers(&mut out)?;
+        write!(out, "(intcode) ")?;
+        out.flush()?;
+
+        for line in input.lines() {
+            if !self.execute(&line?, &mut out)? {
+                break;
+            }
+            write!(out, "(intcode) ")?;
+            out.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Cell> Computer<T> {
+    /// Hand this computer to an interactive debugger on stdin/stdout, returning it once the
+    /// user quits. If the user aborts, the process exits.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the terminal cannot be read from or written to.
+    #[must_use]
+    pub fn debug(self) -> Self {
+        let mut debugger = Debugger::new(self);
+        let interrupts = catch_interrupts().ok();
+        if let Some(interrupts) = &interrupts {
+            debugger.break_on_interrupt(Arc::clone(interrupts.flag()));
+        }
+        debugger
+            .repl(io::stdin().lock(), io::stdout())
+            .expect("could not communicate with terminal");
+        drop(interrupts);
+        if debugger.aborted() {
+            std::process::exit(130);
+        }
+        debugger.into_computer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debugger(program: &str) -> Debugger {
+        Debugger::new(program.parse().expect("Could not parse program"))
+    }
+
+    fn run_commands(debugger: &mut Debugger, commands: &str) -> String {
+        let mut out = Vec::new();
+        debugger
+            .repl(io::Cursor::new(commands), &mut out)
+            .expect("Could not run debugger");
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Whether Ctrl-C would end the process right now, without raising a real one.
+    fn released() -> bool {
+        catching()
+            .as_ref()
+            .is_some_and(|catching| catching.released.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn test_ctrl_c_released_on_drop() {
+        let interrupts = catch_interrupts().expect("Could not catch interrupts");
+        assert!(!released());
+
+        let handlers = interrupts.handlers;
+        drop(interrupts);
+        assert!(released());
+        assert!(handlers
+            .into_iter()
+            .all(|handler| !signal_hook::low_level::unregister(handler)));
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = debugger("1,0,0,0,104,7,99");
+        debugger.add_breakpoint(4);
+
+        let mut outputs = Vec::new();
+        assert_eq!(
+            debugger.continue_execution(&mut outputs),
+            Stop::Breakpoint(4)
+        );
+        assert!(outputs.is_empty());
+
+        assert_eq!(debugger.continue_execution(&mut outputs), Stop::Halted);
+        assert_eq!(outputs, [7]);
+    }
+
+    #[test]
+    fn test_step_and_inspect() {
+        let mut debugger = debugger("1,0,0,0,104,7,99");
+        let text = run_commands(&mut debugger, "s\nm 0 4\ns\nq\ns\n");
+        assert_eq!(
+            text,
+            [
+                "ip=0 rb=0 next: add [0]=1 [0]=1 [0]=1",
+                "(intcode) 0: add [0]=1 [0]=1 [0]=1",
+                "(intcode) 0000: 2 0 0 0",
+                "(intcode) 4: out [5]=7",
+                "output: 7",
+                "(intcode) ",
+            ]
+            .join("\n")
+        );
+        assert_eq!(debugger.computer().instruction_pointer(), 6);
+    }
+
+    #[test]
+    fn test_error_stops() {
+        let mut debugger = debugger("104,1,1,-1,0,0,99");
+        let mut outputs = Vec::new();
+        assert_eq!(
+            debugger.continue_execution(&mut outputs),
+            Stop::Error(ExecutionError::AddressOutOfRange(2))
+        );
+        assert_eq!(outputs, [1]);
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let mut debugger = debugger("1105,1,0");
+        let flag = Arc::new(AtomicBool::new(true));
+        debugger.break_on_interrupt(Arc::clone(&flag));
+
+        let interrupter = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            flag.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(
+            debugger.continue_execution(&mut Vec::new()),
+            Stop::Interrupted(0)
+        );
+        interrupter.join().unwrap();
+    }
+
+    #[test]
+    fn test_abort() {
+        let mut debugger = debugger("99");
+        run_commands(&mut debugger, "q\n");
+        assert!(!debugger.aborted());
+        run_commands(&mut debugger, "a\ns\n");
+        assert!(debugger.aborted());
+    }
+
+    #[test]
+    fn test_input_and_set() {
+        let mut debugger = debugger("3,9,1,9,10,9,4,9,99,0,5");
+        let text = run_commands(&mut debugger, "c\ni 3\nset 10 6\nc\n");
+        assert!(text.contains("waiting for input"));
+        assert!(text.contains("output: 9\nhalted"));
+    }
+
+    #[test]
+    fn test_list() {
+        let mut debugger = debugger("104,7,99");
+        let text = run_commands(&mut debugger, "s\nl\n");
+        assert!(text.ends_with("  0000: 104 7  out  #7\n> 0002: 99     hlt\n(intcode) "));
+    }
+}
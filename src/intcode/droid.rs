@@ -0,0 +1,271 @@
+/// Drives the day 15 repair droid, which moves one square at a time in response to movement
+/// commands and reports what it found, and maps out the area it can reach.
+use super::{Backend, Computer, ExecutionError, Memory, RunState};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+
+pub type Position = (i64, i64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+
+    fn command(self) -> i64 {
+        match self {
+            Direction::North => 1,
+            Direction::South => 2,
+            Direction::West => 3,
+            Direction::East => 4,
+        }
+    }
+
+    /// The position one step from `position` in this direction. North is towards negative y.
+    #[must_use]
+    pub fn step(self, (x, y): Position) -> Position {
+        match self {
+            Direction::North => (x, y - 1),
+            Direction::South => (x, y + 1),
+            Direction::West => (x - 1, y),
+            Direction::East => (x + 1, y),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Terrain {
+    Wall,
+    Open,
+    Oxygen,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DroidError {
+    Execution(ExecutionError),
+    /// The droid reported a status other than 0, 1 or 2.
+    UnexpectedStatus(i64),
+    /// The program stopped without reporting a status.
+    NoStatus,
+}
+
+impl Display for DroidError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DroidError::Execution(e) => write!(f, "{e}"),
+            DroidError::UnexpectedStatus(status) => write!(f, "unexpected status {status}"),
+            DroidError::NoStatus => write!(f, "the droid did not report a status"),
+        }
+    }
+}
+
+impl From<ExecutionError> for DroidError {
+    fn from(e: ExecutionError) -> Self {
+        DroidError::Execution(e)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Droid {
+    computer: Computer,
+}
+
+impl Droid {
+    /// Start the droid program. Its memory is paged so that snapshots of it are cheap.
+    #[must_use]
+    pub fn new(program: &Memory) -> Self {
+        Self {
+            computer: Computer::new(program.clone().with_backend(Backend::Paged)),
+        }
+    }
+
+    /// Try to move one step, returning what is in that direction. The droid only moves if it
+    /// is not a wall.
+    ///
+    /// # Errors
+    ///
+    /// Will return `DroidError` if the program fails or does not report a valid status.
+    pub fn try_move(&mut self, direction: Direction) -> Result<Terrain, DroidError> {
+        self.computer.push_input(direction.command());
+        match self.computer.run_until_event()? {
+            RunState::Output(0) => Ok(Terrain::Wall),
+            RunState::Output(1) => Ok(Terrain::Open),
+            RunState::Output(2) => Ok(Terrain::Oxygen),
+            RunState::Output(status) => Err(DroidError::UnexpectedStatus(status)),
+            RunState::NeedsInput | RunState::Halted => Err(DroidError::NoStatus),
+        }
+    }
+}
+
+/// The area discovered by the droid, with its starting point at `(0, 0)`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ShipMap {
+    tiles: HashMap<Position, Terrain>,
+    oxygen: Option<Position>,
+}
+
+impl ShipMap {
+    #[must_use]
+    pub fn get(&self, position: Position) -> Option<Terrain> {
+        self.tiles.get(&position).copied()
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = (Position, Terrain)> + '_ {
+        self.tiles
+            .iter()
+            .map(|(position, terrain)| (*position, *terrain))
+    }
+
+    /// Where the oxygen system is, if it was found.
+    #[must_use]
+    pub fn oxygen(&self) -> Option<Position> {
+        self.oxygen
+    }
+
+    /// The number of steps from `start` to every position which can be reached from it.
+    #[must_use]
+    pub fn distances_from(&self, start: Position) -> HashMap<Position, usize> {
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(position) = queue.pop_front() {
+            let distance = distances[&position];
+            for next in Direction::ALL.map(|direction| direction.step(position)) {
+                let open = matches!(self.get(next), Some(Terrain::Open | Terrain::Oxygen));
+                if open && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+}
+
+/// Map everything the droid can reach. Rather than walking the droid back after each dead end,
+/// every position visited keeps a snapshot of the droid standing there, and exploration resumes
+/// from those.
+///
+/// # Errors
+///
+/// Will return `DroidError` if the program fails or does not report a valid status.
+pub fn explore(program: &Memory) -> Result<ShipMap, DroidError> {
+    let mut droid = Droid::new(program);
+    let mut map = ShipMap {
+        tiles: HashMap::from([((0, 0), Terrain::Open)]),
+        oxygen: None,
+    };
+    let mut queue = VecDeque::from([((0, 0), droid.computer.snapshot())]);
+
+    while let Some((position, snapshot)) = queue.pop_front() {
+        for direction in Direction::ALL {
+            let next = direction.step(position);
+            if map.tiles.contains_key(&next) {
+                continue;
+            }
+
+            droid.computer.restore(&snapshot);
+            let terrain = droid.try_move(direction)?;
+            map.tiles.insert(next, terrain);
+            if terrain == Terrain::Oxygen {
+                map.oxygen = Some(next);
+            }
+            if terrain != Terrain::Wall {
+                queue.push_back((next, droid.computer.snapshot()));
+            }
+        }
+    }
+
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    /// A droid in this five-by-five maze, starting in the bottom-left open square:
+    ///
+    /// ```text
+    /// #####
+    /// #..O#
+    /// #.#.#
+    /// #...#
+    /// #####
+    /// ```
+    const MAZE: &str = "
+        loop:   in   cmd
+                arb  cmd
+                add  @deltas #0 delta
+                mul  cmd #-1 neg
+                arb  neg
+                add  pos delta target
+                arb  target
+                add  @grid #0 tile
+                mul  target #-1 neg
+                arb  neg
+                jz   tile #wall
+                add  target #0 pos
+                eq   tile #2 found
+                jnz  found #oxygen
+                out  #1
+                jnz  #1 #loop
+        oxygen: out  #2
+                jnz  #1 #loop
+        wall:   out  #0
+                jnz  #1 #loop
+        cmd:    data 0
+        delta:  data 0
+        neg:    data 0
+        target: data 0
+        tile:   data 0
+        found:  data 0
+        pos:    data 16
+        deltas: data 0 -5 5 -1 1
+        grid:   data 0 0 0 0 0
+                data 0 1 1 2 0
+                data 0 1 0 1 0
+                data 0 1 1 1 0
+                data 0 0 0 0 0
+    ";
+
+    fn program() -> Memory {
+        assemble(MAZE).unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn test_try_move() {
+        let mut droid = Droid::new(&program());
+        assert_eq!(droid.try_move(Direction::South), Ok(Terrain::Wall));
+        assert_eq!(droid.try_move(Direction::East), Ok(Terrain::Open));
+        assert_eq!(droid.try_move(Direction::East), Ok(Terrain::Open));
+        assert_eq!(droid.try_move(Direction::North), Ok(Terrain::Open));
+        assert_eq!(droid.try_move(Direction::North), Ok(Terrain::Oxygen));
+    }
+
+    #[test]
+    fn test_explore() {
+        let map = explore(&program()).unwrap();
+        assert_eq!(map.oxygen(), Some((2, -2)));
+        assert_eq!(map.get((1, -1)), Some(Terrain::Wall));
+        assert_eq!(
+            map.tiles()
+                .filter(|(_, terrain)| *terrain != Terrain::Wall)
+                .count(),
+            8
+        );
+
+        assert_eq!(map.distances_from((0, 0))[&(2, -2)], 4);
+        assert_eq!(map.distances_from((2, -2)).values().max(), Some(&4));
+    }
+}
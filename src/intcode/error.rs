@@ -0,0 +1,52 @@
+use core::fmt::Display;
+
+/// A failure while executing an Intcode program. A variant with an address carries the address
+/// of the instruction which failed, not of the operand or target it was trying to use.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExecutionError {
+    /// The cell at this address does not hold a known opcode.
+    InvalidOpcode(usize),
+    /// A parameter of the instruction at this address has the given unknown mode.
+    InvalidMode(usize, i64),
+    /// The instruction at this address would write to an immediate-mode parameter.
+    WriteToImmediate(usize),
+    /// The instruction at this address refers to a negative or unrepresentable address, through
+    /// a parameter, a relative base adjustment or a jump target.
+    AddressOutOfRange(usize),
+    /// The program needed input and its input source had run dry.
+    InputExhausted,
+    /// The program halted before producing an output which was expected of it.
+    MissingOutput,
+    /// The program executed more instructions than it was allowed to.
+    StepLimitExceeded,
+    /// The program is looping through the instruction at this address, and will keep doing so
+    /// forever without any input or output.
+    InfiniteLoop(usize),
+    /// Every computer on a network is halted, or idle with nothing for the NAT to release, so
+    /// nothing more will happen.
+    NetworkStalled,
+}
+
+impl Display for ExecutionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ExecutionError::InvalidOpcode(address) => write!(f, "invalid opcode at {address}"),
+            ExecutionError::InvalidMode(address, mode) => {
+                write!(f, "invalid parameter mode {mode} at {address}")
+            }
+            ExecutionError::WriteToImmediate(address) => {
+                write!(f, "write to immediate-mode parameter at {address}")
+            }
+            ExecutionError::AddressOutOfRange(address) => {
+                write!(f, "address out of range at {address}")
+            }
+            ExecutionError::InputExhausted => write!(f, "input exhausted"),
+            ExecutionError::MissingOutput => write!(f, "halted without producing output"),
+            ExecutionError::StepLimitExceeded => write!(f, "step limit exceeded"),
+            ExecutionError::InfiniteLoop(address) => write!(f, "infinite loop at {address}"),
+            ExecutionError::NetworkStalled => write!(f, "network stalled"),
+        }
+    }
+}
+
+impl core::error::Error for ExecutionError {}
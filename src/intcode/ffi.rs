@@ -0,0 +1,209 @@
+/// A C ABI for the Intcode VM, so that programs written in other languages (visualizations,
+/// mostly) can drive the same machine as the solutions. The declarations are in
+/// `include/intcode.h`. To get a library which can be linked from C, build with
+///
+/// ```text
+/// cargo rustc --lib --release --features ffi --crate-type cdylib
+/// ```
+///
+/// A machine is created from a program string with `intcode_new` and must be released with
+/// `intcode_free`. Values output by the program are queued inside the machine until taken with
+/// `intcode_pop_output`.
+use super::{Computer, RunState};
+use std::collections::VecDeque;
+use std::ffi::{c_char, CStr};
+
+/// What a machine did when asked to run.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntcodeStatus {
+    /// A single instruction was executed without needing input or halting.
+    Running = 0,
+    /// The program is waiting for input.
+    NeedsInput = 1,
+    /// The program has halted.
+    Halted = 2,
+    /// The program executed an invalid instruction.
+    Error = -1,
+}
+
+/// A machine along with the outputs it has produced which have not been taken yet.
+pub struct IntcodeComputer {
+    computer: Computer,
+    outputs: VecDeque<i64>,
+}
+
+impl IntcodeComputer {
+    fn step(&mut self) -> IntcodeStatus {
+        match self.computer.step() {
+            Ok(None) => IntcodeStatus::Running,
+            Ok(Some(RunState::Output(value))) => {
+                self.outputs.push_back(value);
+                IntcodeStatus::Running
+            }
+            Ok(Some(RunState::NeedsInput)) => IntcodeStatus::NeedsInput,
+            Ok(Some(RunState::Halted)) => IntcodeStatus::Halted,
+            Err(_) => IntcodeStatus::Error,
+        }
+    }
+
+    fn run(&mut self) -> IntcodeStatus {
+        loop {
+            match self.computer.run_until_event() {
+                Ok(RunState::Output(value)) => self.outputs.push_back(value),
+                Ok(RunState::NeedsInput) => return IntcodeStatus::NeedsInput,
+                Ok(RunState::Halted) => return IntcodeStatus::Halted,
+                Err(_) => return IntcodeStatus::Error,
+            }
+        }
+    }
+}
+
+/// Create a machine from a program in the comma-separated form of a puzzle input. Returns null
+/// if `program` is null, not valid UTF-8 or not a valid program.
+///
+/// # Safety
+///
+/// `program` must be null or point to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_new(program: *const c_char) -> *mut IntcodeComputer {
+    if program.is_null() {
+        return std::ptr::null_mut();
+    }
+    let Some(computer) = CStr::from_ptr(program)
+        .to_str()
+        .ok()
+        .and_then(|text| text.parse().ok())
+    else {
+        return std::ptr::null_mut();
+    };
+
+    Box::into_raw(Box::new(IntcodeComputer {
+        computer,
+        outputs: VecDeque::new(),
+    }))
+}
+
+/// Release a machine. Does nothing if `computer` is null.
+///
+/// # Safety
+///
+/// `computer` must be null or have come from `intcode_new`, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_free(computer: *mut IntcodeComputer) {
+    if !computer.is_null() {
+        drop(Box::from_raw(computer));
+    }
+}
+
+/// Queue a value to be consumed by the next input instruction.
+///
+/// # Safety
+///
+/// `computer` must have come from `intcode_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_push_input(computer: *mut IntcodeComputer, value: i64) {
+    (*computer).computer.push_input(value);
+}
+
+/// Take the oldest output which has not been taken yet, writing it to `value`. Returns false,
+/// leaving `value` alone, if there is none.
+///
+/// # Safety
+///
+/// `computer` must have come from `intcode_new` and not have been freed, and `value` must be
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_pop_output(
+    computer: *mut IntcodeComputer,
+    value: *mut i64,
+) -> bool {
+    match (*computer).outputs.pop_front() {
+        Some(output) => {
+            *value = output;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Execute a single instruction, unless the program is waiting for input or has halted.
+///
+/// # Safety
+///
+/// `computer` must have come from `intcode_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_step(computer: *mut IntcodeComputer) -> IntcodeStatus {
+    (*computer).step()
+}
+
+/// Run until the program needs input which has not been queued, halts or fails. Never returns
+/// `Running`.
+///
+/// # Safety
+///
+/// `computer` must have come from `intcode_new` and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_run(computer: *mut IntcodeComputer) -> IntcodeStatus {
+    (*computer).run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    fn outputs(computer: *mut IntcodeComputer) -> Vec<i64> {
+        let mut outputs = Vec::new();
+        let mut value = 0;
+        while unsafe { intcode_pop_output(computer, &mut value) } {
+            outputs.push(value);
+        }
+        outputs
+    }
+
+    #[test]
+    fn test_run() {
+        let program = CString::new("3,9,8,9,10,9,4,9,99,-1,8").unwrap();
+        unsafe {
+            let computer = intcode_new(program.as_ptr());
+            assert!(!computer.is_null());
+            assert_eq!(intcode_run(computer), IntcodeStatus::NeedsInput);
+            assert!(outputs(computer).is_empty());
+
+            intcode_push_input(computer, 8);
+            assert_eq!(intcode_run(computer), IntcodeStatus::Halted);
+            assert_eq!(outputs(computer), [1]);
+            intcode_free(computer);
+        }
+    }
+
+    #[test]
+    fn test_step() {
+        let program = CString::new("104,1,104,2,99").unwrap();
+        unsafe {
+            let computer = intcode_new(program.as_ptr());
+            assert_eq!(intcode_step(computer), IntcodeStatus::Running);
+            assert_eq!(outputs(computer), [1]);
+            assert_eq!(intcode_step(computer), IntcodeStatus::Running);
+            assert_eq!(intcode_step(computer), IntcodeStatus::Halted);
+            assert_eq!(outputs(computer), [2]);
+            intcode_free(computer);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let invalid = CString::new("1,2,x").unwrap();
+        let failing = CString::new("42").unwrap();
+        unsafe {
+            assert!(intcode_new(std::ptr::null()).is_null());
+            assert!(intcode_new(invalid.as_ptr()).is_null());
+
+            let computer = intcode_new(failing.as_ptr());
+            assert_eq!(intcode_run(computer), IntcodeStatus::Error);
+            intcode_free(computer);
+            intcode_free(std::ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,234 @@
+/// Differential tests: random programs are run on a deliberately simple reference interpreter,
+/// written straight from the puzzle descriptions, and on every combination of execution engine
+/// and memory backend, and all of them must agree.
+use super::{Backend, Computer, Engine, Memory, RunState};
+use proptest::prelude::*;
+
+/// Programs are stopped after this many instructions, as they may well loop forever.
+const STEP_LIMIT: usize = 500;
+
+/// How much of memory is compared after a run.
+const COMPARED_CELLS: usize = 256;
+
+/// Programs which write beyond this address are skipped, to keep dense memory small.
+const MAX_ADDRESS: usize = 4096;
+
+/// How a run ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum End {
+    Halted,
+    NeedsInput,
+    StepLimit,
+    /// The instruction at this address could not be executed.
+    Failed(usize),
+}
+
+#[derive(Debug, PartialEq)]
+struct Run {
+    outputs: Vec<i64>,
+    end: End,
+    memory: Vec<i64>,
+}
+
+/// The reference interpreter. Returns `None` if arithmetic overflows, which the puzzles never
+/// need and which the real computer doesn't guard against, or if the program writes somewhere
+/// too far away.
+fn reference(program: &[i64], inputs: &[i64]) -> Option<Run> {
+    let mut memory = program.to_vec();
+    let mut inputs = inputs.iter().copied();
+    let mut outputs = Vec::new();
+    let (mut ip, mut rb, mut steps) = (0_usize, 0_i64, 0);
+
+    let read = |memory: &Vec<i64>, address: usize| memory.get(address).copied().unwrap_or(0);
+    let end = 'run: loop {
+        let opcode = read(&memory, ip);
+        let mode = |n: u32| opcode / 10_i64.pow(n + 1) % 10;
+        // the address of parameter `n`.
+        let address = |memory: &Vec<i64>, n: u32| -> Option<usize> {
+            let raw = read(memory, ip + n as usize);
+            match mode(n) {
+                0 => usize::try_from(raw).ok(),
+                1 => Some(ip + n as usize),
+                2 => usize::try_from(rb.checked_add(raw)?).ok(),
+                _ => None,
+            }
+        };
+        let arity = match opcode % 100 {
+            1 | 2 | 7 | 8 => 3,
+            3 | 4 | 9 => 1,
+            5 | 6 => 2,
+            99 => 0,
+            _ => break End::Failed(ip),
+        };
+        let mut addresses = Vec::new();
+        for n in 1..=arity {
+            match address(&memory, n) {
+                Some(a) => addresses.push(a),
+                None => break 'run End::Failed(ip),
+            }
+        }
+        let writes = matches!(opcode % 100, 1 | 2 | 3 | 7 | 8);
+        if writes && mode(arity) == 1 {
+            break End::Failed(ip);
+        }
+        let value = |n: usize| read(&memory, addresses[n]);
+
+        let mut next = ip + 1 + arity as usize;
+        let mut write = None;
+        match opcode % 100 {
+            99 => break End::Halted,
+            3 if inputs.len() == 0 => break End::NeedsInput,
+            _ if steps >= STEP_LIMIT => break End::StepLimit,
+            1 => write = Some(value(0).checked_add(value(1))?),
+            2 => write = Some(value(0).checked_mul(value(1))?),
+            3 => write = inputs.next(),
+            4 => outputs.push(value(0)),
+            5 | 6 => {
+                if (value(0) != 0) == (opcode % 100 == 5) {
+                    match usize::try_from(value(1)) {
+                        Ok(target) => next = target,
+                        Err(_) => break End::Failed(ip),
+                    }
+                }
+            }
+            7 => write = Some(i64::from(value(0) < value(1))),
+            8 => write = Some(i64::from(value(0) == value(1))),
+            _ => match rb.checked_add(value(0)) {
+                Some(base) => rb = base,
+                None => break End::Failed(ip),
+            },
+        }
+        if let Some(value) = write {
+            let target = addresses[arity as usize - 1];
+            if target > MAX_ADDRESS {
+                return None;
+            }
+            if target >= memory.len() {
+                memory.resize(target + 1, 0);
+            }
+            memory[target] = value;
+        }
+        ip = next;
+        steps += 1;
+    };
+
+    Some(Run {
+        outputs,
+        end,
+        memory: (0..COMPARED_CELLS).map(|a| read(&memory, a)).collect(),
+    })
+}
+
+fn run(program: &[i64], inputs: &[i64], backend: Backend, engine: Engine) -> Run {
+    let mut memory = Memory::new(backend);
+    memory.patch(&program.iter().copied().enumerate().collect::<Vec<_>>());
+    let mut computer = Computer::new(memory);
+    computer.set_engine(engine);
+    computer.set_step_limit(Some(STEP_LIMIT));
+    for value in inputs {
+        computer.push_input(*value);
+    }
+
+    let mut outputs = Vec::new();
+    let end = loop {
+        match computer.run_until_event() {
+            Ok(RunState::Output(value)) => outputs.push(value),
+            Ok(RunState::Halted) => break End::Halted,
+            Ok(RunState::NeedsInput) => break End::NeedsInput,
+            Err(super::ExecutionError::StepLimitExceeded) => break End::StepLimit,
+            Err(_) => break End::Failed(computer.instruction_pointer()),
+        }
+    };
+    Run {
+        outputs,
+        end,
+        memory: (0..COMPARED_CELLS)
+            .map(|a| computer.memory().read_register(a))
+            .collect(),
+    }
+}
+
+prop_compose! {
+    /// A single instruction with random modes and parameters. Written parameters are never in
+    /// immediate mode, and the occasional unknown opcode is thrown in.
+    fn instruction()(
+        opcode in prop::sample::select(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 5, 6, 99, 42]),
+        modes in prop::array::uniform3(0_i64..3),
+        params in prop::array::uniform3(-8_i64..48),
+    ) -> Vec<i64> {
+        let (arity, writes) = match opcode {
+            1 | 2 | 7 | 8 => (3, true),
+            3 => (1, true),
+            4 | 9 => (1, false),
+            5 | 6 => (2, false),
+            _ => (0, false),
+        };
+        let mut modes = modes;
+        if writes && modes[arity - 1] == 1 {
+            modes[arity - 1] = 0;
+        }
+        let mut cells = vec![opcode + modes[0] * 100 + modes[1] * 1000 + modes[2] * 10000];
+        for n in 0..arity {
+            cells.push(if modes[n] == 0 { params[n].abs() } else { params[n] });
+        }
+        cells
+    }
+}
+
+prop_compose! {
+    fn program()(
+        instructions in prop::collection::vec(instruction(), 1..24),
+        data in prop::collection::vec(-20_i64..20, 0..8),
+    ) -> Vec<i64> {
+        let mut program: Vec<i64> = instructions.concat();
+        program.push(99);
+        program.extend(data);
+        program
+    }
+}
+
+proptest! {
+    #[test]
+    fn interpreter_matches_reference(
+        program in program(),
+        inputs in prop::collection::vec(-20_i64..20, 0..6),
+    ) {
+        let expected = reference(&program, &inputs);
+        prop_assume!(expected.is_some(), "out of scope");
+        let actual = run(&program, &inputs, Backend::Dense, Engine::Interpreted);
+        prop_assert_eq!(Some(actual), expected);
+    }
+
+    #[test]
+    fn engines_and_backends_agree(
+        program in program(),
+        inputs in prop::collection::vec(-20_i64..20, 0..6),
+    ) {
+        prop_assume!(reference(&program, &inputs).is_some(), "out of scope");
+        let expected = run(&program, &inputs, Backend::Dense, Engine::Interpreted);
+        for backend in [Backend::Dense, Backend::Sparse, Backend::Paged] {
+            for engine in [Engine::Interpreted, Engine::Compiled, Engine::Optimized] {
+                let actual = run(&program, &inputs, backend, engine);
+                prop_assert_eq!(&actual, &expected, "{:?} memory, {:?} engine", backend, engine);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_reference() {
+    let quine = [
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+    let run = reference(&quine, &[]).unwrap();
+    assert_eq!(run.outputs, quine);
+    assert_eq!(run.end, End::Halted);
+
+    assert_eq!(
+        reference(&[3, 0, 4, 0, 99], &[]).unwrap().end,
+        End::NeedsInput
+    );
+    assert_eq!(reference(&[1105, 1, 0], &[]).unwrap().end, End::StepLimit);
+    assert_eq!(reference(&[104, 1, 42], &[]).unwrap().end, End::Failed(2));
+    assert_eq!(reference(&[1102, i64::MAX, 2, 0, 99], &[]), None);
+}
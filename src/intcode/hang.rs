@@ -0,0 +1,245 @@
+/// Opt-in detection of programs which are provably stuck in a loop that can never perform input
+/// or output, such as a network computer waiting on a value which was never wired up to it.
+use super::{Cell, Computer, ExecutionError, Instruction, Memory};
+use alloc::string::ToString;
+use core::hash::{Hash, Hasher};
+
+/// The 64-bit FNV-1a hash, which is simple enough to do without `std`'s hashers. It only needs
+/// to make accidental collisions unlikely, since a match is always confirmed by comparing
+/// memory.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// A hash of one non-zero cell. The hash of a whole memory is the XOR of the hashes of its
+/// cells, so it can be kept up to date as single cells are written.
+fn cell_hash<T: Cell>(address: usize, value: &T) -> u64 {
+    let mut hasher = Fnv::default();
+    address.hash(&mut hasher);
+    match value.to_i64() {
+        Some(value) => value.hash(&mut hasher),
+        None => value.to_string().hash(&mut hasher),
+    }
+    hasher.finish()
+}
+
+fn memory_hash<T: Cell>(memory: &Memory<T>) -> u64 {
+    memory.cells().fold(0, |hash, (address, value)| {
+        hash ^ cell_hash(address, &value)
+    })
+}
+
+/// The state of a computer just after it took a jump.
+#[derive(Clone, Debug)]
+struct Saved<T> {
+    instruction_pointer: usize,
+    relative_base: i64,
+    hash: u64,
+    memory: Memory<T>,
+}
+
+/// Looks for a repeated state using Brent's cycle detection. Every loop takes a jump, so the
+/// state is only examined after jumps: it is compared against one saved state, which is replaced
+/// after 1, 2, 4, 8, ... further jumps. If the instruction pointer, relative base and memory
+/// all repeat with no input or output in between, the program will repeat them forever.
+#[derive(Clone, Debug)]
+pub(super) struct HangDetector<T> {
+    /// The hash of memory, or `None` until it is first needed.
+    hash: Option<u64>,
+    saved: Option<Saved<T>>,
+    jumps: usize,
+    period: usize,
+}
+
+impl<T> Default for HangDetector<T> {
+    fn default() -> Self {
+        Self {
+            hash: None,
+            saved: None,
+            jumps: 0,
+            period: 1,
+        }
+    }
+}
+
+impl<T: Cell> HangDetector<T> {
+    /// The address an instruction is about to write to and the value it holds beforehand.
+    pub(super) fn before(instruction: &Instruction, memory: &Memory<T>) -> Option<(usize, T)> {
+        instruction
+            .get_written_register()
+            .map(|address| (address, memory.read_register(address)))
+    }
+
+    /// Examine the state after an instruction has executed.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError::InfiniteLoop` if the instruction was a jump which returned
+    /// the computer to a state it has already been in since its last input or output.
+    pub(super) fn after(
+        &mut self,
+        instruction: &Instruction,
+        memory: &Memory<T>,
+        written: Option<(usize, T)>,
+        jump: Option<usize>,
+        relative_base: i64,
+    ) -> Result<(), ExecutionError> {
+        let hash = self.hash.get_or_insert_with(|| memory_hash(memory));
+        if let Some((address, old)) = written {
+            for value in [old, memory.read_register(address)] {
+                if !value.is_zero() {
+                    *hash ^= cell_hash(address, &value);
+                }
+            }
+        }
+        let hash = *hash;
+
+        if matches!(instruction, Instruction::Input(_) | Instruction::Output(_)) {
+            *self = Self {
+                hash: Some(hash),
+                ..Self::default()
+            };
+            return Ok(());
+        }
+        let Some(instruction_pointer) = jump else {
+            return Ok(());
+        };
+
+        if let Some(saved) = &self.saved {
+            if (saved.instruction_pointer, saved.relative_base, saved.hash)
+                == (instruction_pointer, relative_base, hash)
+                && saved.memory.diff(memory).is_empty()
+            {
+                return Err(ExecutionError::InfiniteLoop(instruction_pointer));
+            }
+        }
+
+        self.jumps += 1;
+        if self.jumps >= self.period {
+            self.saved = Some(Saved {
+                instruction_pointer,
+                relative_base,
+                hash,
+                memory: memory.clone(),
+            });
+            self.jumps = 0;
+            self.period *= 2;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Cell> Computer<T> {
+    /// Stop with `ExecutionError::InfiniteLoop` if the program returns to a state it has already
+    /// been in without performing any input or output in between, since it would then loop
+    /// forever. The error is reported once the loop has gone round at least once more, with
+    /// the computer pointing at an instruction within it. While detection is enabled,
+    /// instructions are always interpreted, even if the compiled engine was chosen.
+    pub fn set_hang_detection(&mut self, enabled: bool) {
+        *self.hang_detector_mut() = enabled.then(HangDetector::default);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+    use crate::intcode::{Engine, RunState};
+
+    fn computer(source: &str) -> Computer {
+        let mut computer: Computer = assemble(source).unwrap().parse().unwrap();
+        computer.set_engine(Engine::Compiled);
+        computer.set_hang_detection(true);
+        computer
+    }
+
+    #[test]
+    fn test_tight_loop() {
+        let mut computer = computer("loop: jnz #1 #loop");
+        assert_eq!(
+            computer.run_until_event(),
+            Err(ExecutionError::InfiniteLoop(0))
+        );
+    }
+
+    #[test]
+    fn test_loop_changing_memory() {
+        // flips a flag back and forth forever.
+        let mut computer = computer(
+            "
+            loop: eq   flag #0 flag
+                  mul  count #1 count
+                  jnz  #1 #loop
+            flag: data 0
+            count: data 5
+            ",
+        );
+        assert_eq!(
+            computer.run_until_event(),
+            Err(ExecutionError::InfiniteLoop(0))
+        );
+    }
+
+    #[test]
+    fn test_terminating_loop() {
+        // counts down from 1000 before outputting and halting.
+        let mut computer = computer(
+            "
+            loop: add  count #-1 count
+                  jnz  count #loop
+                  out  #7
+                  hlt
+            count: data 1000
+            ",
+        );
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(7)));
+        assert_eq!(computer.run_until_event(), Ok(RunState::Halted));
+    }
+
+    #[test]
+    fn test_loop_with_io() {
+        let mut computer = computer("loop: out #1\njnz #1 #loop");
+        assert_eq!(computer.outputs().take(100).count(), 100);
+
+        computer.set_hang_detection(false);
+        computer.set_step_limit(Some(1000));
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(1)));
+    }
+
+    #[test]
+    fn test_memory_changed_between_runs() {
+        let mut computer = computer(
+            "
+            loop: jz   flag #loop
+                  out  #1
+                  hlt
+            flag: data 0
+            ",
+        );
+        computer.set_step_limit(Some(1));
+        assert_eq!(
+            computer.run_until_event(),
+            Err(ExecutionError::StepLimitExceeded)
+        );
+
+        computer.memory_mut().set_register(6, 1);
+        computer.set_step_limit(None);
+        assert_eq!(computer.run_until_event(), Ok(RunState::Output(1)));
+    }
+}
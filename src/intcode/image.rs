@@ -0,0 +1,240 @@
+/// Saves grids of tiles, such as the arcade screen or the painted hull, as PNG images or as
+/// animated GIFs. Each tile becomes a `scale` by `scale` square of pixels; positions with nothing
+/// drawn are black.
+use super::painting_robot::{Color, Hull};
+use super::{Cell, ScreenBuffer};
+use std::io::{self, Write};
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// The area covered by a set of tiles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Bounds {
+    left: i64,
+    top: i64,
+    width: usize,
+    height: usize,
+}
+
+impl Bounds {
+    fn around<'a>(positions: impl IntoIterator<Item = &'a (i64, i64)>) -> Option<Self> {
+        let (mut left, mut top, mut right, mut bottom) = (i64::MAX, i64::MAX, i64::MIN, i64::MIN);
+        for (x, y) in positions {
+            left = left.min(*x);
+            right = right.max(*x);
+            top = top.min(*y);
+            bottom = bottom.max(*y);
+        }
+        Some(Self {
+            left,
+            top,
+            width: usize::try_from(right.checked_sub(left)? + 1).ok()?,
+            height: usize::try_from(bottom.checked_sub(top)? + 1).ok()?,
+        })
+    }
+
+    /// The tiles as rows of RGB pixels, `scale` pixels to a tile.
+    fn rasterize(self, tiles: &[((i64, i64), Rgb)], scale: usize) -> Vec<u8> {
+        let mut grid = vec![BLACK; self.width * self.height];
+        for ((x, y), color) in tiles {
+            let column = usize::try_from(x - self.left).unwrap_or_default();
+            let row = usize::try_from(y - self.top).unwrap_or_default();
+            grid[row * self.width + column] = *color;
+        }
+
+        let mut pixels = Vec::with_capacity(grid.len() * scale * scale * 3);
+        for row in grid.chunks(self.width) {
+            for _ in 0..scale {
+                for color in row {
+                    for _ in 0..scale {
+                        pixels.extend(color);
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    fn size<N: TryFrom<usize>>(self, scale: usize) -> io::Result<(N, N)> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "image is too large");
+        let width = self.width.checked_mul(scale).ok_or_else(too_large)?;
+        let height = self.height.checked_mul(scale).ok_or_else(too_large)?;
+        Ok((
+            N::try_from(width).map_err(|_| too_large())?,
+            N::try_from(height).map_err(|_| too_large())?,
+        ))
+    }
+}
+
+/// Write the tiles as a PNG, cropped to the smallest rectangle containing all of them.
+///
+/// # Errors
+///
+/// Will return `io::Error` if there are no tiles, the image would be too large, or writing fails.
+pub fn write_png(
+    out: impl Write,
+    tiles: impl IntoIterator<Item = ((i64, i64), Rgb)>,
+    scale: usize,
+) -> io::Result<()> {
+    let tiles: Vec<_> = tiles.into_iter().collect();
+    let bounds = Bounds::around(tiles.iter().map(|(position, _)| position))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "nothing to draw"))?;
+    let (width, height) = bounds.size(scale)?;
+
+    let mut encoder = png::Encoder::new(out, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&bounds.rasterize(&tiles, scale))?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Write each frame of tiles as one frame of a looping animated GIF, showing each for `delay`
+/// hundredths of a second. Every frame is cropped to the same rectangle, the smallest one
+/// containing all the tiles of every frame.
+///
+/// # Errors
+///
+/// Will return `io::Error` if there are no tiles, the image would be too large, or writing fails.
+pub fn write_gif<F>(
+    out: impl Write,
+    frames: impl IntoIterator<Item = F>,
+    scale: usize,
+    delay: u16,
+) -> io::Result<()>
+where
+    F: IntoIterator<Item = ((i64, i64), Rgb)>,
+{
+    let frames: Vec<Vec<_>> = frames
+        .into_iter()
+        .map(|frame| frame.into_iter().collect())
+        .collect();
+    let bounds = Bounds::around(frames.iter().flatten().map(|(position, _)| position))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "nothing to draw"))?;
+    let (width, height) = bounds.size(scale)?;
+
+    let mut encoder = gif::Encoder::new(out, width, height, &[]).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+    for tiles in &frames {
+        let mut frame = gif::Frame::from_rgb(width, height, &bounds.rasterize(tiles, scale));
+        frame.delay = delay;
+        encoder.write_frame(&frame).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+impl<T: Cell> ScreenBuffer<T> {
+    /// The tiles currently on screen, coloured by `palette`, ready to be saved as an image or a
+    /// frame of an animation.
+    pub fn pixels(&self, palette: impl Fn(&T) -> Rgb) -> Vec<((i64, i64), Rgb)> {
+        self.tiles()
+            .map(|(position, tile)| (position, palette(tile)))
+            .collect()
+    }
+
+    /// Save the screen as a PNG, with each tile coloured by `palette`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if the screen is empty, the image would be too large, or writing
+    /// fails.
+    pub fn write_png(
+        &self,
+        out: impl Write,
+        scale: usize,
+        palette: impl Fn(&T) -> Rgb,
+    ) -> io::Result<()> {
+        write_png(out, self.pixels(palette), scale)
+    }
+}
+
+impl Hull {
+    /// Save the painted panels as a PNG, white on black.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if nothing was painted, the image would be too large, or writing
+    /// fails.
+    pub fn write_png(&self, out: impl Write, scale: usize) -> io::Result<()> {
+        let pixels = self.painted().iter().map(|position| {
+            let color = match self.get(*position) {
+                Color::White => WHITE,
+                Color::Black => BLACK,
+            };
+            (*position, color)
+        });
+        write_png(out, pixels, scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgb = [255, 0, 0];
+
+    fn decode_png(data: &[u8]) -> (u32, u32, Vec<u8>) {
+        let mut reader = png::Decoder::new(data).read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        buffer.truncate(info.buffer_size());
+        (info.width, info.height, buffer)
+    }
+
+    #[test]
+    fn test_write_png() {
+        let mut data = Vec::new();
+        write_png(&mut data, [((1, 1), RED), ((2, 2), WHITE)], 2).unwrap();
+        let (width, height, pixels) = decode_png(&data);
+        assert_eq!((width, height), (4, 4));
+        assert_eq!(pixels[..6], [255, 0, 0, 255, 0, 0]);
+        assert_eq!(pixels[6..12], [0; 6]);
+        assert_eq!(pixels[pixels.len() - 3..], WHITE);
+    }
+
+    #[test]
+    fn test_write_png_empty() {
+        let error = write_png(Vec::new(), [], 1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_screen_write_png() {
+        let mut screen: ScreenBuffer = ScreenBuffer::new();
+        for value in [0, 0, 1, 3, 1, 2, -1, 0, 500] {
+            screen.push(value);
+        }
+        let mut data = Vec::new();
+        screen
+            .write_png(&mut data, 1, |tile| if *tile == 1 { WHITE } else { RED })
+            .unwrap();
+        let (width, height, pixels) = decode_png(&data);
+        assert_eq!((width, height), (4, 2));
+        assert_eq!(pixels[..3], WHITE);
+        assert_eq!(pixels[pixels.len() - 3..], RED);
+    }
+
+    #[test]
+    fn test_write_gif() {
+        let frames = [vec![((0, 0), RED)], vec![((0, 0), RED), ((2, 1), WHITE)]];
+        let mut data = Vec::new();
+        write_gif(&mut data, frames, 3, 10).unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(data.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (9, 6));
+        let mut count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            count += 1;
+        }
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,227 @@
+use super::{Cell, ExecutionError, Memory};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+/// A decoded instruction. Every parameter has already been resolved to the address it refers
+/// to, so immediate-mode parameters hold the position of the parameter itself.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Instruction {
+    Add(usize, usize, usize),
+    Multiply(usize, usize, usize),
+    Input(usize),
+    Output(usize),
+    JumpIfTrue(usize, usize),
+    JumpIfFalse(usize, usize),
+    LessThan(usize, usize, usize),
+    Equals(usize, usize, usize),
+    AdjustRelativeBase(usize),
+    Halt,
+}
+
+impl Instruction {
+    #[must_use]
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Add(_, _, _) => "add",
+            Instruction::Multiply(_, _, _) => "mul",
+            Instruction::Input(_) => "in",
+            Instruction::Output(_) => "out",
+            Instruction::JumpIfTrue(_, _) => "jnz",
+            Instruction::JumpIfFalse(_, _) => "jz",
+            Instruction::LessThan(_, _, _) => "lt",
+            Instruction::Equals(_, _, _) => "eq",
+            Instruction::AdjustRelativeBase(_) => "arb",
+            Instruction::Halt => "hlt",
+        }
+    }
+
+    /// The resolved addresses of this instruction's parameters, in order.
+    #[must_use]
+    pub fn parameters(&self) -> Vec<usize> {
+        match self {
+            Instruction::Add(a, b, c)
+            | Instruction::Multiply(a, b, c)
+            | Instruction::LessThan(a, b, c)
+            | Instruction::Equals(a, b, c) => vec![*a, *b, *c],
+            Instruction::JumpIfTrue(a, b) | Instruction::JumpIfFalse(a, b) => vec![*a, *b],
+            Instruction::Input(a) | Instruction::Output(a) | Instruction::AdjustRelativeBase(a) => {
+                vec![*a]
+            }
+            Instruction::Halt => vec![],
+        }
+    }
+
+    /// The register this instruction writes to, if any, including the target of an input.
+    #[must_use]
+    pub fn get_written_register(&self) -> Option<usize> {
+        match self {
+            Instruction::Input(x) => Some(*x),
+            _ => self.get_output_register(),
+        }
+    }
+
+    /// The registers whose values this instruction reads.
+    #[must_use]
+    pub fn get_read_registers(&self) -> Vec<usize> {
+        let mut parameters = self.parameters();
+        if self.get_written_register().is_some() {
+            parameters.pop();
+        }
+        parameters
+    }
+
+    /// Describe this instruction along with the current value at each parameter's address,
+    /// e.g. `add [5]=3 [6]=4 [0]=1`.
+    #[must_use]
+    pub fn describe<T: Cell>(&self, mem: &Memory<T>) -> String {
+        let mut text = self.mnemonic().to_string();
+        for address in self.parameters() {
+            text.push_str(&format!(" [{address}]={}", mem.read_register(address)));
+        }
+        text
+    }
+
+    /// The register written by instructions whose result depends only on memory.
+    #[must_use]
+    pub fn get_output_register(&self) -> Option<usize> {
+        match self {
+            Instruction::Add(_, _, x)
+            | Instruction::Multiply(_, _, x)
+            | Instruction::LessThan(_, _, x)
+            | Instruction::Equals(_, _, x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn get_output_value<T: Cell>(&self, mem: &Memory<T>) -> T {
+        match self {
+            Instruction::Add(a, b, _) => mem.read_register(*a) + mem.read_register(*b),
+            Instruction::Multiply(a, b, _) => mem.read_register(*a) * mem.read_register(*b),
+            Instruction::LessThan(a, b, _) => {
+                T::from_i64(i64::from(mem.read_register(*a) < mem.read_register(*b)))
+            }
+            Instruction::Equals(a, b, _) => {
+                T::from_i64(i64::from(mem.read_register(*a) == mem.read_register(*b)))
+            }
+            _ => T::default(),
+        }
+    }
+
+    /// The address to continue from if this is a jump which is taken.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError::AddressOutOfRange`, carrying the address of the cell which
+    /// holds the jump target, if that target is not a valid address.
+    pub fn get_jump_target<T: Cell>(
+        &self,
+        mem: &Memory<T>,
+    ) -> Result<Option<usize>, ExecutionError> {
+        let taken = match self {
+            Instruction::JumpIfTrue(a, _) => !mem.read_register(*a).is_zero(),
+            Instruction::JumpIfFalse(a, _) => mem.read_register(*a).is_zero(),
+            _ => false,
+        };
+        match self {
+            Instruction::JumpIfTrue(_, b) | Instruction::JumpIfFalse(_, b) if taken => {
+                mem.read_address(*b).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    #[must_use]
+    pub fn get_register_change(&self) -> usize {
+        match self {
+            Instruction::Add(_, _, _)
+            | Instruction::Multiply(_, _, _)
+            | Instruction::LessThan(_, _, _)
+            | Instruction::Equals(_, _, _) => 4,
+            Instruction::JumpIfTrue(_, _) | Instruction::JumpIfFalse(_, _) => 3,
+            Instruction::Input(_) | Instruction::Output(_) | Instruction::AdjustRelativeBase(_) => {
+                2
+            }
+            Instruction::Halt => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_register_change() {
+        let add = Instruction::Add(1, 2, 3);
+        assert_eq!(add.get_register_change(), 4);
+
+        let mul = Instruction::Multiply(1, 2, 3);
+        assert_eq!(mul.get_register_change(), 4);
+
+        let halt = Instruction::Halt;
+        assert_eq!(halt.get_register_change(), 0);
+
+        let jump = Instruction::JumpIfTrue(1, 2);
+        assert_eq!(jump.get_register_change(), 3);
+
+        let output = Instruction::Output(1);
+        assert_eq!(output.get_register_change(), 2);
+    }
+
+    #[test]
+    fn test_describe() {
+        let mem: Memory = "1,5,6,0,99,3,4".parse().unwrap();
+        assert_eq!(
+            Instruction::Add(5, 6, 0).describe(&mem),
+            "add [5]=3 [6]=4 [0]=1"
+        );
+        assert_eq!(Instruction::Halt.describe(&mem), "hlt");
+    }
+
+    #[test]
+    fn test_read_and_written_registers() {
+        let add = Instruction::Add(1, 2, 3);
+        assert_eq!(add.get_read_registers(), [1, 2]);
+        assert_eq!(add.get_written_register(), Some(3));
+
+        let input = Instruction::Input(4);
+        assert!(input.get_read_registers().is_empty());
+        assert_eq!(input.get_written_register(), Some(4));
+
+        let jump = Instruction::JumpIfFalse(5, 6);
+        assert_eq!(jump.get_read_registers(), [5, 6]);
+        assert_eq!(jump.get_written_register(), None);
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let mem: Memory = "7,5,6,0,99,3,8".parse().unwrap();
+        assert_eq!(Instruction::LessThan(5, 6, 0).get_output_value(&mem), 1);
+        assert_eq!(Instruction::LessThan(6, 5, 0).get_output_value(&mem), 0);
+        assert_eq!(Instruction::Equals(5, 5, 0).get_output_value(&mem), 1);
+        assert_eq!(Instruction::Equals(5, 6, 0).get_output_value(&mem), 0);
+    }
+
+    #[test]
+    fn test_get_jump_target() {
+        let mem: Memory = "5,0,6,0,99,3,8".parse().unwrap();
+        assert_eq!(
+            Instruction::JumpIfTrue(5, 6).get_jump_target(&mem),
+            Ok(Some(8))
+        );
+        assert_eq!(
+            Instruction::JumpIfTrue(1, 6).get_jump_target(&mem),
+            Ok(None)
+        );
+        assert_eq!(
+            Instruction::JumpIfFalse(1, 6).get_jump_target(&mem),
+            Ok(Some(8))
+        );
+        assert_eq!(
+            Instruction::JumpIfFalse(5, 6).get_jump_target(&mem),
+            Ok(None)
+        );
+    }
+}
@@ -0,0 +1,294 @@
+#[cfg(feature = "std")]
+use super::Cell;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, BufReader};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{Receiver, Sender};
+
+/// A supply of values for a program's input instructions.
+pub trait InputSource<T> {
+    /// The next input value, or `None` if no more input is available.
+    fn next_input(&mut self) -> Option<T>;
+}
+
+/// A destination for values produced by a program's output instructions.
+pub trait OutputSink<T> {
+    fn write_output(&mut self, value: T);
+}
+
+impl<T, F: FnMut() -> Option<T>> InputSource<T> for F {
+    fn next_input(&mut self) -> Option<T> {
+        self()
+    }
+}
+
+impl<T> InputSource<T> for VecDeque<T> {
+    fn next_input(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+}
+
+/// Adapts any iterator into an `InputSource`.
+pub struct IterInput<I>(pub I);
+
+impl<T, I: Iterator<Item = T>> InputSource<T> for IterInput<I> {
+    fn next_input(&mut self) -> Option<T> {
+        self.0.next()
+    }
+}
+
+impl<T, F: FnMut(T)> OutputSink<T> for F {
+    fn write_output(&mut self, value: T) {
+        self(value);
+    }
+}
+
+impl<T> OutputSink<T> for Vec<T> {
+    fn write_output(&mut self, value: T) {
+        self.push(value);
+    }
+}
+
+impl<T> OutputSink<T> for VecDeque<T> {
+    fn write_output(&mut self, value: T) {
+        self.push_back(value);
+    }
+}
+
+/// Blocks until a value is received, treating a disconnected sender as the end of input.
+#[cfg(feature = "std")]
+impl<T> InputSource<T> for Receiver<T> {
+    fn next_input(&mut self) -> Option<T> {
+        self.recv().ok()
+    }
+}
+
+/// Outputs sent after the receiving end has hung up are discarded.
+#[cfg(feature = "std")]
+impl<T> OutputSink<T> for Sender<T> {
+    fn write_output(&mut self, value: T) {
+        let _ = self.send(value);
+    }
+}
+
+/// How the contents of a file are turned into input values.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Integers separated by commas or whitespace.
+    Values,
+    /// Lines of text, each sent as ASCII codes followed by a newline (see
+    /// `Computer::send_line`).
+    Ascii,
+}
+
+/// Streams input from a file (or any other reader) a line at a time, so that long command
+/// sequences and scripts can be kept in `data/` rather than in the code. The input ends at the
+/// end of the file, or at the first line which cannot be read or parsed; the reason for the
+/// latter can be found with `error`.
+#[cfg(feature = "std")]
+pub struct FileInput<R = BufReader<File>> {
+    reader: R,
+    format: InputFormat,
+    pending: VecDeque<i64>,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl FileInput {
+    /// Open a file of comma or whitespace separated values.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if the file cannot be opened.
+    pub fn values(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(
+            BufReader::new(File::open(path)?),
+            InputFormat::Values,
+        ))
+    }
+
+    /// Open a file of ASCII text, to be sent a line at a time.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if the file cannot be opened.
+    pub fn ascii(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(
+            BufReader::new(File::open(path)?),
+            InputFormat::Ascii,
+        ))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> FileInput<R> {
+    #[must_use]
+    pub fn new(reader: R, format: InputFormat) -> Self {
+        Self {
+            reader,
+            format,
+            pending: VecDeque::new(),
+            error: None,
+        }
+    }
+
+    /// Why the input ended early, if it did.
+    #[must_use]
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    /// Read lines until one yields some values, returning `false` at the end of the input.
+    fn fill(&mut self) -> bool {
+        let mut line = String::new();
+        while self.pending.is_empty() && self.error.is_none() {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return false,
+                Ok(_) => self.parse_line(&line),
+                Err(e) => self.error = Some(e),
+            }
+        }
+        !self.pending.is_empty()
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        let line = line.trim_end_matches(['\r', '\n']);
+        match self.format {
+            InputFormat::Values => {
+                let values: Result<Vec<i64>, _> = line
+                    .split([',', ' ', '\t'])
+                    .filter(|value| !value.is_empty())
+                    .map(|value| {
+                        value
+                            .parse()
+                            .map_err(|e| format!("invalid input value {value:?}: {e}"))
+                    })
+                    .collect();
+                match values {
+                    Ok(values) => self.pending.extend(values),
+                    Err(message) => {
+                        self.error = Some(io::Error::new(io::ErrorKind::InvalidData, message));
+                    }
+                }
+            }
+            InputFormat::Ascii => {
+                if !line.is_ascii() {
+                    let message = format!("line is not ASCII: {line:?}");
+                    self.error = Some(io::Error::new(io::ErrorKind::InvalidData, message));
+                    return;
+                }
+                self.pending
+                    .extend(line.bytes().chain([b'\n']).map(i64::from));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Cell, R: BufRead> InputSource<T> for FileInput<R> {
+    fn next_input(&mut self) -> Option<T> {
+        if self.error.is_some() || !self.fill() {
+            return None;
+        }
+        self.pending.pop_front().map(T::from_i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closure_input() {
+        let mut count = 0;
+        let mut source = || {
+            count += 1;
+            (count <= 2).then_some(count)
+        };
+        assert_eq!(source.next_input(), Some(1));
+        assert_eq!(source.next_input(), Some(2));
+        assert_eq!(source.next_input(), None);
+    }
+
+    #[test]
+    fn test_iter_input() {
+        let mut source = IterInput([5, 6].into_iter());
+        assert_eq!(source.next_input(), Some(5));
+        assert_eq!(source.next_input(), Some(6));
+        assert_eq!(source.next_input(), None);
+    }
+
+    #[test]
+    fn test_channels() {
+        let (mut tx, mut rx) = std::sync::mpsc::channel();
+        tx.write_output(1);
+        tx.write_output(2);
+        drop(tx);
+        assert_eq!(rx.next_input(), Some(1));
+        assert_eq!(rx.next_input(), Some(2));
+        assert_eq!(rx.next_input(), None);
+    }
+
+    fn file_input(text: &str, format: InputFormat) -> FileInput<&[u8]> {
+        FileInput::new(text.as_bytes(), format)
+    }
+
+    #[test]
+    fn test_file_input_values() {
+        let mut source = file_input("1,2, 3\n\n-4 5\n", InputFormat::Values);
+        let values: Vec<i64> = std::iter::from_fn(|| source.next_input()).collect();
+        assert_eq!(values, [1, 2, 3, -4, 5]);
+        assert!(source.error().is_none());
+    }
+
+    #[test]
+    fn test_file_input_invalid_value() {
+        let mut source = file_input("7,x,8", InputFormat::Values);
+        assert_eq!(InputSource::<i64>::next_input(&mut source), None);
+        assert_eq!(
+            source.error().map(io::Error::kind),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_file_input_ascii() {
+        let mut source = file_input("NOT A J\r\nWALK", InputFormat::Ascii);
+        let text: String = std::iter::from_fn(|| source.next_input())
+            .map(|value: i64| char::from(u8::try_from(value).unwrap()))
+            .collect();
+        assert_eq!(text, "NOT A J\nWALK\n");
+    }
+
+    #[test]
+    fn test_file_input_program() {
+        let path = std::env::temp_dir().join(format!("intcode-input-{}.txt", std::process::id()));
+        std::fs::write(&path, "3\n4\n").unwrap();
+        let mut source = FileInput::values(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // outputs the sum of two inputs.
+        let mut computer: crate::intcode::Computer =
+            "3,11,3,12,1,11,12,11,4,11,99".parse().unwrap();
+        let mut outputs = Vec::new();
+        computer.run_with(&mut source, &mut outputs).unwrap();
+        assert_eq!(outputs, [7]);
+    }
+
+    #[test]
+    fn test_closure_output() {
+        let mut total = 0;
+        let mut sink = |value| total += value;
+        sink.write_output(3);
+        sink.write_output(4);
+        assert_eq!(total, 7);
+    }
+}
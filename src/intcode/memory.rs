@@ -0,0 +1,830 @@
+use super::asm::disassemble;
+use super::{AddressMap, Cell, Computer, ExecutionError, Instruction, RunState};
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt::Display;
+#[cfg(test)]
+use core::ops::{Add, AddAssign};
+use core::str::FromStr;
+
+/// How to treat cells which do not hold a known opcode when decoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Decoding {
+    /// Unknown opcodes are reported as `ExecutionError::InvalidOpcode`.
+    #[default]
+    Strict,
+    /// Unknown opcodes are treated as `Halt`.
+    Lenient,
+}
+
+/// The storage strategy used to hold a program's cells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Backend {
+    /// Contiguous cells, grown on demand to cover the highest address written.
+    Dense,
+    /// Only non-zero cells are stored, keyed by address. Suited to programs which write to
+    /// addresses far beyond their own length.
+    Sparse,
+    /// Fixed-size pages shared between clones, so that cloning is O(1) and only the pages
+    /// written to afterwards are copied. Suited to searches which branch a program many times.
+    Paged,
+}
+
+/// The number of cells in each page of `Backend::Paged` memory.
+const PAGE_SIZE: usize = 256;
+
+/// The most data cells shown on a single row of a listing.
+const LISTING_WIDTH: usize = 8;
+
+/// The fewest zero cells in a row which a listing leaves out, rather than showing.
+const ELIDED_ZEROS: usize = 64;
+
+type Page<T> = Arc<Vec<T>>;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Cells<T> {
+    Dense(Vec<T>),
+    Sparse(AddressMap<T>),
+    Paged(Arc<Vec<Page<T>>>),
+}
+
+/// The cells of an Intcode program. The cell type defaults to `i64`, which is sufficient for
+/// every puzzle input; wider types can be chosen with e.g. `Memory<i128>`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Memory<T = i64>(Cells<T>);
+
+impl<T: Cell> Default for Memory<T> {
+    fn default() -> Self {
+        Self::new(Backend::Dense)
+    }
+}
+
+impl<T: Cell> Memory<T> {
+    #[must_use]
+    pub fn new(backend: Backend) -> Self {
+        match backend {
+            Backend::Dense => Self(Cells::Dense(Vec::new())),
+            Backend::Sparse => Self(Cells::Sparse(AddressMap::new())),
+            Backend::Paged => Self(Cells::Paged(Arc::new(Vec::new()))),
+        }
+    }
+
+    #[must_use]
+    pub fn backend(&self) -> Backend {
+        match self.0 {
+            Cells::Dense(_) => Backend::Dense,
+            Cells::Sparse(_) => Backend::Sparse,
+            Cells::Paged(_) => Backend::Paged,
+        }
+    }
+
+    /// Move the contents of this memory into the given backend.
+    #[must_use]
+    pub fn with_backend(self, backend: Backend) -> Self {
+        if self.backend() == backend {
+            return self;
+        }
+
+        let mut converted = Self::new(backend);
+        for (pos, value) in self.cells() {
+            converted.set_register(pos, value);
+        }
+        converted
+    }
+
+    /// Iterate over the stored cells which hold a non-zero value, in no particular order.
+    pub(super) fn cells(&self) -> Box<dyn Iterator<Item = (usize, T)> + '_> {
+        match &self.0 {
+            Cells::Dense(cells) => Box::new(
+                cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(pos, value)| (pos, value.clone())),
+            ),
+            Cells::Sparse(cells) => {
+                Box::new(cells.iter().map(|(pos, value)| (*pos, value.clone())))
+            }
+            Cells::Paged(pages) => Box::new(pages.iter().enumerate().flat_map(|(ix, page)| {
+                page.iter()
+                    .enumerate()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(move |(offset, value)| (ix * PAGE_SIZE + offset, value.clone()))
+            })),
+        }
+    }
+
+    /// List the addresses whose values differ between this memory and `other`, in address
+    /// order, along with the value held in each.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<(usize, T, T)> {
+        let addresses: BTreeSet<usize> = self
+            .cells()
+            .chain(other.cells())
+            .map(|(pos, _)| pos)
+            .collect();
+        addresses
+            .into_iter()
+            .filter_map(|pos| {
+                let (before, after) = (self.read_register(pos), other.read_register(pos));
+                (before != after).then_some((pos, before, after))
+            })
+            .collect()
+    }
+
+    /// A listing of every cell up to the last non-zero one, with the address of the first cell
+    /// on each row. Long runs of zero cells are left out, so that a program which writes far
+    /// beyond its own cells can still be listed. A cell which holds a valid instruction is shown
+    /// on a row with its operands, followed by its disassembly; other cells are grouped into rows
+    /// of data. The row starting at `pointer`, if given, is marked with `>`.
+    ///
+    /// Cells are decoded in a single sweep from address 0, so data which happens to look like an
+    /// instruction is shown as one. No row runs past `pointer`, so the instruction there is
+    /// always decoded from its opcode.
+    #[must_use]
+    pub fn dump(&self, pointer: Option<usize>) -> String {
+        self.listing(pointer, |pos| if Some(pos) == pointer { '>' } else { ' ' })
+    }
+
+    /// One past the last cell which holds a non-zero value, or 0 if every cell is zero.
+    #[must_use]
+    pub fn extent(&self) -> usize {
+        self.cells()
+            .map(|(pos, _)| pos + 1)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The ranges of addresses a listing covers: those of the non-zero cells and `pointer`, with
+    /// runs of at least `ELIDED_ZEROS` zero cells between them left out. The first range starts at
+    /// address 0 unless such a run comes before it.
+    fn listed_ranges(&self, pointer: Option<usize>) -> Vec<(usize, usize)> {
+        let mut addresses: Vec<usize> = self.cells().map(|(pos, _)| pos).chain(pointer).collect();
+        addresses.sort_unstable();
+
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for pos in addresses {
+            match ranges.last_mut() {
+                Some((_, end)) if pos < *end + ELIDED_ZEROS => *end = (*end).max(pos + 1),
+                None if pos < ELIDED_ZEROS => ranges.push((0, pos + 1)),
+                _ => ranges.push((pos, pos + 1)),
+            }
+        }
+        ranges
+    }
+
+    /// The listing produced by `dump`, with each row marked by `marker` given its address.
+    pub(super) fn listing(&self, pointer: Option<usize>, marker: impl Fn(usize) -> char) -> String {
+        enum Row {
+            Cells(usize, String, String),
+            Elided(usize),
+        }
+
+        let mut rows = Vec::new();
+        let mut pos = 0;
+        for (start, end) in self.listed_ranges(pointer) {
+            if start > pos {
+                rows.push(Row::Elided(start - pos));
+            }
+            pos = pos.max(start);
+
+            while pos < end {
+                let boundary = pointer
+                    .filter(|pointer| *pointer > pos)
+                    .unwrap_or(usize::MAX);
+                let values: Vec<i64> = (pos..pos + 4)
+                    .map_while(|address| self.read_register(address).to_i64())
+                    .collect();
+                let (length, text) = disassemble(&values)
+                    .filter(|(_, length)| pos + length <= boundary)
+                    .map_or_else(
+                        || {
+                            (
+                                boundary.min(end).min(pos + LISTING_WIDTH) - pos,
+                                String::new(),
+                            )
+                        },
+                        |(text, length)| (length, text),
+                    );
+                let cells: Vec<String> = (pos..pos + length)
+                    .map(|address| self.read_register(address).to_string())
+                    .collect();
+                rows.push(Row::Cells(pos, cells.join(" "), text));
+                pos += length;
+            }
+        }
+
+        let width = rows
+            .iter()
+            .filter_map(|row| match row {
+                Row::Cells(_, cells, text) if !text.is_empty() => Some(cells.len()),
+                _ => None,
+            })
+            .max()
+            .unwrap_or_default();
+        rows.iter()
+            .map(|row| match row {
+                Row::Cells(pos, cells, text) => {
+                    let row = format!("{} {pos:04}: {cells:<width$}  {text}", marker(*pos));
+                    format!("{}\n", row.trim_end())
+                }
+                Row::Elided(count) => format!("  ...   ({count} zero cells)\n"),
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn read_register(&self, pos: usize) -> T {
+        match &self.0 {
+            Cells::Dense(cells) => cells.get(pos).cloned().unwrap_or_default(),
+            Cells::Sparse(cells) => cells.get(&pos).cloned().unwrap_or_default(),
+            Cells::Paged(pages) => pages
+                .get(pos / PAGE_SIZE)
+                .map(|page| page[pos % PAGE_SIZE].clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn set_register(&mut self, pos: usize, value: T) {
+        match &mut self.0 {
+            Cells::Dense(cells) => {
+                if pos >= cells.len() {
+                    if value.is_zero() {
+                        return;
+                    }
+                    cells.resize(pos + 1, T::default());
+                }
+                cells[pos] = value;
+            }
+            Cells::Sparse(cells) => {
+                if value.is_zero() {
+                    cells.remove(&pos);
+                } else {
+                    cells.insert(pos, value);
+                }
+            }
+            Cells::Paged(pages) => {
+                let ix = pos / PAGE_SIZE;
+                if ix >= pages.len() {
+                    if value.is_zero() {
+                        return;
+                    }
+                    Arc::make_mut(pages)
+                        .resize_with(ix + 1, || Arc::new(vec![T::default(); PAGE_SIZE]));
+                }
+                let page = &mut Arc::make_mut(pages)[ix];
+                Arc::make_mut(page)[pos % PAGE_SIZE] = value;
+            }
+        }
+    }
+
+    /// Write each value to its address, in order.
+    pub fn patch(&mut self, patches: &[(usize, T)]) {
+        for (pos, value) in patches {
+            self.set_register(*pos, value.clone());
+        }
+    }
+
+    /// Return this memory with each value written to its address, in order.
+    #[must_use]
+    pub fn with_patches(mut self, patches: &[(usize, T)]) -> Self {
+        self.patch(patches);
+        self
+    }
+
+    /// Read the value at `pos` for use as an address.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError::AddressOutOfRange`, carrying `pos` itself, if the stored value
+    /// is negative or too large to be an address.
+    pub fn read_address(&self, pos: usize) -> Result<usize, ExecutionError> {
+        self.read_register(pos)
+            .to_address()
+            .ok_or(ExecutionError::AddressOutOfRange(pos))
+    }
+
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the instruction at `pos` cannot be decoded.
+    pub fn read_instruction(&self, pos: usize) -> Result<Instruction, ExecutionError> {
+        self.decode(pos, 0)
+    }
+
+    /// Decode the instruction at `pos`, resolving each of its parameters to the address it refers
+    /// to according to its mode. Immediate-mode parameters resolve to their own position.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the opcode is unknown, a parameter has an unknown mode,
+    /// refers to an invalid address, or is an immediate-mode parameter which would be written to.
+    pub fn decode(&self, pos: usize, relative_base: i64) -> Result<Instruction, ExecutionError> {
+        self.decode_with(pos, relative_base, Decoding::Strict)
+    }
+
+    /// Decode the instruction at `pos`, choosing how to treat unknown opcodes.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the instruction cannot be decoded.
+    pub fn decode_with(
+        &self,
+        pos: usize,
+        relative_base: i64,
+        decoding: Decoding,
+    ) -> Result<Instruction, ExecutionError> {
+        let opcode = self.read_register(pos).to_i64().unwrap_or_default();
+        let param = |offset| self.resolve_parameter(pos, offset, opcode, relative_base);
+        let target = |offset: u32| {
+            if opcode / 10_i64.pow(offset + 1) % 10 == 1 {
+                return Err(ExecutionError::WriteToImmediate(pos));
+            }
+            param(offset)
+        };
+
+        Ok(match opcode % 100 {
+            1 => Instruction::Add(param(1)?, param(2)?, target(3)?),
+            2 => Instruction::Multiply(param(1)?, param(2)?, target(3)?),
+            3 => Instruction::Input(target(1)?),
+            4 => Instruction::Output(param(1)?),
+            5 => Instruction::JumpIfTrue(param(1)?, param(2)?),
+            6 => Instruction::JumpIfFalse(param(1)?, param(2)?),
+            7 => Instruction::LessThan(param(1)?, param(2)?, target(3)?),
+            8 => Instruction::Equals(param(1)?, param(2)?, target(3)?),
+            9 => Instruction::AdjustRelativeBase(param(1)?),
+            99 => Instruction::Halt,
+            _ => match decoding {
+                Decoding::Strict => return Err(ExecutionError::InvalidOpcode(pos)),
+                Decoding::Lenient => Instruction::Halt,
+            },
+        })
+    }
+
+    fn resolve_parameter(
+        &self,
+        pos: usize,
+        offset: u32,
+        opcode: i64,
+        relative_base: i64,
+    ) -> Result<usize, ExecutionError> {
+        let param_pos = pos + offset as usize;
+        match opcode / 10_i64.pow(offset + 1) % 10 {
+            0 => self
+                .read_address(param_pos)
+                .map_err(|_| ExecutionError::AddressOutOfRange(pos)),
+            1 => Ok(param_pos),
+            2 => self
+                .read_register(param_pos)
+                .to_i64()
+                .and_then(|offset| relative_base.checked_add(offset))
+                .and_then(|address| usize::try_from(address).ok())
+                .ok_or(ExecutionError::AddressOutOfRange(pos)),
+            mode => Err(ExecutionError::InvalidMode(pos, mode)),
+        }
+    }
+
+    /// Apply the effect of a single instruction to these registers. Only instructions whose
+    /// result can be computed from memory alone (arithmetic and comparisons) change anything.
+    pub fn execute(&mut self, instruction: &Instruction) {
+        if let Some(register) = instruction.get_output_register() {
+            let value = instruction.get_output_value(self);
+            self.set_register(register, value);
+        }
+    }
+
+    /// Run a program which takes no input to completion in place, discarding any output.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program executes an invalid instruction, or
+    /// `ExecutionError::InputExhausted` if it asks for input.
+    pub fn run_in_place(&mut self) -> Result<(), ExecutionError> {
+        self.run_in_place_limited(None)
+    }
+
+    fn run_in_place_limited(&mut self, step_limit: Option<usize>) -> Result<(), ExecutionError> {
+        let mut computer = Computer::new(core::mem::take(self));
+        computer.set_step_limit(step_limit);
+        let result = loop {
+            match computer.run_until_event() {
+                Ok(RunState::Output(_)) => {}
+                Ok(RunState::Halted) => break Ok(()),
+                Ok(RunState::NeedsInput) => break Err(ExecutionError::InputExhausted),
+                Err(e) => break Err(e),
+            }
+        };
+        *self = computer.into_memory();
+        result
+    }
+
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if the program cannot be run to completion.
+    pub fn run(&self) -> Result<Self, ExecutionError> {
+        let mut mem = self.clone();
+        mem.run_in_place()?;
+        Ok(mem)
+    }
+
+    /// Run a copy of the program to completion, giving up once it has executed `step_limit`
+    /// instructions.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError::StepLimitExceeded` if the program runs for too long, or
+    /// another `ExecutionError` if it cannot be run to completion.
+    pub fn run_with_limit(&self, step_limit: usize) -> Result<Self, ExecutionError> {
+        let mut mem = self.clone();
+        mem.run_in_place_limited(Some(step_limit))?;
+        Ok(mem)
+    }
+}
+
+#[cfg(test)]
+impl<T: Cell> Add<Instruction> for Memory<T> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Instruction) -> Self::Output {
+        self.execute(&rhs);
+        self
+    }
+}
+
+#[cfg(test)]
+impl<T: Cell> AddAssign<Instruction> for Memory<T> {
+    fn add_assign(&mut self, rhs: Instruction) {
+        *self = core::mem::take(self) + rhs;
+    }
+}
+
+/// Shows the listing produced by `dump`, without marking any row.
+impl<T: Cell> Display for Memory<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.dump(None))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseMemoryError;
+
+/// Parses comma-separated values. So that program files can be annotated, whitespace and
+/// newlines may appear around values, everything from a `#` to the end of a line is a comment,
+/// and the final value may be followed by a comma.
+impl<T: Cell> FromStr for Memory<T> {
+    type Err = ParseMemoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text: String = s
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let text = text.trim();
+        let text = text.strip_suffix(',').unwrap_or(text);
+
+        let registers = text
+            .split(',')
+            .map(|value_str| value_str.trim().parse().map_err(|_| ParseMemoryError))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self(Cells::Dense(registers)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_memory() -> Memory {
+        Memory(Cells::Dense(vec![1, 2, 4, 0, 2, 1, 3, 3, 99]))
+    }
+
+    fn registers(mem: &Memory, count: usize) -> Vec<i64> {
+        (0..count).map(|pos| mem.read_register(pos)).collect()
+    }
+
+    #[test]
+    fn test_example_memory() {
+        assert_eq!(
+            registers(&example_memory(), 9),
+            [1, 2, 4, 0, 2, 1, 3, 3, 99]
+        );
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        let parsed: Memory = "1,2,4,0,2,1,3,3,99"
+            .parse()
+            .expect("Could not parse example program");
+        assert_eq!(registers(&parsed, 9), [1, 2, 4, 0, 2, 1, 3, 3, 99])
+    }
+
+    #[test]
+    fn test_dump() {
+        let mem: Memory = "1,9,10,3,2,3,11,0,99,30,40,50".parse().unwrap();
+        assert_eq!(
+            mem.dump(Some(4)),
+            "  0000: 1 9 10 3  add  9 10 3
+> 0004: 2 3 11 0  mul  3 11 0
+  0008: 99        hlt
+  0009: 30 40 50
+"
+        );
+        assert_eq!(mem.to_string(), mem.dump(None));
+    }
+
+    #[test]
+    fn test_dump_pointer() {
+        // the pointer lands inside what would otherwise be decoded as one instruction, and
+        // past the end of the non-zero cells.
+        let mem: Memory = "1,1,1,1,-5,0,0,0,0,0,0,0,7".parse().unwrap();
+        assert_eq!(
+            mem.with_backend(Backend::Sparse).dump(Some(2)),
+            "  0000: 1 1
+> 0002: 1 1 -5 0  add  1 -5 0
+  0006: 0 0 0 0 0 0 7
+"
+        );
+        assert_eq!(
+            Memory::<i64>::default().dump(Some(1)),
+            "  0000: 0\n> 0001: 0\n"
+        );
+    }
+
+    #[test]
+    fn test_dump_elides_zeros() {
+        let mut mem: Memory = "104,7,99"
+            .parse::<Memory>()
+            .unwrap()
+            .with_backend(Backend::Sparse);
+        mem.set_register(1_000_000_000_000, 5);
+        assert_eq!(
+            mem.dump(Some(1_000_000_000_002)),
+            "  0000: 104 7  out  #7
+  0002: 99     hlt
+  ...   (999999999997 zero cells)
+  1000000000000: 5 0
+> 1000000000002: 0
+"
+        );
+    }
+
+    #[test]
+    fn test_patch() {
+        let mut mem = example_memory();
+        mem.patch(&[(1, 5), (20, 3), (1, 6)]);
+        assert_eq!(mem.read_register(1), 6);
+        assert_eq!(mem.read_register(20), 3);
+
+        let patched = example_memory().with_patches(&[(0, 2)]);
+        assert_eq!(registers(&patched, 2), [2, 2]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = example_memory();
+        let after = before.run().unwrap();
+        assert_eq!(after.diff(&after), []);
+        assert_eq!(before.diff(&after), [(0, 1, 6)]);
+
+        let mut sparse = after.clone().with_backend(Backend::Sparse);
+        sparse.set_register(1, 0);
+        sparse.set_register(50, 7);
+        assert_eq!(after.diff(&sparse), [(1, 2, 0), (50, 0, 7)]);
+    }
+
+    #[test]
+    fn test_run_with_limit() {
+        let mem: Memory = "1,0,0,0,1105,1,4".parse().unwrap();
+        assert_eq!(
+            mem.run_with_limit(100).map(|_| ()),
+            Err(ExecutionError::StepLimitExceeded)
+        );
+        assert!(example_memory().run_with_limit(2).is_ok());
+    }
+
+    #[test]
+    fn test_parse_commented_memory() {
+        let text = "
+            # swap the first two cells
+            1, 2, 4,  # add
+            0, 2, 1, 3, 3,
+            99,       # halt
+        ";
+        let parsed: Memory = text.parse().expect("Could not parse commented program");
+        assert_eq!(registers(&parsed, 9), [1, 2, 4, 0, 2, 1, 3, 3, 99]);
+
+        assert_eq!(
+            "1,2\n".parse::<Memory>().map(|mem| mem.read_register(1)),
+            Ok(2)
+        );
+        assert!("1,,2".parse::<Memory>().is_err());
+        assert!("1 2".parse::<Memory>().is_err());
+    }
+
+    #[test]
+    fn test_read_register() {
+        let mem = example_memory();
+        assert_eq!(mem.read_register(0), 1);
+        assert_eq!(mem.read_register(4), 2);
+        assert_eq!(mem.read_register(8), 99);
+        assert_eq!(mem.read_register(20), 0);
+    }
+
+    #[test]
+    fn test_set_register_beyond_program() {
+        let mut mem = example_memory();
+        mem.set_register(500, 7);
+        assert_eq!(mem.read_register(500), 7);
+        assert_eq!(mem.read_register(499), 0);
+    }
+
+    #[test]
+    fn test_negative_values() {
+        let parsed: Memory = "1101,-7,3,0,99".parse().expect("Could not parse program");
+        assert_eq!(parsed.read_register(1), -7);
+
+        let mut mem = example_memory();
+        mem.set_register(3, -20);
+        assert_eq!(mem.read_register(3), -20);
+    }
+
+    #[test]
+    fn test_large_values() {
+        let mut mem = example_memory();
+        mem.set_register(0, 1_125_899_906_842_624);
+        assert_eq!(mem.read_register(0), 1_125_899_906_842_624);
+    }
+
+    #[test]
+    fn test_wide_cells() {
+        let mut mem: Memory<i128> = "1,0,0,0,99".parse().expect("Could not parse program");
+        mem.set_register(0, i128::from(i64::MAX) * 4);
+        assert_eq!(mem.read_register(0), i128::from(i64::MAX) * 4);
+    }
+
+    #[test]
+    fn test_sparse_far_address() {
+        let mut mem: Memory = Memory::new(Backend::Sparse);
+        mem.set_register(1 << 40, 12);
+        assert_eq!(mem.read_register(1 << 40), 12);
+        assert_eq!(mem.read_register(0), 0);
+    }
+
+    #[test]
+    fn test_with_backend() {
+        let sparse = example_memory().with_backend(Backend::Sparse);
+        assert_eq!(sparse.backend(), Backend::Sparse);
+        assert_eq!(registers(&sparse, 9), [1, 2, 4, 0, 2, 1, 3, 3, 99]);
+
+        let paged = sparse.with_backend(Backend::Paged);
+        assert_eq!(paged.backend(), Backend::Paged);
+        assert_eq!(registers(&paged, 9), [1, 2, 4, 0, 2, 1, 3, 3, 99]);
+
+        let dense = paged.with_backend(Backend::Dense);
+        assert_eq!(dense.backend(), Backend::Dense);
+        assert_eq!(registers(&dense, 9), [1, 2, 4, 0, 2, 1, 3, 3, 99]);
+    }
+
+    #[test]
+    fn test_paged_clones_share_pages() {
+        let mut original: Memory = Memory::new(Backend::Paged);
+        original.set_register(3, 1);
+        original.set_register(PAGE_SIZE * 2 + 1, 2);
+
+        let mut copy = original.clone();
+        copy.set_register(3, 5);
+        copy.set_register(PAGE_SIZE * 4, 6);
+        assert_eq!(original.read_register(3), 1);
+        assert_eq!(original.read_register(PAGE_SIZE * 4), 0);
+        assert_eq!(copy.read_register(3), 5);
+
+        let (Cells::Paged(original), Cells::Paged(copy)) = (&original.0, &copy.0) else {
+            unreachable!("both memories are paged");
+        };
+        assert!(!Arc::ptr_eq(&original[0], &copy[0]));
+        assert!(Arc::ptr_eq(&original[2], &copy[2]));
+    }
+
+    #[test]
+    fn test_read_instruction_add() {
+        let mem = example_memory();
+        assert_eq!(mem.read_instruction(0), Ok(Instruction::Add(2, 4, 0)));
+    }
+
+    #[test]
+    fn test_read_instruction_multiply() {
+        let mem = example_memory();
+        assert_eq!(mem.read_instruction(4), Ok(Instruction::Multiply(1, 3, 3)));
+    }
+
+    #[test]
+    fn test_decode_modes() {
+        let mem: Memory = "1002,4,3,4,33".parse().unwrap();
+        assert_eq!(mem.decode(0, 0), Ok(Instruction::Multiply(4, 2, 4)));
+
+        let mem: Memory = "109,19,204,-34,99".parse().unwrap();
+        assert_eq!(mem.decode(0, 0), Ok(Instruction::AdjustRelativeBase(1)));
+        assert_eq!(mem.decode(2, 2000), Ok(Instruction::Output(1966)));
+    }
+
+    #[test]
+    fn test_decode_unknown_opcode() {
+        let mem: Memory = "1,0,0,0,42".parse().unwrap();
+        assert_eq!(mem.decode(4, 0), Err(ExecutionError::InvalidOpcode(4)));
+        assert_eq!(
+            mem.decode_with(4, 0, Decoding::Lenient),
+            Ok(Instruction::Halt)
+        );
+        assert_eq!(mem.decode(99, 0), Err(ExecutionError::InvalidOpcode(99)));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mem: Memory = "1301,0,0,0,11101,1,1,7,1,-5,0,0,209,-9".parse().unwrap();
+        assert_eq!(mem.decode(0, 0), Err(ExecutionError::InvalidMode(0, 3)));
+        assert_eq!(mem.decode(4, 0), Err(ExecutionError::WriteToImmediate(4)));
+        assert_eq!(mem.decode(8, 0), Err(ExecutionError::AddressOutOfRange(8)));
+        assert_eq!(
+            mem.decode(12, 0),
+            Err(ExecutionError::AddressOutOfRange(12))
+        );
+        assert_eq!(mem.decode(12, 9), Ok(Instruction::AdjustRelativeBase(0)));
+    }
+
+    #[test]
+    fn test_read_instruction_halt() {
+        let mem = example_memory();
+        assert_eq!(mem.read_instruction(8), Ok(Instruction::Halt));
+    }
+
+    #[test]
+    fn test_execute_instruction_add() {
+        let mem = example_memory();
+
+        let add = Instruction::Add(0, 2, 0);
+        let after = mem.clone() + add;
+        assert_eq!(registers(&after, 9), [5, 2, 4, 0, 2, 1, 3, 3, 99]);
+
+        let add = Instruction::Add(4, 8, 1);
+        let after = mem + add;
+        assert_eq!(registers(&after, 9), [1, 101, 4, 0, 2, 1, 3, 3, 99]);
+    }
+
+    #[test]
+    fn test_execute_instruction_multiply() {
+        let mem = example_memory();
+
+        let mul = Instruction::Multiply(1, 2, 0);
+        let after = mem.clone() + mul;
+        assert_eq!(registers(&after, 9), [8, 2, 4, 0, 2, 1, 3, 3, 99]);
+
+        let mul = Instruction::Multiply(1, 3, 4);
+        let after = mem + mul;
+        assert_eq!(registers(&after, 9), [1, 2, 4, 0, 0, 1, 3, 3, 99]);
+    }
+
+    #[test]
+    fn test_execute_in_place() {
+        let mut mem = example_memory();
+        mem.execute(&Instruction::Add(0, 2, 0));
+        assert_eq!(registers(&mem, 9), [5, 2, 4, 0, 2, 1, 3, 3, 99]);
+    }
+
+    #[test]
+    fn test_run_in_place() {
+        let mut mem = example_memory();
+        mem.run_in_place().unwrap();
+        assert_eq!(registers(&mem, 9), [6, 2, 4, 0, 2, 1, 3, 3, 99]);
+    }
+
+    #[test]
+    fn test_run_needing_input() {
+        let mem: Memory = "3,0,99".parse().unwrap();
+        assert!(matches!(mem.run(), Err(ExecutionError::InputExhausted)));
+    }
+
+    #[test]
+    fn test_run_program() {
+        let mem = example_memory();
+        let after = mem.run().unwrap();
+        assert_eq!(registers(&after, 9), [6, 2, 4, 0, 2, 1, 3, 3, 99]);
+
+        let sparse = example_memory()
+            .with_backend(Backend::Sparse)
+            .run()
+            .unwrap();
+        assert_eq!(registers(&sparse, 9), [6, 2, 4, 0, 2, 1, 3, 3, 99]);
+
+        let paged = example_memory().with_backend(Backend::Paged).run().unwrap();
+        assert_eq!(registers(&paged, 9), [6, 2, 4, 0, 2, 1, 3, 3, 99]);
+    }
+}
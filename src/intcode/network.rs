@@ -0,0 +1,323 @@
+/// A network of Intcode computers which exchange packets of three values: a destination
+/// address followed by an X and a Y value. Each computer is told its address as its first input,
+/// and is given `-1` whenever it asks for input while no packets are waiting for it.
+use super::{Cell, Computer, ExecutionError, Memory, RunState};
+
+/// The address monitored by the NAT device rather than by a computer.
+pub const NAT_ADDRESS: usize = 255;
+
+/// The default number of consecutive turns a computer must spend waiting on an empty queue, without
+/// sending anything, before it counts as idle.
+pub const DEFAULT_IDLE_THRESHOLD: usize = 2;
+
+/// A packet sent from one computer to another.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Packet<T = i64> {
+    pub destination: usize,
+    pub x: T,
+    pub y: T,
+}
+
+struct Node<T> {
+    computer: Computer<T>,
+    outputs: Vec<T>,
+    halted: bool,
+    /// Consecutive turns which began with nothing queued and in which nothing was sent.
+    idle_turns: usize,
+}
+
+pub struct Network<T = i64> {
+    nodes: Vec<Node<T>>,
+    nat: Option<Packet<T>>,
+    released: Option<T>,
+    idle_threshold: usize,
+}
+
+impl<T: Cell> Network<T> {
+    /// Start `size` copies of `program`, with addresses from zero upwards.
+    #[must_use]
+    pub fn new(program: &Memory<T>, size: usize) -> Self {
+        let nodes = (0..size)
+            .map(|address| {
+                let mut computer = Computer::new(program.clone());
+                computer.push_input(T::from_i64(i64::try_from(address).unwrap_or_default()));
+                Node {
+                    computer,
+                    outputs: Vec::new(),
+                    halted: false,
+                    idle_turns: 0,
+                }
+            })
+            .collect();
+        Self {
+            nodes,
+            nat: None,
+            released: None,
+            idle_threshold: DEFAULT_IDLE_THRESHOLD,
+        }
+    }
+
+    /// Set how many consecutive turns a computer must spend asking for input with an empty
+    /// queue, without sending any packets, before it counts as idle.
+    pub fn set_idle_threshold(&mut self, turns: usize) {
+        self.idle_threshold = turns;
+    }
+
+    /// Whether the computer at `address` is idle. Halted computers are always idle.
+    #[must_use]
+    pub fn is_idle(&self, address: usize) -> bool {
+        self.nodes.get(address).is_none_or(|node| {
+            node.halted
+                || (node.computer.pending_inputs() == 0 && node.idle_turns >= self.idle_threshold)
+        })
+    }
+
+    /// Whether every computer on the network is idle, meaning the NAT should release its packet.
+    #[must_use]
+    pub fn all_idle(&self) -> bool {
+        (0..self.nodes.len()).all(|address| self.is_idle(address))
+    }
+
+    /// Whether every computer on the network has halted.
+    #[must_use]
+    pub fn all_halted(&self) -> bool {
+        self.nodes.iter().all(|node| node.halted)
+    }
+
+    /// The most recent packet received by the NAT, which is the one it will release next.
+    #[must_use]
+    pub fn nat_packet(&self) -> Option<&Packet<T>> {
+        self.nat.as_ref()
+    }
+
+    fn route(&mut self, packet: Packet<T>) {
+        if packet.destination == NAT_ADDRESS {
+            self.nat = Some(packet);
+        } else if let Some(node) = self.nodes.get_mut(packet.destination) {
+            node.computer.push_input(packet.x);
+            node.computer.push_input(packet.y);
+        }
+    }
+
+    /// Give each computer one turn, running it until it asks for input again. Packets are
+    /// delivered as soon as they are sent; those addressed to computers outside the network are
+    /// dropped. Returns `true` if every computer is now idle (see `all_idle`).
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if any computer fails.
+    pub fn tick(&mut self) -> Result<bool, ExecutionError> {
+        for ix in 0..self.nodes.len() {
+            let node = &mut self.nodes[ix];
+            if node.halted {
+                continue;
+            }
+            let waiting = node.computer.pending_inputs() == 0;
+            if waiting {
+                node.computer.push_input(T::from_i64(-1));
+            }
+
+            let mut packets = Vec::new();
+            loop {
+                match node.computer.run_until_event()? {
+                    RunState::Output(value) => node.outputs.push(value),
+                    RunState::NeedsInput => break,
+                    RunState::Halted => {
+                        node.halted = true;
+                        break;
+                    }
+                }
+                if let [destination, x, y] = &node.outputs[..] {
+                    if let Some(destination) = destination.to_address() {
+                        packets.push(Packet {
+                            destination,
+                            x: x.clone(),
+                            y: y.clone(),
+                        });
+                    }
+                    node.outputs.clear();
+                }
+            }
+
+            if waiting && packets.is_empty() {
+                node.idle_turns += 1;
+            } else {
+                node.idle_turns = 0;
+            }
+            for packet in packets {
+                self.route(packet);
+            }
+        }
+
+        Ok(self.all_idle())
+    }
+
+    /// Run the network until the NAT receives its first packet, and return it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if any computer fails, or `ExecutionError::NetworkStalled` if
+    /// every computer halts or goes idle before the NAT receives a packet.
+    pub fn run_until_nat(&mut self) -> Result<Packet<T>, ExecutionError> {
+        loop {
+            let idle = self.tick()?;
+            if let Some(packet) = &self.nat {
+                return Ok(packet.clone());
+            }
+            if idle {
+                return Err(ExecutionError::NetworkStalled);
+            }
+        }
+    }
+
+    /// Run the network, having the NAT release its packet to address zero whenever every
+    /// computer is idle, until the NAT releases the same Y value twice in a row. Returns that Y
+    /// value.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if any computer fails, or `ExecutionError::NetworkStalled` if
+    /// every computer halts, or goes idle before the NAT has a packet to release.
+    pub fn run_until_repeated_nat(&mut self) -> Result<T, ExecutionError> {
+        loop {
+            if !self.tick()? {
+                continue;
+            }
+            if self.all_halted() {
+                return Err(ExecutionError::NetworkStalled);
+            }
+            let Some(packet) = self.nat.clone() else {
+                return Err(ExecutionError::NetworkStalled);
+            };
+            if self.released.as_ref() == Some(&packet.y) {
+                return Ok(packet.y);
+            }
+            self.released = Some(packet.y.clone());
+            self.route(Packet {
+                destination: 0,
+                ..packet
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    /// Computer 0 forwards every packet it receives to the NAT with its Y replaced by zero.
+    /// Computer 1 sends a single packet to the NAT the first time it finds no input waiting,
+    /// then stays quiet.
+    const PROGRAM: &str = "
+                in   address
+        listen: in   x
+                eq   x #-1 flag
+                jnz  flag #idle
+                in   y
+                out  #255
+                out  x
+                out  #0
+                jnz  #1 #listen
+        idle:   eq   address #1 flag
+                jz   flag #listen
+                eq   sent #1 flag
+                jnz  flag #listen
+                add  #1 #0 sent
+                out  #255
+                out  #7
+                out  #1
+                jnz  #1 #listen
+        address: data 0
+        x:       data 0
+        y:       data 0
+        flag:    data 0
+        sent:    data 0
+    ";
+
+    fn network() -> Network {
+        let program = assemble(PROGRAM).unwrap().parse().unwrap();
+        Network::new(&program, 2)
+    }
+
+    #[test]
+    fn test_run_until_nat() {
+        assert_eq!(
+            network().run_until_nat(),
+            Ok(Packet {
+                destination: 255,
+                x: 7,
+                y: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_run_until_repeated_nat() {
+        assert_eq!(network().run_until_repeated_nat(), Ok(0));
+    }
+
+    #[test]
+    fn test_stalled() {
+        let halting = "99".parse().unwrap();
+        assert_eq!(
+            Network::<i64>::new(&halting, 2).run_until_nat(),
+            Err(ExecutionError::NetworkStalled)
+        );
+        assert_eq!(
+            Network::<i64>::new(&halting, 2).run_until_repeated_nat(),
+            Err(ExecutionError::NetworkStalled)
+        );
+
+        let listening = assemble("listen: in x\n jnz #1 #listen\n x: data 0")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Network::<i64>::new(&listening, 2).run_until_nat(),
+            Err(ExecutionError::NetworkStalled)
+        );
+        assert_eq!(
+            Network::<i64>::new(&listening, 2).run_until_repeated_nat(),
+            Err(ExecutionError::NetworkStalled)
+        );
+    }
+
+    #[test]
+    fn test_routing() {
+        let mut network = network();
+        network.route(Packet {
+            destination: 0,
+            x: 3,
+            y: 4,
+        });
+        network.route(Packet {
+            destination: 99,
+            x: 5,
+            y: 6,
+        });
+        assert_eq!(network.tick(), Ok(false));
+        assert_eq!(network.nat_packet().map(|packet| packet.x), Some(3));
+    }
+
+    #[test]
+    fn test_idle_threshold() {
+        let mut network = network();
+        network.set_idle_threshold(3);
+        network.run_until_nat().unwrap();
+        assert!(!network.is_idle(0));
+
+        assert_eq!(network.tick(), Ok(false));
+        assert_eq!(network.tick(), Ok(false));
+        assert!(!network.all_idle());
+        assert_eq!(network.tick(), Ok(true));
+        assert!(network.is_idle(0) && network.is_idle(1));
+
+        network.route(Packet {
+            destination: 1,
+            x: 0,
+            y: 0,
+        });
+        assert!(!network.is_idle(1));
+    }
+}
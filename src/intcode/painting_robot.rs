@@ -0,0 +1,205 @@
+/// Drives the day 11 hull painting robot: it is told the color of the panel it is standing on,
+/// paints that panel, turns and moves forward one panel, until the program halts.
+use super::{Computer, ExecutionError, Memory, RunState};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+pub type Position = (i64, i64);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Color {
+    #[default]
+    Black,
+    White,
+}
+
+impl Color {
+    fn value(self) -> i64 {
+        match self {
+            Color::Black => 0,
+            Color::White => 1,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PaintError {
+    Execution(ExecutionError),
+    /// The robot output something other than 0 or 1 as a color or a turn.
+    UnexpectedOutput(i64),
+    /// The program stopped after painting a panel, without saying which way to turn.
+    MissingTurn,
+}
+
+impl Display for PaintError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaintError::Execution(e) => write!(f, "{e}"),
+            PaintError::UnexpectedOutput(value) => write!(f, "unexpected output {value}"),
+            PaintError::MissingTurn => write!(f, "the robot did not say which way to turn"),
+        }
+    }
+}
+
+impl From<ExecutionError> for PaintError {
+    fn from(e: ExecutionError) -> Self {
+        PaintError::Execution(e)
+    }
+}
+
+/// The panels of the hull, with the robot's starting panel at `(0, 0)` and up towards
+/// negative y. Panels which have never been painted are black.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hull {
+    panels: HashMap<Position, Color>,
+    painted: HashSet<Position>,
+}
+
+impl Hull {
+    #[must_use]
+    pub fn get(&self, position: Position) -> Color {
+        self.panels.get(&position).copied().unwrap_or_default()
+    }
+
+    /// Every panel which was painted at least once, whatever color it ended up.
+    #[must_use]
+    pub fn painted(&self) -> &HashSet<Position> {
+        &self.painted
+    }
+
+    fn paint(&mut self, position: Position, color: Color) {
+        self.panels.insert(position, color);
+        self.painted.insert(position);
+    }
+
+    /// Draw the white panels as `#` and the black panels as `.`, cropped to the white panels.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let white: Vec<Position> = self
+            .panels
+            .iter()
+            .filter(|(_, color)| **color == Color::White)
+            .map(|(position, _)| *position)
+            .collect();
+        let Some(min_x) = white.iter().map(|(x, _)| *x).min() else {
+            return String::new();
+        };
+        let max_x = white.iter().map(|(x, _)| *x).max().unwrap_or(min_x);
+        let min_y = white.iter().map(|(_, y)| *y).min().unwrap_or_default();
+        let max_y = white.iter().map(|(_, y)| *y).max().unwrap_or(min_y);
+
+        let mut image = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                image.push(match self.get((x, y)) {
+                    Color::White => '#',
+                    Color::Black => '.',
+                });
+            }
+            image.push('\n');
+        }
+        image
+    }
+}
+
+/// Run the robot until it halts, starting on a panel of color `start`.
+///
+/// # Errors
+///
+/// Will return `PaintError` if the program fails or the robot's outputs don't make sense.
+pub fn paint(program: &Memory, start: Color) -> Result<Hull, PaintError> {
+    let mut computer = Computer::new(program.clone());
+    let mut hull = Hull::default();
+    hull.panels.insert((0, 0), start);
+    let (mut position, mut heading) = ((0, 0), (0, -1));
+
+    loop {
+        computer.push_input(hull.get(position).value());
+        let color = match computer.run_until_event()? {
+            RunState::Output(0) => Color::Black,
+            RunState::Output(1) => Color::White,
+            RunState::Output(value) => return Err(PaintError::UnexpectedOutput(value)),
+            RunState::NeedsInput | RunState::Halted => return Ok(hull),
+        };
+        hull.paint(position, color);
+
+        heading = match computer.run_until_event()? {
+            RunState::Output(0) => (heading.1, -heading.0),
+            RunState::Output(1) => (-heading.1, heading.0),
+            RunState::Output(value) => return Err(PaintError::UnexpectedOutput(value)),
+            RunState::NeedsInput | RunState::Halted => return Err(PaintError::MissingTurn),
+        };
+        position = (position.0 + heading.0, position.1 + heading.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    /// Makes the moves from the example in the puzzle, checking that the robot is told its
+    /// starting panel is white when it returns there.
+    const EXAMPLE: &str = "
+                in   color
+                out  #1
+                out  #0
+                in   color
+                out  #0
+                out  #0
+                in   color
+                out  #1
+                out  #0
+                in   color
+                out  #1
+                out  #0
+                in   color
+                jz   color #wrong
+                out  #0
+                out  #1
+                in   color
+                out  #1
+                out  #0
+                in   color
+                out  #1
+                out  #0
+                hlt
+        wrong:  out  #7
+                hlt
+        color: data 0
+    ";
+
+    #[test]
+    fn test_paint() {
+        let program = assemble(EXAMPLE).unwrap().parse().unwrap();
+        let hull = paint(&program, Color::Black).unwrap();
+        assert_eq!(hull.painted().len(), 6);
+        assert_eq!(hull.get((0, 0)), Color::Black);
+        assert_eq!(hull.get((1, -1)), Color::White);
+        assert_eq!(hull.render(), "..#\n..#\n##.\n");
+    }
+
+    #[test]
+    fn test_start_color() {
+        let program = assemble("in c\nout c\nout #1\nhlt\nc: data 0")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(paint(&program, Color::White).unwrap().render(), "#\n");
+        assert_eq!(paint(&program, Color::Black).unwrap().render(), "");
+    }
+
+    #[test]
+    fn test_errors() {
+        let missing_turn = assemble("in c\nout #1\nhlt\nc: data 0").unwrap();
+        assert_eq!(
+            paint(&missing_turn.parse().unwrap(), Color::Black),
+            Err(PaintError::MissingTurn)
+        );
+        let bad_color = assemble("in c\nout #2\nhlt\nc: data 0").unwrap();
+        assert_eq!(
+            paint(&bad_color.parse().unwrap(), Color::Black),
+            Err(PaintError::UnexpectedOutput(2))
+        );
+    }
+}
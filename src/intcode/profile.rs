@@ -0,0 +1,299 @@
+/// Opt-in execution statistics, for finding out where a program spends its time.
+use super::{AddressMap, Cell, Computer, Instruction, Memory};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+/// How many of the hottest addresses are listed when a profile is displayed.
+const HOT_ADDRESSES_SHOWN: usize = 10;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+    steps: usize,
+    mnemonics: BTreeMap<&'static str, usize>,
+    addresses: AddressMap<usize>,
+}
+
+impl Profile {
+    pub(super) fn record(&mut self, instruction: &Instruction, instruction_pointer: usize) {
+        self.steps += 1;
+        *self.mnemonics.entry(instruction.mnemonic()).or_default() += 1;
+        *self.addresses.entry(instruction_pointer).or_default() += 1;
+    }
+
+    /// The total number of instructions executed.
+    #[must_use]
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// The number of times instructions with the given mnemonic (see `Instruction::mnemonic`)
+    /// were executed.
+    #[must_use]
+    pub fn count(&self, mnemonic: &str) -> usize {
+        self.mnemonics.get(mnemonic).copied().unwrap_or_default()
+    }
+
+    /// The `count` most executed instruction addresses, most executed first, with the number
+    /// of times each was executed.
+    #[must_use]
+    pub fn hot_addresses(&self, count: usize) -> Vec<(usize, usize)> {
+        let mut addresses: Vec<(usize, usize)> = self
+            .addresses
+            .iter()
+            .map(|(address, hits)| (*address, *hits))
+            .collect();
+        addresses.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        addresses.truncate(count);
+        addresses
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "{} instructions executed", self.steps)?;
+        for (mnemonic, count) in &self.mnemonics {
+            writeln!(f, "  {mnemonic:<4}{count:>12}")?;
+        }
+        writeln!(f, "hot addresses:")?;
+        for (address, hits) in self.hot_addresses(HOT_ADDRESSES_SHOWN) {
+            writeln!(f, "  {address:04}{hits:>12}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The highest addresses accessed by a program, for finding out how far beyond its own cells
+/// it reaches. Every cell of an executed instruction counts as read, including its opcode and
+/// any immediate operands.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    highest_read: Option<usize>,
+    highest_written: Option<usize>,
+}
+
+impl MemoryStats {
+    pub(super) fn record(&mut self, instruction: &Instruction, instruction_pointer: usize) {
+        let fetched = instruction_pointer + instruction.get_register_change() - 1;
+        let read = instruction.get_read_registers().into_iter().max();
+        self.highest_read = self.highest_read.max(Some(fetched)).max(read);
+        self.highest_written = self.highest_written.max(instruction.get_written_register());
+    }
+
+    /// The highest address read, or `None` if nothing has been executed.
+    #[must_use]
+    pub fn highest_read(&self) -> Option<usize> {
+        self.highest_read
+    }
+
+    /// The highest address written, or `None` if nothing has been written.
+    #[must_use]
+    pub fn highest_written(&self) -> Option<usize> {
+        self.highest_written
+    }
+
+    /// The highest address either read or written.
+    #[must_use]
+    pub fn highest_accessed(&self) -> Option<usize> {
+        self.highest_read.max(self.highest_written)
+    }
+}
+
+/// The addresses executed by a program, for finding out which parts of it a run reached. Every
+/// cell of an executed instruction is covered, including its operands.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    program_length: usize,
+    instructions: BTreeSet<usize>,
+    cells: BTreeSet<usize>,
+}
+
+impl Coverage {
+    fn new(program_length: usize) -> Self {
+        Self {
+            program_length,
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn record(&mut self, instruction: &Instruction, instruction_pointer: usize) {
+        self.instructions.insert(instruction_pointer);
+        // a halt does not move the instruction pointer, but still occupies a cell.
+        let length = instruction.get_register_change().max(1);
+        self.cells
+            .extend(instruction_pointer..instruction_pointer + length);
+    }
+
+    /// The length of the program (see `Memory::extent`) when tracking began.
+    #[must_use]
+    pub fn program_length(&self) -> usize {
+        self.program_length
+    }
+
+    /// Whether an instruction starting at `address` was executed.
+    #[must_use]
+    pub fn executed(&self, address: usize) -> bool {
+        self.instructions.contains(&address)
+    }
+
+    /// The address of every instruction executed, in order.
+    pub fn instructions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.instructions.iter().copied()
+    }
+
+    /// The number of cells of the program which were executed, either as opcodes or operands.
+    #[must_use]
+    pub fn cells_covered(&self) -> usize {
+        self.cells.range(..self.program_length).count()
+    }
+
+    /// The listing of `memory` given by `Memory::dump`, with each row starting with an executed
+    /// instruction marked with `+`. Usually `memory` is the program as it was before running,
+    /// since code may have been overwritten since.
+    #[must_use]
+    pub fn annotate<T: Cell>(&self, memory: &Memory<T>) -> String {
+        memory.listing(None, |pos| if self.executed(pos) { '+' } else { ' ' })
+    }
+}
+
+impl Display for Coverage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let covered = self.cells_covered();
+        let percentage = (covered * 100)
+            .checked_div(self.program_length)
+            .unwrap_or(100);
+        write!(
+            f,
+            "{covered} of {} cells executed ({percentage}%)",
+            self.program_length
+        )
+    }
+}
+
+impl<T: Cell> Computer<T> {
+    /// Start counting executed instructions, discarding any previous profile. While profiling,
+    /// instructions are always interpreted, even if the compiled engine was chosen.
+    pub fn start_profiling(&mut self) {
+        *self.profile_mut() = Some(Profile::default());
+    }
+
+    /// Stop profiling, returning the statistics gathered.
+    pub fn stop_profiling(&mut self) -> Option<Profile> {
+        self.profile_mut().take()
+    }
+
+    /// Start recording the highest addresses read and written, discarding any previous record.
+    /// While recording, instructions are always interpreted, even if the compiled engine was
+    /// chosen.
+    pub fn track_memory(&mut self) {
+        *self.memory_stats_mut() = Some(MemoryStats::default());
+    }
+
+    /// The addresses accessed since `track_memory` was called, or `None` if it has not been.
+    #[must_use]
+    pub fn memory_stats(&self) -> Option<MemoryStats> {
+        self.memory_stats_ref().copied()
+    }
+
+    /// Start recording which addresses are executed, discarding any previous record. Coverage is
+    /// measured against the program as it is in memory now. While recording, instructions are
+    /// always interpreted, even if the compiled engine was chosen.
+    pub fn track_coverage(&mut self) {
+        *self.coverage_mut() = Some(Coverage::new(self.memory().extent()));
+    }
+
+    /// The addresses executed since `track_coverage` was called, or `None` if it has not been.
+    #[must_use]
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::RunState;
+
+    #[test]
+    fn test_profile() {
+        // counts down from 3, outputting each value.
+        let mut computer: Computer = "4,10,1001,10,-1,10,1005,10,0,99,3".parse().unwrap();
+        computer.start_profiling();
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [3, 2, 1]);
+        computer.run_until_event().unwrap();
+
+        let profile = computer.stop_profiling().unwrap();
+        assert_eq!(profile.steps(), 9);
+        assert_eq!(profile.count("out"), 3);
+        assert_eq!(profile.count("add"), 3);
+        assert_eq!(profile.count("jnz"), 3);
+        assert_eq!(profile.count("hlt"), 0);
+        assert_eq!(profile.hot_addresses(2), [(0, 3), (2, 3)]);
+        assert!(profile
+            .to_string()
+            .starts_with("9 instructions executed\n  add"));
+        assert!(computer.stop_profiling().is_none());
+    }
+
+    #[test]
+    fn test_memory_stats() {
+        // reads an input, stores it at 1000 and outputs it from there via the relative base.
+        let mut computer: Computer = "3,1000,109,990,204,10,99".parse().unwrap();
+        computer.set_engine(crate::intcode::Engine::Compiled);
+        assert_eq!(computer.memory_stats(), None);
+
+        computer.track_memory();
+        assert_eq!(computer.run_until_event(), Ok(RunState::NeedsInput));
+        assert_eq!(computer.memory_stats(), Some(MemoryStats::default()));
+
+        computer.push_input(42);
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [42]);
+        let stats = computer.memory_stats().unwrap();
+        assert_eq!(stats.highest_written(), Some(1000));
+        assert_eq!(stats.highest_read(), Some(1000));
+        assert_eq!(stats.highest_accessed(), Some(1000));
+    }
+
+    #[test]
+    fn test_coverage() {
+        // outputs whether its input is positive; the branch not taken is never executed.
+        let program: Memory = "3,12,1007,12,1,13,1005,13,11,104,1,99,0,0".parse().unwrap();
+        let mut computer = Computer::new(program.clone());
+        computer.set_engine(crate::intcode::Engine::Compiled);
+        computer.track_coverage();
+        computer.push_input(0);
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), Vec::<i64>::new());
+
+        let coverage = computer.coverage().unwrap();
+        assert_eq!(coverage.program_length(), 12);
+        assert_eq!(coverage.instructions().collect::<Vec<_>>(), [0, 2, 6, 11]);
+        assert!(!coverage.executed(9));
+        assert_eq!(coverage.cells_covered(), 10);
+        assert_eq!(coverage.to_string(), "10 of 12 cells executed (83%)");
+        assert_eq!(
+            coverage.annotate(&program),
+            [
+                "+ 0000: 3 12          in   12",
+                "+ 0002: 1007 12 1 13  lt   12 #1 13",
+                "+ 0006: 1005 13 11    jnz  13 #11",
+                "  0009: 104 1         out  #1",
+                "+ 0011: 99            hlt",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_memory_stats_fetches() {
+        let mut computer: Computer = "1101,2,3,5,99,0".parse().unwrap();
+        computer.track_memory();
+        computer.run_until_event().unwrap();
+
+        let stats = computer.memory_stats().unwrap();
+        assert_eq!(stats.highest_read(), Some(3));
+        assert_eq!(stats.highest_written(), Some(5));
+        assert_eq!(stats.highest_accessed(), Some(5));
+    }
+}
@@ -0,0 +1,169 @@
+/// Python bindings for the Intcode VM, so that programs can be explored from a notebook while
+/// running on the same machine as the solutions. To build a module which Python can import:
+///
+/// ```text
+/// PYO3_BUILD_EXTENSION_MODULE=1 cargo rustc --lib --release --features python --crate-type cdylib
+/// cp target/release/libadvent_of_code.so intcode.so
+/// ```
+///
+/// after which `from intcode import Computer` gives a class wrapping `Computer`.
+use super::{Computer, ExecutionError, Instruction, RunState};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+fn execution_error(e: ExecutionError) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// An Intcode machine, created from a program in the comma-separated form of a puzzle input.
+/// Copying one with `copy.copy` branches execution from the current state.
+#[pyclass(name = "Computer", module = "intcode", skip_from_py_object)]
+#[derive(Clone)]
+pub struct PyComputer {
+    computer: Computer,
+}
+
+#[pymethods]
+impl PyComputer {
+    #[new]
+    fn new(program: &str) -> PyResult<Self> {
+        let computer = program
+            .parse()
+            .map_err(|_| PyValueError::new_err("invalid Intcode program"))?;
+        Ok(Self { computer })
+    }
+
+    /// Queue a value to be consumed by the next input instruction.
+    fn push_input(&mut self, value: i64) {
+        self.computer.push_input(value);
+    }
+
+    /// Queue a line of ASCII input, terminated by a newline.
+    fn send_line(&mut self, line: &str) {
+        self.computer.send_line(line);
+    }
+
+    /// Run until the program needs input which has not been queued or halts, returning the
+    /// values it output.
+    fn run(&mut self) -> PyResult<Vec<i64>> {
+        let mut outputs = Vec::new();
+        while let RunState::Output(value) =
+            self.computer.run_until_event().map_err(execution_error)?
+        {
+            outputs.push(value);
+        }
+        Ok(outputs)
+    }
+
+    /// Execute a single instruction, returning the value output by it, if any.
+    fn step(&mut self) -> PyResult<Option<i64>> {
+        match self.computer.step().map_err(execution_error)? {
+            Some(RunState::Output(value)) => Ok(Some(value)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Read one line of ASCII output, or None if the program stops without printing anything.
+    fn read_line(&mut self) -> PyResult<Option<String>> {
+        self.computer.read_line().map_err(execution_error)
+    }
+
+    /// Read all ASCII output until the program asks for input or halts.
+    fn read_until_prompt(&mut self) -> PyResult<String> {
+        self.computer.read_until_prompt().map_err(execution_error)
+    }
+
+    /// The value stored at an address.
+    fn read(&self, address: usize) -> i64 {
+        self.computer.memory().read_register(address)
+    }
+
+    /// Store a value at an address.
+    fn write(&mut self, address: usize, value: i64) {
+        self.computer.memory_mut().set_register(address, value);
+    }
+
+    /// A listing of memory with the next instruction marked.
+    fn dump(&self) -> String {
+        self.computer.dump()
+    }
+
+    #[getter]
+    fn instruction_pointer(&self) -> usize {
+        self.computer.instruction_pointer()
+    }
+
+    #[getter]
+    fn relative_base(&self) -> i64 {
+        self.computer.relative_base()
+    }
+
+    /// The number of instructions executed so far.
+    #[getter]
+    fn steps(&self) -> usize {
+        self.computer.steps()
+    }
+
+    /// Whether the next instruction is a halt.
+    #[getter]
+    fn halted(&self) -> bool {
+        self.computer.next_instruction() == Ok(Instruction::Halt)
+    }
+
+    /// Whether the next instruction is an input and no input is queued for it.
+    #[getter]
+    fn needs_input(&self) -> bool {
+        matches!(self.computer.next_instruction(), Ok(Instruction::Input(_)))
+            && self.computer.pending_inputs() == 0
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Computer(instruction_pointer={}, relative_base={}, steps={})",
+            self.computer.instruction_pointer(),
+            self.computer.relative_base(),
+            self.computer.steps()
+        )
+    }
+}
+
+#[pymodule]
+fn intcode(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyComputer>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let mut computer = PyComputer::new("3,9,8,9,10,9,4,9,99,-1,8").unwrap();
+        assert!(computer.run().unwrap().is_empty());
+        assert!(computer.needs_input());
+
+        let mut branch = computer.__copy__();
+        computer.push_input(8);
+        assert_eq!(computer.run().unwrap(), [1]);
+        assert!(computer.halted());
+
+        branch.push_input(7);
+        assert_eq!(branch.run().unwrap(), [0]);
+        assert_eq!(branch.steps(), 3);
+    }
+
+    #[test]
+    fn test_memory() {
+        let mut computer = PyComputer::new("1,0,0,0,99").unwrap();
+        computer.write(1, 4);
+        assert_eq!(computer.step().unwrap(), None);
+        assert_eq!(computer.read(0), 100);
+        assert_eq!(computer.instruction_pointer(), 4);
+        assert!(PyComputer::new("1,x").is_err());
+        assert!(PyComputer::new("42").unwrap().run().is_err());
+    }
+}
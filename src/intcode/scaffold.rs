@@ -0,0 +1,280 @@
+/// Plans the day 17 vacuum robot's route: traces the path along the scaffold from the camera
+/// image, then compresses it into a main routine and three movement functions, each of which
+/// must fit in the robot's twenty character memory.
+use super::{Cell, Computer};
+use std::fmt::Display;
+
+/// The most characters the robot accepts in the main routine or a movement function, not
+/// counting the newline.
+pub const MAX_LENGTH: usize = 20;
+
+/// The names of the movement functions, as called from the main routine.
+const NAMES: [char; 3] = ['A', 'B', 'C'];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Left,
+    Right,
+    Forward(usize),
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Left => write!(f, "L"),
+            Command::Right => write!(f, "R"),
+            Command::Forward(distance) => write!(f, "{distance}"),
+        }
+    }
+}
+
+/// Format commands the way the robot reads them, separated by commas.
+fn format(commands: &[Command]) -> String {
+    commands
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Whether a camera image character shows scaffold, including scaffold with the robot on it.
+fn is_scaffold(ch: u8) -> bool {
+    matches!(ch, b'#' | b'^' | b'v' | b'<' | b'>')
+}
+
+/// Follow the scaffold from the robot's position in the camera image, going straight on at
+/// every intersection and turning only at corners, until it reaches a dead end. Returns `None`
+/// if the image does not show the robot.
+#[must_use]
+pub fn trace_path(image: &str) -> Option<Vec<Command>> {
+    let grid: Vec<&[u8]> = image.lines().map(str::as_bytes).collect();
+    let scaffold = |(x, y): (i64, i64)| {
+        let row = usize::try_from(y).ok().and_then(|y| grid.get(y));
+        let ch = row.and_then(|row| usize::try_from(x).ok().and_then(|x| row.get(x)));
+        ch.is_some_and(|ch| is_scaffold(*ch))
+    };
+
+    let (mut position, mut heading) = grid.iter().zip(0..).find_map(|(row, y)| {
+        row.iter().zip(0..).find_map(|(ch, x)| {
+            let heading = match ch {
+                b'^' => (0, -1),
+                b'v' => (0, 1),
+                b'<' => (-1, 0),
+                b'>' => (1, 0),
+                _ => return None,
+            };
+            Some(((x, y), heading))
+        })
+    })?;
+
+    let mut path = Vec::new();
+    let mut distance = 0;
+    loop {
+        let ahead = (position.0 + heading.0, position.1 + heading.1);
+        if scaffold(ahead) {
+            position = ahead;
+            distance += 1;
+            continue;
+        }
+        if distance > 0 {
+            path.push(Command::Forward(distance));
+            distance = 0;
+        }
+
+        let left = (heading.1, -heading.0);
+        let right = (-heading.1, heading.0);
+        if scaffold((position.0 + left.0, position.1 + left.1)) {
+            path.push(Command::Left);
+            heading = left;
+        } else if scaffold((position.0 + right.0, position.1 + right.1)) {
+            path.push(Command::Right);
+            heading = right;
+        } else {
+            return Some(path);
+        }
+    }
+}
+
+/// A main routine and the three movement functions it calls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Routines {
+    main: String,
+    functions: [String; 3],
+}
+
+impl Routines {
+    #[must_use]
+    pub fn main(&self) -> &str {
+        &self.main
+    }
+
+    /// Movement functions A, B and C. Any the path did not need are empty.
+    #[must_use]
+    pub fn functions(&self) -> &[String; 3] {
+        &self.functions
+    }
+
+    /// Queue the routines as input to the robot, followed by the answer to whether it should
+    /// show a continuous video feed.
+    pub fn send<T: Cell>(&self, computer: &mut Computer<T>, video_feed: bool) {
+        computer.send_line(&self.main);
+        for function in &self.functions {
+            computer.send_line(function);
+        }
+        computer.send_line(if video_feed { "y" } else { "n" });
+    }
+}
+
+/// Split the path into a main routine calling up to three movement functions, none of them
+/// longer than `MAX_LENGTH` characters. Returns `None` if the path can't be split up that way.
+///
+/// This is a depth-first search: at each point in the path, try calling each function defined so
+/// far, then (if there's room for another) each possible new function starting there, longest
+/// first.
+#[must_use]
+pub fn compress(path: &[Command]) -> Option<Routines> {
+    let mut functions = Vec::new();
+    let mut calls = Vec::new();
+    if !search(path, &mut functions, &mut calls) {
+        return None;
+    }
+
+    let main = calls
+        .iter()
+        .map(|index| NAMES[*index].to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut formatted = functions.into_iter().map(format);
+    let functions = [(); 3].map(|()| formatted.next().unwrap_or_default());
+    Some(Routines { main, functions })
+}
+
+fn search<'a>(
+    path: &'a [Command],
+    functions: &mut Vec<&'a [Command]>,
+    calls: &mut Vec<usize>,
+) -> bool {
+    if path.is_empty() {
+        return true;
+    }
+    // each call takes a name and a comma, apart from the last which needs no comma.
+    if calls.len() == MAX_LENGTH.div_ceil(2) {
+        return false;
+    }
+
+    for index in 0..functions.len() {
+        let function = functions[index];
+        if path.starts_with(function) {
+            calls.push(index);
+            if search(&path[function.len()..], functions, calls) {
+                return true;
+            }
+            calls.pop();
+        }
+    }
+
+    if functions.len() < NAMES.len() {
+        let longest = (1..=path.len())
+            .take_while(|length| format(&path[..*length]).len() <= MAX_LENGTH)
+            .last()
+            .unwrap_or(0);
+        for length in (1..=longest).rev() {
+            functions.push(&path[..length]);
+            calls.push(functions.len() - 1);
+            if search(&path[length..], functions, calls) {
+                return true;
+            }
+            calls.pop();
+            functions.pop();
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE: &str = "\
+#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......";
+
+    const PATH: &str = "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2";
+
+    fn parse(text: &str) -> Vec<Command> {
+        text.split(',')
+            .map(|token| match token {
+                "L" => Command::Left,
+                "R" => Command::Right,
+                distance => Command::Forward(distance.parse().unwrap()),
+            })
+            .collect()
+    }
+
+    /// The full path the robot follows when running the routines.
+    fn expand(routines: &Routines) -> String {
+        routines
+            .main()
+            .split(',')
+            .map(|name| {
+                let index = NAMES.iter().position(|n| n.to_string() == name).unwrap();
+                routines.functions()[index].as_str()
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn test_trace_path() {
+        assert_eq!(trace_path(IMAGE), Some(parse(PATH)));
+        assert_eq!(trace_path("###\n"), None);
+    }
+
+    #[test]
+    fn test_compress() {
+        let routines = compress(&parse(PATH)).unwrap();
+        assert_eq!(expand(&routines), PATH);
+        assert!(routines.main().len() <= MAX_LENGTH);
+        assert!(routines.functions().iter().all(|f| f.len() <= MAX_LENGTH));
+    }
+
+    #[test]
+    fn test_compress_long_path() {
+        let a = "R,12,L,10,R,12";
+        let b = "L,8,R,10,R,6";
+        let c = "R,12,L,10,R,10,L,8";
+        let path = [a, b, a, c, b, c, b, c, a, c].join(",");
+        let routines = compress(&parse(&path)).unwrap();
+        assert_eq!(expand(&routines), path);
+        assert!(routines.main().len() <= MAX_LENGTH);
+        assert!(routines.functions().iter().all(|f| f.len() <= MAX_LENGTH));
+    }
+
+    #[test]
+    fn test_compress_impossible() {
+        let path = "L,1,L,2,L,3,L,4,L,5,L,6,L,7,L,8,L,9,L,10,L,11,L,12,L,13,L,14,L,15,L,16";
+        assert_eq!(compress(&parse(path)), None);
+    }
+
+    #[test]
+    fn test_send() {
+        let routines = compress(&parse("L,4,R,2")).unwrap();
+        let mut computer: Computer = "99".parse().unwrap();
+        routines.send(&mut computer, false);
+        assert_eq!(computer.pending_inputs(), "A\nL,4,R,2\n\n\nn\n".len());
+    }
+}
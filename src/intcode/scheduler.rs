@@ -0,0 +1,278 @@
+/// Runs several computers together, with the outputs of each optionally feeding the inputs of
+/// another, either deterministically on one thread or with a thread per computer.
+use super::{Cell, Computer, ExecutionError, RunState};
+use std::sync::mpsc;
+use std::thread;
+
+/// How the computers in a `Cluster` take turns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scheduling {
+    /// A single thread runs the computers in a fixed order, so every run interleaves them in the
+    /// same way (see `Cluster::run`).
+    #[default]
+    Deterministic,
+    /// Each computer runs on its own thread, passing values over channels. The order in which
+    /// computers run depends on the operating system, so only programs which do not care about
+    /// it give the same results every time.
+    Threaded,
+}
+
+struct Node<T> {
+    computer: Computer<T>,
+    destination: Option<usize>,
+    outputs: Vec<T>,
+    halted: bool,
+}
+
+pub struct Cluster<T = i64> {
+    nodes: Vec<Node<T>>,
+}
+
+impl<T> Default for Cluster<T> {
+    fn default() -> Self {
+        Self { nodes: Vec::new() }
+    }
+}
+
+impl<T: Cell> Cluster<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a computer, returning the index which identifies it. Its outputs are only recorded
+    /// until it is connected to another computer.
+    pub fn add(&mut self, computer: Computer<T>) -> usize {
+        self.nodes.push(Node {
+            computer,
+            destination: None,
+            outputs: Vec::new(),
+            halted: false,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Send every output of computer `from` to the input of computer `to`, replacing any
+    /// previous destination.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if either index does not identify a computer in the cluster.
+    pub fn connect(&mut self, from: usize, to: usize) {
+        assert!(to < self.nodes.len(), "no computer with index {to}");
+        self.nodes[from].destination = Some(to);
+    }
+
+    #[must_use]
+    pub fn computer(&self, ix: usize) -> Option<&Computer<T>> {
+        self.nodes.get(ix).map(|node| &node.computer)
+    }
+
+    pub fn computer_mut(&mut self, ix: usize) -> Option<&mut Computer<T>> {
+        self.nodes.get_mut(ix).map(|node| &mut node.computer)
+    }
+
+    /// Every value output by computer `ix` so far, whether or not it was passed on.
+    #[must_use]
+    pub fn outputs(&self, ix: usize) -> &[T] {
+        self.nodes.get(ix).map_or(&[], |node| &node.outputs)
+    }
+
+    /// Whether computer `ix` has halted.
+    #[must_use]
+    pub fn halted(&self, ix: usize) -> bool {
+        self.nodes.get(ix).is_some_and(|node| node.halted)
+    }
+
+    fn run_deterministic(&mut self) -> Result<(), ExecutionError> {
+        loop {
+            let mut progressed = false;
+            for ix in 0..self.nodes.len() {
+                if self.nodes[ix].halted {
+                    continue;
+                }
+                let steps = self.nodes[ix].computer.steps();
+                loop {
+                    let node = &mut self.nodes[ix];
+                    match node.computer.run_until_event()? {
+                        RunState::Output(value) => {
+                            node.outputs.push(value.clone());
+                            if let Some(destination) = node.destination {
+                                self.nodes[destination].computer.push_input(value);
+                            }
+                        }
+                        RunState::NeedsInput => break,
+                        RunState::Halted => {
+                            node.halted = true;
+                            break;
+                        }
+                    }
+                }
+                progressed |= self.nodes[ix].computer.steps() != steps;
+            }
+            if !progressed {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<T: Cell + Send + 'static> Cluster<T> {
+    /// Run the computers until every one has halted or is waiting for input which will never
+    /// arrive. Inputs already queued on a computer are used before any passed on to it.
+    ///
+    /// With `Scheduling::Deterministic`, the computers take turns in the order they were added.
+    /// On its turn, a computer runs until it needs input which has not been queued, or halts,
+    /// and each value it outputs is queued on its destination as soon as it is produced. Rounds
+    /// of turns continue until one passes in which no computer executes an instruction.
+    ///
+    /// With `Scheduling::Threaded`, a computer waiting for input stops once every computer which
+    /// could send it some has stopped. Computers waiting on each other in a cycle never stop, so
+    /// a run which the deterministic scheduler would end in deadlock never returns.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ExecutionError` if any computer executes an invalid instruction. The
+    /// deterministic scheduler stops straight away, while the threaded one waits for the other
+    /// computers to stop and reports the failure of the first in the cluster.
+    pub fn run(&mut self, scheduling: Scheduling) -> Result<(), ExecutionError> {
+        match scheduling {
+            Scheduling::Deterministic => self.run_deterministic(),
+            Scheduling::Threaded => self.run_threaded(),
+        }
+    }
+
+    fn run_threaded(&mut self) -> Result<(), ExecutionError> {
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            self.nodes.iter().map(|_| mpsc::channel()).unzip();
+        let handles: Vec<_> = self
+            .nodes
+            .drain(..)
+            .zip(receivers)
+            .map(|(mut node, mut input)| {
+                let destination = node.destination.map(|ix| senders[ix].clone());
+                thread::spawn(move || {
+                    let mut output = |value: T| {
+                        if let Some(sender) = &destination {
+                            let _ = sender.send(value.clone());
+                        }
+                        node.outputs.push(value);
+                    };
+                    let result = node.computer.run_with(&mut input, &mut output);
+                    node.halted = result.is_ok();
+                    (node, result)
+                })
+            })
+            .collect();
+        drop(senders);
+
+        let mut failure = None;
+        for handle in handles {
+            let (node, result) = handle.join().expect("computer thread panicked");
+            self.nodes.push(node);
+            match result {
+                Ok(()) | Err(ExecutionError::InputExhausted) => {}
+                Err(e) => {
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+        failure.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The day 7 example which amplifies a signal around a loop of five amplifiers.
+    const AMPLIFIER: &str = "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,\
+                             28,1005,28,6,99,0,0,5";
+
+    fn amplifier_loop() -> Cluster {
+        let mut cluster: Cluster = Cluster::new();
+        for phase in [9, 8, 7, 6, 5] {
+            let mut computer: Computer = AMPLIFIER.parse().unwrap();
+            computer.push_input(phase);
+            cluster.add(computer);
+        }
+        for ix in 0..5 {
+            cluster.connect(ix, (ix + 1) % 5);
+        }
+        cluster.computer_mut(0).unwrap().push_input(0);
+        cluster
+    }
+
+    #[test]
+    fn test_schedulers_agree() {
+        for scheduling in [Scheduling::Deterministic, Scheduling::Threaded] {
+            let mut cluster = amplifier_loop();
+            assert_eq!(cluster.run(scheduling), Ok(()));
+            assert_eq!(cluster.outputs(4).last(), Some(&139_629_729));
+            assert!((0..5).all(|ix| cluster.halted(ix)));
+        }
+    }
+
+    #[test]
+    fn test_deterministic_interleaving() {
+        // each computer outputs its input plus one, forever, and the first starts at zero. Both
+        // produce five values before the first runs into its step limit.
+        let program = "3,9,1001,9,1,9,4,9,1105,1,0,0";
+        let mut cluster: Cluster = Cluster::new();
+        for _ in 0..2 {
+            let mut computer: Computer = program.parse().unwrap();
+            computer.set_step_limit(Some(20));
+            cluster.add(computer);
+        }
+        cluster.connect(0, 1);
+        cluster.connect(1, 0);
+        cluster.computer_mut(0).unwrap().push_input(0);
+
+        assert_eq!(
+            cluster.run(Scheduling::Deterministic),
+            Err(ExecutionError::StepLimitExceeded)
+        );
+        assert_eq!(cluster.outputs(0), [1, 3, 5, 7, 9]);
+        assert_eq!(cluster.outputs(1), [2, 4, 6, 8, 10]);
+    }
+
+    #[test]
+    fn test_deadlock() {
+        // both computers wait for the other to send something first.
+        let mut cluster: Cluster = Cluster::new();
+        for _ in 0..2 {
+            cluster.add("3,0,4,0,99".parse().unwrap());
+        }
+        cluster.connect(0, 1);
+        cluster.connect(1, 0);
+        assert_eq!(cluster.run(Scheduling::Deterministic), Ok(()));
+        assert!(!cluster.halted(0) && !cluster.halted(1));
+        assert_eq!(cluster.computer(0).unwrap().steps(), 0);
+    }
+
+    #[test]
+    fn test_unconnected_input() {
+        // the second computer has no source of input, so stops waiting under both schedulers.
+        for scheduling in [Scheduling::Deterministic, Scheduling::Threaded] {
+            let mut cluster: Cluster = Cluster::new();
+            cluster.add("104,1,99".parse().unwrap());
+            cluster.add("3,0,99".parse().unwrap());
+            assert_eq!(cluster.run(scheduling), Ok(()));
+            assert!(cluster.halted(0) && !cluster.halted(1));
+            assert_eq!(cluster.outputs(0), [1]);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        for scheduling in [Scheduling::Deterministic, Scheduling::Threaded] {
+            let mut cluster: Cluster = Cluster::new();
+            cluster.add("3,0,99".parse().unwrap());
+            cluster.add("98".parse().unwrap());
+            assert_eq!(
+                cluster.run(scheduling),
+                Err(ExecutionError::InvalidOpcode(0))
+            );
+        }
+    }
+}
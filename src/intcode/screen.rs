@@ -0,0 +1,145 @@
+/// Collects output which arrives as `(x, y, value)` triples into a sparse grid of tiles, as
+/// produced by the arcade cabinet, the hull-painting robot and other screen-drawing programs.
+use super::{Cell, OutputSink};
+use std::collections::HashMap;
+
+/// The position at which a triple carries a score rather than a tile.
+pub const SCORE_POSITION: (i64, i64) = (-1, 0);
+
+#[derive(Clone, Debug, Default)]
+pub struct ScreenBuffer<T = i64> {
+    tiles: HashMap<(i64, i64), T>,
+    pending: Vec<T>,
+    score: Option<T>,
+}
+
+impl<T: Cell> ScreenBuffer<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the next output value. Every third value completes a triple, which either draws a
+    /// tile or, at `SCORE_POSITION`, updates the score. Triples whose coordinates do not fit in
+    /// an `i64` are ignored.
+    pub fn push(&mut self, value: T) {
+        self.pending.push(value);
+        if self.pending.len() < 3 {
+            return;
+        }
+
+        let Ok([x, y, value]) = <[T; 3]>::try_from(std::mem::take(&mut self.pending)) else {
+            return;
+        };
+        let (Some(x), Some(y)) = (x.to_i64(), y.to_i64()) else {
+            return;
+        };
+
+        if (x, y) == SCORE_POSITION {
+            self.score = Some(value);
+        } else {
+            self.tiles.insert((x, y), value);
+        }
+    }
+
+    /// The most recent score drawn, if any.
+    #[must_use]
+    pub fn score(&self) -> Option<&T> {
+        self.score.as_ref()
+    }
+
+    #[must_use]
+    pub fn get(&self, x: i64, y: i64) -> Option<&T> {
+        self.tiles.get(&(x, y))
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = ((i64, i64), &T)> {
+        self.tiles.iter().map(|(position, tile)| (*position, tile))
+    }
+
+    /// The number of positions currently showing `tile`.
+    #[must_use]
+    pub fn count(&self, tile: &T) -> usize {
+        self.tiles.values().filter(|value| *value == tile).count()
+    }
+
+    /// A position currently showing `tile`, if there is one. If several do, which one is
+    /// returned is unspecified.
+    #[must_use]
+    pub fn find(&self, tile: &T) -> Option<(i64, i64)> {
+        self.tiles
+            .iter()
+            .find(|(_, value)| *value == tile)
+            .map(|(position, _)| *position)
+    }
+
+    /// Draw the screen as text, one character per position from `glyph`, covering the
+    /// smallest rectangle containing every tile drawn. Undrawn positions are spaces.
+    #[must_use]
+    pub fn render(&self, glyph: impl Fn(&T) -> char) -> String {
+        let xs = self.tiles.keys().map(|(x, _)| *x);
+        let ys = self.tiles.keys().map(|(_, y)| *y);
+        let (Some(left), Some(right), Some(top), Some(bottom)) =
+            (xs.clone().min(), xs.max(), ys.clone().min(), ys.max())
+        else {
+            return String::new();
+        };
+
+        (top..=bottom)
+            .map(|y| {
+                (left..=right)
+                    .map(|x| self.get(x, y).map_or(' ', &glyph))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl<T: Cell> OutputSink<T> for ScreenBuffer<T> {
+    fn write_output(&mut self, value: T) {
+        self.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::Computer;
+
+    fn screen(values: &[i64]) -> ScreenBuffer {
+        let mut screen = ScreenBuffer::new();
+        for value in values {
+            screen.push(*value);
+        }
+        screen
+    }
+
+    #[test]
+    fn test_tiles_and_score() {
+        let screen = screen(&[1, 2, 3, 6, 5, 4, -1, 0, 12, 1, 2, 4, -1, 0, 15, 9]);
+        assert_eq!(screen.get(1, 2), Some(&4));
+        assert_eq!(screen.get(6, 5), Some(&4));
+        assert_eq!(screen.get(0, 0), None);
+        assert_eq!(screen.count(&4), 2);
+        assert_eq!(screen.find(&3), None);
+        assert_eq!(screen.score(), Some(&15));
+    }
+
+    #[test]
+    fn test_render() {
+        let screen = screen(&[0, 0, 1, 2, 0, 2, 1, 1, 1]);
+        let glyph = |tile: &i64| if *tile == 1 { '#' } else { 'o' };
+        assert_eq!(screen.render(glyph), "# o\n # ");
+        assert_eq!(ScreenBuffer::<i64>::new().render(glyph), "");
+    }
+
+    #[test]
+    fn test_collects_program_output() {
+        let mut computer: Computer = "104,1,104,2,104,3,104,-1,104,0,104,99,99".parse().unwrap();
+        let mut screen = ScreenBuffer::new();
+        computer.run_with(&mut || None, &mut screen).unwrap();
+        assert_eq!(screen.find(&3), Some((1, 2)));
+        assert_eq!(screen.score(), Some(&99));
+    }
+}
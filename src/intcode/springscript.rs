@@ -0,0 +1,413 @@
+/// Springscript, the language of the day 21 springdroid, and an automated search for a script
+/// that gets the droid across the hull.
+///
+/// A script is a list of `AND`, `OR` and `NOT` statements over the droid's ground sensors and its
+/// two writable registers, `T` and `J`, ending with `WALK` or `RUN`. Whenever `J` is true after
+/// running the script, the droid jumps four tiles forward.
+use super::{Cell, Computer, ExecutionError, Memory};
+use std::fmt::Display;
+
+/// The most statements the droid's memory can hold.
+pub const MAX_STATEMENTS: usize = 15;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    /// The temporary register.
+    T,
+    /// The jump register.
+    J,
+}
+
+impl Register {
+    /// The ground sensors, in order of distance from the droid: `A` is one tile away and `I` is
+    /// nine tiles away.
+    pub const SENSORS: [Register; 9] = [
+        Register::A,
+        Register::B,
+        Register::C,
+        Register::D,
+        Register::E,
+        Register::F,
+        Register::G,
+        Register::H,
+        Register::I,
+    ];
+
+    /// How many tiles ahead of the droid this sensor reads, or `None` for `T` and `J`.
+    fn distance(self) -> Option<usize> {
+        Self::SENSORS
+            .iter()
+            .position(|sensor| *sensor == self)
+            .map(|index| index + 1)
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// A single statement, which reads its first register and writes its second. Only `T` and `J`
+/// may be written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Statement {
+    And(Register, Register),
+    Or(Register, Register),
+    Not(Register, Register),
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Statement::And(x, y) => write!(f, "AND {x} {y}"),
+            Statement::Or(x, y) => write!(f, "OR {x} {y}"),
+            Statement::Not(x, y) => write!(f, "NOT {x} {y}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Sensors `A` to `D` only.
+    Walk,
+    /// All nine sensors.
+    Run,
+}
+
+impl Mode {
+    #[must_use]
+    pub fn sensors(self) -> &'static [Register] {
+        match self {
+            Mode::Walk => &Register::SENSORS[..4],
+            Mode::Run => &Register::SENSORS,
+        }
+    }
+
+    fn command(self) -> &'static str {
+        match self {
+            Mode::Walk => "WALK",
+            Mode::Run => "RUN",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Script {
+    mode: Mode,
+    statements: Vec<Statement>,
+}
+
+impl Script {
+    #[must_use]
+    pub fn new(mode: Mode, statements: Vec<Statement>) -> Self {
+        Self { mode, statements }
+    }
+
+    #[must_use]
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    #[must_use]
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+
+    /// Whether the droid would jump, given whether there is ground at each distance ahead.
+    fn jumps(&self, ground: impl Fn(usize) -> bool) -> bool {
+        let (mut t, mut j) = (false, false);
+        for statement in &self.statements {
+            let (Statement::And(x, y) | Statement::Or(x, y) | Statement::Not(x, y)) = *statement;
+            let value = match x {
+                Register::T => t,
+                Register::J => j,
+                sensor => sensor.distance().is_some_and(&ground),
+            };
+            let target = if y == Register::T { &mut t } else { &mut j };
+            *target = match statement {
+                Statement::And(..) => *target && value,
+                Statement::Or(..) => *target || value,
+                Statement::Not(..) => !value,
+            };
+        }
+        j
+    }
+
+    /// Whether the droid makes it across a hull, given whether there is ground at each tile
+    /// starting from the droid's own. Anything beyond the end of the hull is taken to be ground.
+    #[must_use]
+    pub fn survives(&self, hull: &[bool]) -> bool {
+        let ground = |position: usize| hull.get(position).copied().unwrap_or(true);
+        let mut position = 0;
+        while position < hull.len() {
+            position += if self.jumps(|distance| ground(position + distance)) {
+                4
+            } else {
+                1
+            };
+            if !ground(position) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Display for Script {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for statement in &self.statements {
+            writeln!(f, "{statement}")?;
+        }
+        writeln!(f, "{}", self.mode.command())
+    }
+}
+
+/// The result of running a script on the droid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The droid made it across and reported the amount of hull damage.
+    Damage(i64),
+    /// The droid fell into space; this is the animation of its last moments.
+    Fell(String),
+}
+
+/// Run a script on the springdroid program.
+///
+/// # Errors
+///
+/// Will return `ExecutionError` if the program executes an invalid instruction.
+pub fn run<T: Cell>(program: &Memory<T>, script: &Script) -> Result<Outcome, ExecutionError> {
+    let mut computer = Computer::new(program.clone());
+    computer.read_until_prompt()?;
+    for line in script.to_string().lines() {
+        computer.send_line(line);
+    }
+    let text = computer.read_until_prompt()?;
+    Ok(
+        match computer.take_non_ascii().and_then(|value| value.to_i64()) {
+            Some(damage) => Outcome::Damage(damage),
+            None => Outcome::Fell(text),
+        },
+    )
+}
+
+/// Find the hull the droid fell through in the animation of its last moments.
+fn parse_hull(text: &str) -> Option<Vec<bool>> {
+    text.lines()
+        .find(|line| line.contains('#') && line.bytes().all(|ch| ch == b'#' || ch == b'.'))
+        .map(|line| line.bytes().map(|ch| ch == b'#').collect())
+}
+
+/// Append statements setting `J` to whether any of `sensors` sees a hole.
+fn any_hole(statements: &mut Vec<Statement>, sensors: &[Register]) {
+    for (index, sensor) in sensors.iter().enumerate() {
+        if index == 0 {
+            statements.push(Statement::Not(*sensor, Register::J));
+        } else {
+            statements.push(Statement::Not(*sensor, Register::T));
+            statements.push(Statement::Or(Register::T, Register::J));
+        }
+    }
+}
+
+/// Append statements requiring at least one of `sensors` to see ground before jumping.
+fn any_ground(statements: &mut Vec<Statement>, sensors: &[Register]) {
+    if let Some((first, rest)) = sensors.split_first() {
+        statements.push(Statement::Not(*first, Register::T));
+        statements.push(Statement::Not(Register::T, Register::T));
+        statements.extend(
+            rest.iter()
+                .map(|sensor| Statement::Or(*sensor, Register::T)),
+        );
+        statements.push(Statement::And(Register::T, Register::J));
+    }
+}
+
+/// Every candidate script, shortest first. Each jumps when there is a hole in at least one of
+/// some set of sensors, ground at every one of a second set, and ground at any one of a third.
+fn candidates(mode: Mode) -> Vec<Script> {
+    let sensors = mode.sensors();
+    let mut scripts = Vec::new();
+    for mut choice in 0..4_usize.pow(u32::try_from(sensors.len()).unwrap_or(u32::MAX)) {
+        let (mut holes, mut grounds, mut either) = (Vec::new(), Vec::new(), Vec::new());
+        for sensor in sensors {
+            match choice % 4 {
+                1 => holes.push(*sensor),
+                2 => grounds.push(*sensor),
+                3 => either.push(*sensor),
+                _ => {}
+            }
+            choice /= 4;
+        }
+        // a single sensor in the third set is no different from having it in the second.
+        if holes.is_empty() || either.len() == 1 {
+            continue;
+        }
+
+        let mut statements = Vec::new();
+        any_hole(&mut statements, &holes);
+        statements.extend(
+            grounds
+                .iter()
+                .map(|sensor| Statement::And(*sensor, Register::J)),
+        );
+        any_ground(&mut statements, &either);
+        if statements.len() <= MAX_STATEMENTS {
+            scripts.push(Script::new(mode, statements));
+        }
+    }
+    scripts.sort_by_key(|script| script.statements.len());
+    scripts
+}
+
+/// Search for a script which gets the droid across the hull, returning it along with the hull
+/// damage reported. Returns `None` if no candidate script works.
+///
+/// Each hull the droid falls through is remembered, and later candidates are first checked
+/// against those hulls without running the program, so that only scripts which might work are
+/// ever sent to the droid.
+///
+/// # Errors
+///
+/// Will return `ExecutionError` if the program executes an invalid instruction.
+pub fn search<T: Cell>(
+    program: &Memory<T>,
+    mode: Mode,
+) -> Result<Option<(Script, i64)>, ExecutionError> {
+    search_with(mode, |script| run(program, script))
+}
+
+fn search_with(
+    mode: Mode,
+    mut run: impl FnMut(&Script) -> Result<Outcome, ExecutionError>,
+) -> Result<Option<(Script, i64)>, ExecutionError> {
+    let mut hulls: Vec<Vec<bool>> = Vec::new();
+    for script in candidates(mode) {
+        if !hulls.iter().all(|hull| script.survives(hull)) {
+            continue;
+        }
+        match run(&script)? {
+            Outcome::Damage(damage) => return Ok(Some((script, damage))),
+            Outcome::Fell(text) => hulls.extend(parse_hull(&text)),
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hull(text: &str) -> Vec<bool> {
+        text.bytes().map(|ch| ch == b'#').collect()
+    }
+
+    /// The animation shown when the droid falls, trimmed to its first frame.
+    fn fell(hull: &str) -> Outcome {
+        let blank = ".".repeat(hull.len());
+        Outcome::Fell(format!(
+            "\nWalking...\n\nDidn't make it across:\n\n{blank}\n{blank}\n@{}\n{hull}\n",
+            &blank[1..]
+        ))
+    }
+
+    /// Stands in for the droid program, with the droid facing each of `hulls` in turn.
+    fn droid<'a>(
+        hulls: &'a [&str],
+        runs: &'a mut usize,
+    ) -> impl FnMut(&Script) -> Result<Outcome, ExecutionError> + 'a {
+        move |script| {
+            *runs += 1;
+            Ok(hulls
+                .iter()
+                .find(|text| !script.survives(&hull(text)))
+                .map_or(Outcome::Damage(19_358_262), |text| fell(text)))
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let script = Script::new(
+            Mode::Walk,
+            vec![
+                Statement::Not(Register::A, Register::J),
+                Statement::And(Register::D, Register::J),
+            ],
+        );
+        assert_eq!(script.to_string(), "NOT A J\nAND D J\nWALK\n");
+    }
+
+    #[test]
+    fn test_survives() {
+        // (!A || !B || !C) && D
+        let mut statements = Vec::new();
+        any_hole(&mut statements, &[Register::A, Register::B, Register::C]);
+        statements.push(Statement::And(Register::D, Register::J));
+        let script = Script::new(Mode::Walk, statements);
+
+        assert!(script.survives(&hull("#####.#..########")));
+        assert!(script.survives(&hull("#####...#########")));
+        assert!(!script.survives(&hull("#####.#.##..#.####")));
+    }
+
+    #[test]
+    fn test_parse_hull() {
+        let Outcome::Fell(text) = fell("#####.#..########") else {
+            unreachable!();
+        };
+        assert_eq!(parse_hull(&text), Some(hull("#####.#..########")));
+        assert_eq!(parse_hull("Walking...\n"), None);
+    }
+
+    #[test]
+    fn test_search_walk() {
+        let hulls = [
+            "#####.#..########",
+            "#####...#########",
+            "#####..#.########",
+        ];
+        let mut runs = 0;
+        let (script, damage) = search_with(Mode::Walk, droid(&hulls, &mut runs))
+            .unwrap()
+            .unwrap();
+        assert_eq!(damage, 19_358_262);
+        assert!(hulls.iter().all(|text| script.survives(&hull(text))));
+        assert!(runs <= hulls.len() + 1);
+    }
+
+    #[test]
+    fn test_search_run() {
+        let hulls = [
+            "#####.#..########",
+            "#####...#########",
+            "#####.#.##..#.####",
+            "#####.###..#.####",
+            "#####..#.#.#######",
+        ];
+        let mut runs = 0;
+        let (script, _) = search_with(Mode::Run, droid(&hulls, &mut runs))
+            .unwrap()
+            .unwrap();
+        assert!(hulls.iter().all(|text| script.survives(&hull(text))));
+        assert!(runs <= hulls.len() + 1);
+    }
+
+    #[test]
+    fn test_search_impossible() {
+        // no script can cross a gap of four.
+        let hulls = ["#####....########"];
+        let mut runs = 0;
+        assert_eq!(search_with(Mode::Walk, droid(&hulls, &mut runs)), Ok(None));
+        assert_eq!(runs, 1);
+    }
+}
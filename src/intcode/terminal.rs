@@ -0,0 +1,357 @@
+/// Adapters connecting ASCII programs to a terminal and to files, which need `std`.
+use super::adventure_map::AdventureMap;
+use super::{Cell, Computer, RunState, Snapshot};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where `play` saves transcripts of sessions when `--transcript` is passed.
+const TRANSCRIPT_DIR: &str = "data/transcripts";
+
+const META_COMMANDS: &str = "meta-commands: :save <slot>, :load <slot>, :map\n";
+
+/// States saved by the `:save` meta-command, each with the text shown before it was saved.
+type Saves<T> = HashMap<String, (Snapshot<T>, String)>;
+
+/// Returns true if the `--interactive` flag was passed to the current binary.
+#[must_use]
+pub fn interactive_requested() -> bool {
+    std::env::args().any(|x| x == "--interactive")
+}
+
+/// The value following `name` in the arguments passed to the current binary, if any.
+fn option_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|x| x != name).nth(1)
+}
+
+/// Create a new file in `TRANSCRIPT_DIR`, named after the current binary and the time.
+fn create_transcript() -> io::Result<File> {
+    let name = std::env::current_exe()?
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    fs::create_dir_all(TRANSCRIPT_DIR)?;
+    File::create(Path::new(TRANSCRIPT_DIR).join(format!("{name}-{time}.txt")))
+}
+
+impl<T: Cell> Computer<T> {
+    /// Connect an ASCII program to a line-based text interface: its output is written to `out`
+    /// and each line read from `input` is sent to it whenever it asks for input. Stops when the
+    /// program halts or `input` ends. Non-ASCII outputs are written on a line of their own.
+    ///
+    /// Lines starting with `:` are meta-commands, which are not sent to the program.
+    /// `:save <slot>` saves the state of the program under the given name, and `:load <slot>`
+    /// returns to it, showing again the text which was printed before it was saved. For the
+    /// day 25 adventure, `:map` draws the rooms visited so far (see `AdventureMap`).
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if reading or writing fails, or if the program executes an
+    /// invalid instruction.
+    pub fn interact(&mut self, input: impl BufRead, out: impl Write) -> io::Result<()> {
+        self.interact_recording(input, out, io::sink())
+    }
+
+    /// Like `interact`, but also writes every line sent to the program to `record`, so the
+    /// session can be replayed later.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if reading or writing fails, or if the program executes an
+    /// invalid instruction.
+    pub fn interact_recording(
+        &mut self,
+        input: impl BufRead,
+        out: impl Write,
+        record: impl Write,
+    ) -> io::Result<()> {
+        self.interact_with_transcript(input, out, record, io::sink())
+    }
+
+    /// Like `interact_recording`, but also writes a transcript of the whole session to
+    /// `transcript`: everything the program printed, interleaved with the lines sent to it, as
+    /// it would have appeared on a terminal.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if reading or writing fails, or if the program executes an
+    /// invalid instruction.
+    pub fn interact_with_transcript(
+        &mut self,
+        input: impl BufRead,
+        mut out: impl Write,
+        mut record: impl Write,
+        mut transcript: impl Write,
+    ) -> io::Result<()> {
+        let mut lines = input.lines();
+        let mut saves = Saves::new();
+        let mut map = AdventureMap::new();
+        let mut shown = String::new();
+        loop {
+            let mut text = self.read_until_prompt().map_err(io::Error::other)?;
+            if let Some(value) = self.take_non_ascii() {
+                text.push_str(&format!("{value}\n"));
+            }
+            write!(out, "{text}")?;
+            out.flush()?;
+            write!(transcript, "{text}")?;
+            if !text.is_empty() {
+                map.observe_output(&text);
+                shown = text;
+            }
+
+            match self.run_until_event().map_err(io::Error::other)? {
+                RunState::NeedsInput => match lines.next() {
+                    Some(line) => {
+                        let line = line?;
+                        writeln!(record, "{line}")?;
+                        record.flush()?;
+                        writeln!(transcript, "{line}")?;
+                        match line.strip_prefix(':') {
+                            Some(command) => {
+                                let reply =
+                                    self.meta_command(command, &mut saves, &mut map, &shown);
+                                write!(out, "{reply}")?;
+                                out.flush()?;
+                                write!(transcript, "{reply}")?;
+                            }
+                            None => {
+                                map.observe_command(&line);
+                                self.send_line(&line);
+                            }
+                        }
+                    }
+                    None => break,
+                },
+                RunState::Halted | RunState::Output(_) => break,
+            }
+        }
+        transcript.flush()
+    }
+
+    /// Carry out a meta-command (without its leading `:`), returning the text to show in reply.
+    /// `shown` is the text most recently printed by the program.
+    fn meta_command(
+        &mut self,
+        command: &str,
+        saves: &mut Saves<T>,
+        map: &mut AdventureMap,
+        shown: &str,
+    ) -> String {
+        match command.split_whitespace().collect::<Vec<_>>()[..] {
+            ["save", slot] => {
+                saves.insert(slot.to_string(), (self.snapshot(), shown.to_string()));
+                format!("saved to slot {slot}\n")
+            }
+            ["load", slot] => match saves.get(slot) {
+                Some((snapshot, text)) => {
+                    self.restore(snapshot);
+                    map.observe_output(text);
+                    format!("loaded slot {slot}\n{text}")
+                }
+                None => format!("nothing saved in slot {slot}\n"),
+            },
+            ["map"] if map.rooms().is_empty() => "no rooms seen yet\n".to_string(),
+            ["map"] => map.render(),
+            _ => META_COMMANDS.to_string(),
+        }
+    }
+
+    /// Play an ASCII program by hand on stdin/stdout, returning the computer once the program
+    /// halts or stdin is closed. With `--replay <file>`, the lines of that file are sent before
+    /// any typed ones; with `--record <file>`, every line sent is saved to that file. With
+    /// `--transcript`, the whole session is also saved to a new file in `data/transcripts/`.
+    /// The meta-commands described for `interact` are available, and are recorded along with
+    /// other lines so that replaying a session repeats them.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the terminal or any of the files cannot be read from or written to, or
+    /// the program fails.
+    #[must_use]
+    pub fn play(mut self) -> Self {
+        let replay = option_value("--replay")
+            .map(|path| fs::read_to_string(path).expect("could not read replay file"))
+            .unwrap_or_default();
+        let input = io::Cursor::new(replay).chain(io::stdin().lock());
+
+        let record: Box<dyn Write> = match option_value("--record") {
+            Some(path) => Box::new(File::create(path).expect("could not create record file")),
+            None => Box::new(io::sink()),
+        };
+        let transcript: Box<dyn Write> = if std::env::args().any(|x| x == "--transcript") {
+            Box::new(create_transcript().expect("could not create transcript file"))
+        } else {
+            Box::new(io::sink())
+        };
+
+        self.interact_with_transcript(input, io::stdout(), record, transcript)
+            .expect("interactive session failed");
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::asm::assemble;
+
+    fn prompt_program() -> Computer {
+        // prints a prompt, then echoes each line back until it reads an empty one.
+        let program = assemble(
+            "
+            prompt: out  #62
+                    out  #10
+                    in   char
+                    eq   char #10 flag
+                    jnz  flag #end
+            echo:   out  char
+                    in   char
+                    eq   char #10 flag
+                    jz   flag #echo
+                    out  #10
+                    jnz  #1 #prompt
+            end:    out  #42000
+                    hlt
+            char:   data 0
+            flag:   data 0
+            ",
+        )
+        .unwrap();
+        program.parse().unwrap()
+    }
+
+    #[test]
+    fn test_interact() {
+        let mut computer = prompt_program();
+        let mut out = Vec::new();
+        computer
+            .interact(io::Cursor::new("north\ntake\n\nignored\n"), &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            ">\nnorth\n>\ntake\n>\n42000\n"
+        );
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut transcript = Vec::new();
+        prompt_program()
+            .interact_recording(
+                io::Cursor::new("north\n\nignored\n"),
+                io::sink(),
+                &mut transcript,
+            )
+            .unwrap();
+        assert_eq!(String::from_utf8(transcript.clone()).unwrap(), "north\n\n");
+
+        let mut out = Vec::new();
+        prompt_program()
+            .interact(io::Cursor::new(transcript), &mut out)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), ">\nnorth\n>\n42000\n");
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut computer = prompt_program();
+        let mut out = Vec::new();
+        computer
+            .interact(
+                io::Cursor::new("north\n:save a\ntake\n:load b\n:load a\n:help\n\n"),
+                &mut out,
+            )
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            [
+                ">",
+                "north",
+                ">",
+                "saved to slot a",
+                "take",
+                ">",
+                "nothing saved in slot b",
+                "loaded slot a",
+                "north",
+                ">",
+                META_COMMANDS.trim_end(),
+                "42000",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_map() {
+        // describes a room named after each line it reads.
+        let program = assemble(
+            "
+            line:   in   char
+                    out  #61
+                    out  #61
+                    out  #32
+            echo:   out  char
+                    in   char
+                    eq   char #10 flag
+                    jz   flag #echo
+                    out  #32
+                    out  #61
+                    out  #61
+                    out  #10
+                    jnz  #1 #line
+            char:   data 0
+            flag:   data 0
+            ",
+        )
+        .unwrap();
+        let mut computer: Computer = program.parse().unwrap();
+        let mut out = Vec::new();
+        computer
+            .interact(io::Cursor::new(":map\nstart\nnorth\n:map\n"), &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            [
+                "no rooms seen yet",
+                "== start ==",
+                "== north ==",
+                "<01>",
+                " |",
+                "[00]",
+                "",
+                " 00 start",
+                ">01 north",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_transcript() {
+        let mut record = Vec::new();
+        let mut transcript = Vec::new();
+        prompt_program()
+            .interact_with_transcript(
+                io::Cursor::new("north\n\n"),
+                io::sink(),
+                &mut record,
+                &mut transcript,
+            )
+            .unwrap();
+        assert_eq!(String::from_utf8(record).unwrap(), "north\n\n");
+        assert_eq!(
+            String::from_utf8(transcript).unwrap(),
+            ">\nnorth\nnorth\n>\n\n42000\n"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+/// `intcode_test!` runs a program to completion and asserts on what it did, so the small example
+/// programs from the puzzle descriptions can be turned into tests in a line each:
+///
+/// ```text
+/// intcode_test!("3,0,4,0,99", inputs = [42], outputs = [42]);
+/// intcode_test!("1002,4,3,4,33", memory = [1002, 4, 3, 4, 99]);
+/// ```
+///
+/// `inputs` are queued before the program starts. `outputs` must match everything the program
+/// outputs, while `memory` only has to match the start of memory. Any of them may be left out,
+/// but those given must come in this order. Panics if the program fails or asks for more input
+/// than was given.
+#[macro_export]
+macro_rules! intcode_test {
+    (
+        $program:expr
+        $(, inputs = [$($input:expr),* $(,)?])?
+        $(, outputs = [$($output:expr),* $(,)?])?
+        $(, memory = [$($cell:expr),* $(,)?])?
+        $(,)?
+    ) => {{
+        let program: &str = $program;
+        let mut computer: $crate::intcode::Computer =
+            program.parse().expect("Could not parse program");
+        $($(computer.push_input($input);)*)?
+
+        let mut outputs: Vec<i64> = Vec::new();
+        loop {
+            match computer.run_until_event() {
+                Ok($crate::intcode::RunState::Output(value)) => outputs.push(value),
+                Ok($crate::intcode::RunState::Halted) => break,
+                Ok($crate::intcode::RunState::NeedsInput) => {
+                    panic!("{program} needs more input than was given")
+                }
+                Err(e) => panic!("{program} failed: {e}"),
+            }
+        }
+
+        $(
+            let expected: Vec<i64> = vec![$($output),*];
+            assert_eq!(outputs, expected, "outputs of {program}");
+        )?
+        $(
+            let expected: Vec<i64> = vec![$($cell),*];
+            let memory: Vec<i64> = (0..expected.len())
+                .map(|address| computer.memory().read_register(address))
+                .collect();
+            assert_eq!(memory, expected, "memory of {program}");
+        )?
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_day_2_examples() {
+        intcode_test!(
+            "1,9,10,3,2,3,11,0,99,30,40,50",
+            memory = [3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]
+        );
+        intcode_test!("1,0,0,0,99", memory = [2, 0, 0, 0, 99]);
+        intcode_test!("2,4,4,5,99,0", memory = [2, 4, 4, 5, 99, 9801]);
+        intcode_test!(
+            "1,1,1,4,99,5,6,0,99",
+            outputs = [],
+            memory = [30, 1, 1, 4, 2]
+        );
+    }
+
+    #[test]
+    fn test_day_5_examples() {
+        intcode_test!("3,0,4,0,99", inputs = [42], outputs = [42]);
+        intcode_test!("1002,4,3,4,33", memory = [1002, 4, 3, 4, 99]);
+        intcode_test!("1101,100,-1,4,0", memory = [1101, 100, -1, 4, 99]);
+        for (input, output) in [(7, 0), (8, 1), (9, 0)] {
+            intcode_test!(
+                "3,9,8,9,10,9,4,9,99,-1,8",
+                inputs = [input],
+                outputs = [output]
+            );
+            intcode_test!(
+                "3,3,1108,-1,8,3,4,3,99",
+                inputs = [input],
+                outputs = [output]
+            );
+        }
+        for (input, output) in [(0, 0), (5, 1)] {
+            intcode_test!(
+                "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9",
+                inputs = [input],
+                outputs = [output],
+            );
+            intcode_test!(
+                "3,3,1105,-1,9,1101,0,0,12,4,12,99,1",
+                inputs = [input],
+                outputs = [output],
+            );
+        }
+    }
+
+    #[test]
+    fn test_day_9_examples() {
+        intcode_test!(
+            "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99",
+            outputs = [109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99]
+        );
+        intcode_test!(
+            "1102,34915192,34915192,7,4,7,99,0",
+            outputs = [1_219_070_632_396_864]
+        );
+        intcode_test!("104,1125899906842624,99", outputs = [1_125_899_906_842_624]);
+    }
+
+    #[test]
+    #[should_panic(expected = "outputs of 3,0,4,0,99")]
+    fn test_wrong_output() {
+        intcode_test!("3,0,4,0,99", inputs = [1], outputs = [2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs more input")]
+    fn test_missing_input() {
+        intcode_test!("3,0,4,0,99", outputs = [2]);
+    }
+}
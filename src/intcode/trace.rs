@@ -0,0 +1,227 @@
+use super::{Cell, Computer, Instruction, Memory};
+use std::fmt::Debug;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// How much detail is written for each traced instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// The address and mnemonic, e.g. `0004 mul`.
+    Mnemonics,
+    /// Also the address and current value of each parameter, e.g. `0004 mul [1]=3 [2]=4 [0]=7`.
+    #[default]
+    Operands,
+    /// Also the value written, the jump target taken or the value output.
+    Effects,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TraceConfig {
+    pub verbosity: Verbosity,
+    /// Stop tracing after this many instructions.
+    pub max_lines: Option<usize>,
+    /// Trace only input and output instructions, each with the number of instructions executed
+    /// before it and the value transferred, e.g. `step 12: 0008 out 7`. This shows the
+    /// conversation between a program and its host without the arithmetic in between, and
+    /// takes the place of `verbosity`.
+    pub io_only: bool,
+}
+
+type Writer = Arc<Mutex<dyn Write + Send>>;
+
+/// Writes a line per executed instruction. Clones of a computer share the same writer.
+#[derive(Clone)]
+pub struct Tracer {
+    writer: Writer,
+    config: TraceConfig,
+    lines: usize,
+}
+
+impl Debug for Tracer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tracer")
+            .field("config", &self.config)
+            .field("lines", &self.lines)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Tracer {
+    fn write_line(&self, line: &str) {
+        let mut writer = self.writer.lock().expect("trace writer panicked");
+        let _ = writeln!(writer, "{line}");
+    }
+
+    /// Describe an instruction about to execute, if it should be traced.
+    pub(super) fn before<T: Cell>(
+        &mut self,
+        instruction: &Instruction,
+        memory: &Memory<T>,
+        instruction_pointer: usize,
+        steps: usize,
+    ) -> Option<String> {
+        if self.config.max_lines.is_some_and(|max| self.lines >= max) {
+            return None;
+        }
+        let transfer = matches!(instruction, Instruction::Input(_) | Instruction::Output(_));
+        if self.config.io_only && !transfer {
+            return None;
+        }
+
+        self.lines += 1;
+        let line = match self.config.verbosity {
+            _ if self.config.io_only => format!(
+                "step {steps}: {instruction_pointer:04} {}",
+                instruction.mnemonic()
+            ),
+            Verbosity::Mnemonics => format!("{instruction_pointer:04} {}", instruction.mnemonic()),
+            Verbosity::Operands | Verbosity::Effects => {
+                format!("{instruction_pointer:04} {}", instruction.describe(memory))
+            }
+        };
+        Some(line)
+    }
+
+    /// Finish and write the line for an instruction which has just executed.
+    pub(super) fn after<T: Cell>(
+        &self,
+        mut line: String,
+        instruction: &Instruction,
+        memory: &Memory<T>,
+        jump: Option<usize>,
+    ) {
+        if self.config.io_only {
+            if let Instruction::Input(address) | Instruction::Output(address) = instruction {
+                line.push_str(&format!(" {}", memory.read_register(*address)));
+            }
+        } else if self.config.verbosity == Verbosity::Effects {
+            if let Some(address) = instruction.get_written_register() {
+                line.push_str(&format!(
+                    " -> [{address}]={}",
+                    memory.read_register(address)
+                ));
+            } else if let Some(target) = jump {
+                line.push_str(&format!(" -> jump {target:04}"));
+            } else if let Instruction::Output(address) = instruction {
+                line.push_str(&format!(" -> output {}", memory.read_register(*address)));
+            }
+        }
+        self.write_line(&line);
+        if self.config.max_lines == Some(self.lines) {
+            self.write_line("... trace limit reached");
+        }
+    }
+}
+
+impl<T: Cell> Computer<T> {
+    /// Write a line to `writer` for every instruction executed from now on.
+    pub fn trace_to(&mut self, writer: impl Write + Send + 'static, config: TraceConfig) {
+        *self.tracer_mut() = Some(Tracer {
+            writer: Arc::new(Mutex::new(writer)),
+            config,
+            lines: 0,
+        });
+    }
+
+    pub fn stop_trace(&mut self) {
+        *self.tracer_mut() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    const PROGRAM: &str = "1,0,0,0,1105,1,8,99,104,7,99";
+
+    fn trace(config: TraceConfig) -> String {
+        let mut computer: Computer = PROGRAM.parse().unwrap();
+        let buffer = SharedBuffer::default();
+        computer.trace_to(buffer.clone(), config);
+        while computer.step() != Ok(Some(crate::intcode::RunState::Halted)) {}
+        buffer.text()
+    }
+
+    #[test]
+    fn test_trace_mnemonics() {
+        let config = TraceConfig {
+            verbosity: Verbosity::Mnemonics,
+            ..TraceConfig::default()
+        };
+        assert_eq!(trace(config), "0000 add\n0004 jnz\n0008 out\n");
+    }
+
+    #[test]
+    fn test_trace_operands() {
+        assert_eq!(
+            trace(TraceConfig::default()),
+            "0000 add [0]=1 [0]=1 [0]=1\n0004 jnz [5]=1 [6]=8\n0008 out [9]=7\n"
+        );
+    }
+
+    #[test]
+    fn test_trace_effects() {
+        let config = TraceConfig {
+            verbosity: Verbosity::Effects,
+            ..TraceConfig::default()
+        };
+        assert_eq!(
+            trace(config),
+            "0000 add [0]=1 [0]=1 [0]=1 -> [0]=2\n\
+            0004 jnz [5]=1 [6]=8 -> jump 0008\n\
+            0008 out [9]=7 -> output 7\n"
+        );
+    }
+
+    #[test]
+    fn test_trace_limit() {
+        let config = TraceConfig {
+            verbosity: Verbosity::Mnemonics,
+            max_lines: Some(2),
+            ..TraceConfig::default()
+        };
+        assert_eq!(
+            trace(config),
+            "0000 add\n0004 jnz\n... trace limit reached\n"
+        );
+    }
+
+    #[test]
+    fn test_trace_io_only() {
+        // reads two values and outputs their sum.
+        let mut computer: Computer = "3,11,3,12,1,11,12,13,4,13,99".parse().unwrap();
+        let buffer = SharedBuffer::default();
+        let config = TraceConfig {
+            io_only: true,
+            max_lines: Some(3),
+            ..TraceConfig::default()
+        };
+        computer.trace_to(buffer.clone(), config);
+        computer.push_input(3);
+        computer.push_input(4);
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [7]);
+        assert_eq!(
+            buffer.text(),
+            "step 0: 0000 in 3\nstep 1: 0002 in 4\nstep 3: 0008 out 7\n... trace limit reached\n"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use super::{Cell, Computer, Instruction, Memory};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// What happened to a watched address.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AccessKind<T> {
+    Read(T),
+    Write { old: T, new: T },
+}
+
+/// An access to a watched address, passed to its callback.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Access<T> {
+    pub address: usize,
+    /// The address of the instruction which made the access.
+    pub instruction_pointer: usize,
+    pub kind: AccessKind<T>,
+}
+
+type Callback<T> = Arc<Mutex<dyn FnMut(&Access<T>) + Send>>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Watch {
+    Read,
+    Write,
+}
+
+struct Watchpoint<T> {
+    address: usize,
+    watch: Watch,
+    callback: Callback<T>,
+}
+
+impl<T> Clone for Watchpoint<T> {
+    fn clone(&self) -> Self {
+        Self {
+            address: self.address,
+            watch: self.watch,
+            callback: Arc::clone(&self.callback),
+        }
+    }
+}
+
+/// The watchpoints registered on a `Computer`. Clones of a computer share the same callbacks.
+pub struct Watchpoints<T>(Vec<Watchpoint<T>>);
+
+impl<T> Default for Watchpoints<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> Clone for Watchpoints<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Debug for Watchpoints<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Watchpoints({} registered)", self.0.len())
+    }
+}
+
+impl<T: Cell> Watchpoints<T> {
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn fire(&self, watch: Watch, access: &Access<T>) {
+        self.0
+            .iter()
+            .filter(|point| point.watch == watch && point.address == access.address)
+            .for_each(|point| {
+                let mut callback = point.callback.lock().expect("watchpoint callback panicked");
+                callback(access);
+            });
+    }
+
+    /// Fire read watchpoints for an instruction about to execute, returning the value currently
+    /// held by the register it will write to.
+    pub(super) fn before(
+        &self,
+        instruction: &Instruction,
+        memory: &Memory<T>,
+        instruction_pointer: usize,
+    ) -> Option<T> {
+        for address in instruction.get_read_registers() {
+            let access = Access {
+                address,
+                instruction_pointer,
+                kind: AccessKind::Read(memory.read_register(address)),
+            };
+            self.fire(Watch::Read, &access);
+        }
+        instruction
+            .get_written_register()
+            .map(|address| memory.read_register(address))
+    }
+
+    /// Fire write watchpoints for an instruction which has just executed.
+    pub(super) fn after(
+        &self,
+        instruction: &Instruction,
+        memory: &Memory<T>,
+        instruction_pointer: usize,
+        old: Option<T>,
+    ) {
+        if let (Some(address), Some(old)) = (instruction.get_written_register(), old) {
+            let access = Access {
+                address,
+                instruction_pointer,
+                kind: AccessKind::Write {
+                    old,
+                    new: memory.read_register(address),
+                },
+            };
+            self.fire(Watch::Write, &access);
+        }
+    }
+}
+
+impl<T: Cell> Computer<T> {
+    /// Call `callback` whenever an instruction reads the value at `address`.
+    pub fn on_read(&mut self, address: usize, callback: impl FnMut(&Access<T>) + Send + 'static) {
+        self.watchpoints_mut().0.push(Watchpoint {
+            address,
+            watch: Watch::Read,
+            callback: Arc::new(Mutex::new(callback)),
+        });
+    }
+
+    /// Call `callback` whenever an instruction writes to `address`, even if the value written
+    /// is unchanged.
+    pub fn on_write(&mut self, address: usize, callback: impl FnMut(&Access<T>) + Send + 'static) {
+        self.watchpoints_mut().0.push(Watchpoint {
+            address,
+            watch: Watch::Write,
+            callback: Arc::new(Mutex::new(callback)),
+        });
+    }
+
+    pub fn clear_watchpoints(&mut self) {
+        self.watchpoints_mut().0.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_on_write() {
+        let mut computer: Computer = "1,0,0,0,2,0,0,0,99".parse().unwrap();
+        let writes = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&writes);
+        computer.on_write(0, move |access| seen.lock().unwrap().push(access.clone()));
+        computer.run_until_event().unwrap();
+
+        let writes = writes.lock().unwrap();
+        assert_eq!(
+            *writes,
+            [
+                Access {
+                    address: 0,
+                    instruction_pointer: 0,
+                    kind: AccessKind::Write { old: 1, new: 2 },
+                },
+                Access {
+                    address: 0,
+                    instruction_pointer: 4,
+                    kind: AccessKind::Write { old: 2, new: 4 },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_on_read() {
+        let mut computer: Computer = "3,9,4,9,1,9,9,10,99,0,0".parse().unwrap();
+        computer.push_input(21);
+        let reads = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&reads);
+        computer.on_read(9, move |access| {
+            if let AccessKind::Read(value) = access.kind {
+                seen.lock()
+                    .unwrap()
+                    .push((access.instruction_pointer, value));
+            }
+        });
+        assert_eq!(computer.outputs().collect::<Vec<_>>(), [21]);
+        assert_eq!(*reads.lock().unwrap(), [(2, 21), (4, 21), (4, 21)]);
+    }
+
+    #[test]
+    fn test_clear_watchpoints() {
+        let mut computer: Computer = "1,0,0,0,99".parse().unwrap();
+        computer.on_write(0, |_| panic!("watchpoint should have been cleared"));
+        computer.clear_watchpoints();
+        computer.run_until_event().unwrap();
+    }
+}
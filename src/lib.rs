@@ -1,2 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod intcode;
+#[cfg(feature = "std")]
 pub mod template;
@@ -1,52 +1,299 @@
 use advent_of_code::template::commands;
+use advent_of_code::template::commands::solve::Input;
 
 mod args {
+    use super::Input;
+    use advent_of_code::template::{dates, profile};
+    use std::collections::BTreeSet;
     use std::process;
+    use std::time::{SystemTime, UNIX_EPOCH};
 
     pub enum Arguments {
+        Benchmarks,
+        Calendar {
+            /// Show which days have a solution and whether its tests pass.
+            local: bool,
+        },
+        Completions {
+            shell: String,
+        },
         Download {
             day: u8,
+            force: bool,
+        },
+        Intcode {
+            path: String,
+            input: Option<String>,
+        },
+        IntcodeBench,
+        IntcodeDot {
+            path: String,
+        },
+        Leaderboard {
+            id: u32,
         },
         Read {
             day: u8,
+            refresh: bool,
         },
         Scaffold {
             day: u8,
+            download: bool,
         },
         Solve {
             day: u8,
             release: bool,
             time: bool,
             submit: Option<u8>,
+            part: Option<u8>,
+            input: Input,
+            /// Re-run whenever the sources change.
+            watch: bool,
+            /// Run the day's tests first, and only solve if they pass.
+            test_first: bool,
+            /// Run the solution under a sampling profiler and write a flamegraph.
+            flamegraph: bool,
+            /// Flags and options understood by the runner or by Intcode solutions, forwarded to the
+            /// binary as-is.
+            forwarded_args: Vec<String>,
+        },
+        Submit {
+            day: u8,
+            part: u8,
+            answer: String,
+            /// Wait out a cooldown and submit again, rather than giving up.
+            wait: bool,
+        },
+        Test {
+            day: Option<u8>,
+        },
+        Time {
+            day: Option<u8>,
+        },
+        TokenCheck,
+        TokenSet {
+            token: String,
+        },
+        TokenShow,
+        Verify {
+            day: Option<u8>,
+        },
+        Wait {
+            day: Option<u8>,
         },
         All {
+            days: Vec<usize>,
             release: bool,
             time: bool,
+            parallel: bool,
+            json: bool,
         },
     }
 
     pub fn parse() -> Result<Arguments, Box<dyn std::error::Error>> {
         let mut args = pico_args::Arguments::from_env();
 
-        let app_args = match args.subcommand()?.as_deref() {
+        let subcommand = args.subcommand()?;
+        // `--no-color` is read by `color_enabled`, which applies it to every command.
+        args.contains("--no-color");
+        // likewise `--profile` is read by `profile::current`.
+        let profile: Option<String> = args.opt_value_from_str("--profile")?;
+        if profile
+            .as_deref()
+            .is_some_and(|name| !profile::is_valid_name(name))
+        {
+            eprintln!("A profile name can only have letters, digits, '-' and '_'.");
+            process::exit(1);
+        }
+
+        let app_args = match subcommand.as_deref() {
             Some("all") => Arguments::All {
+                days: args
+                    .opt_value_from_fn("--days", parse_days)?
+                    .unwrap_or_else(|| (1..=25).collect()),
                 release: args.contains("--release"),
                 time: args.contains("--time"),
+                parallel: args.contains("--parallel"),
+                json: args.contains("--json"),
+            },
+            Some("benchmarks") => Arguments::Benchmarks,
+            Some("calendar") => Arguments::Calendar {
+                local: args.contains("--local"),
+            },
+            Some("completions") => Arguments::Completions {
+                shell: args.free_from_str()?,
             },
             Some("download") => Arguments::Download {
-                day: args.free_from_str()?,
+                force: args.contains("--force"),
+                day: day_or_today(args.opt_free_from_str()?)?,
+            },
+            Some("intcode") => {
+                let input = args.opt_value_from_str("--input")?;
+                let dot = args.contains("--dot");
+                match args.free_from_str::<String>()?.as_str() {
+                    "bench" => Arguments::IntcodeBench,
+                    path if dot => Arguments::IntcodeDot {
+                        path: path.to_string(),
+                    },
+                    path => Arguments::Intcode {
+                        path: path.to_string(),
+                        input,
+                    },
+                }
+            }
+            Some("leaderboard") => Arguments::Leaderboard {
+                id: args.free_from_str()?,
             },
             Some("read") => Arguments::Read {
-                day: args.free_from_str()?,
+                refresh: args.contains("--refresh"),
+                day: day_or_today(args.opt_free_from_str()?)?,
             },
             Some("scaffold") => Arguments::Scaffold {
-                day: args.free_from_str()?,
+                download: args.contains("--download"),
+                day: day_or_today(args.opt_free_from_str()?)?,
             },
-            Some("solve") => Arguments::Solve {
-                day: args.free_from_str()?,
-                release: args.contains("--release"),
-                submit: args.opt_value_from_str("--submit")?,
-                time: args.contains("--time"),
+            Some("solve") => {
+                let mut forwarded_args = Vec::new();
+                for flag in [
+                    "--debug-intcode",
+                    "--heap-profile",
+                    "--interactive",
+                    "--json",
+                    "--transcript",
+                    "--wait",
+                ] {
+                    if args.contains(flag) {
+                        forwarded_args.push(flag.to_string());
+                    }
+                }
+                for option in ["--record", "--replay"] {
+                    if let Some(path) = args.opt_value_from_str::<_, String>(option)? {
+                        forwarded_args.extend([option.to_string(), path]);
+                    }
+                }
+
+                let part = args.opt_value_from_str("--part")?;
+                if part.is_some_and(|part| !(1..=2).contains(&part)) {
+                    eprintln!("--part must be 1 or 2.");
+                    process::exit(1);
+                }
+
+                let submit = args.opt_value_from_str("--submit")?;
+                let file = args.opt_value_from_str("--input")?;
+                let example = args.contains("--example");
+                let mut stdin = args.contains("--stdin");
+                let release = args.contains("--release");
+                let time = args.contains("--time");
+                let watch = args.contains("--watch");
+                let test_first = args.contains("--test");
+                let flamegraph = args.contains("--flamegraph");
+
+                // the day, then the example number if any, with `-` anywhere to read stdin.
+                let mut numbers: Vec<u8> = vec![];
+                while let Some(free) = args.opt_free_from_str::<String>()? {
+                    if free == "-" {
+                        stdin = true;
+                    } else {
+                        numbers.push(
+                            free.parse()
+                                .map_err(|_| format!("unexpected argument: {free}"))?,
+                        );
+                    }
+                }
+                if numbers.len() > if example { 2 } else { 1 } {
+                    return Err(format!("too many arguments: {numbers:?}").into());
+                }
+                let day = day_or_today(numbers.first().copied())?;
+
+                let input = match (file, example, stdin) {
+                    (None, false, false) => Input::Puzzle,
+                    (Some(path), false, false) => Input::File(path),
+                    (None, true, false) => Input::Example(numbers.get(1).copied()),
+                    (None, false, true) => Input::Stdin,
+                    _ => {
+                        eprintln!("Only one of --input, --example and --stdin can be used.");
+                        process::exit(1);
+                    }
+                };
+                if submit.is_some() && (profile.is_some() || !matches!(input, Input::Puzzle)) {
+                    eprintln!("Only answers for the main account's puzzle input can be submitted.");
+                    process::exit(1);
+                }
+                if watch && (submit.is_some() || matches!(input, Input::Stdin)) {
+                    eprintln!("--watch cannot be used with --submit or stdin input.");
+                    process::exit(1);
+                }
+                if flamegraph && (watch || time || submit.is_some()) {
+                    eprintln!("--flamegraph cannot be used with --watch, --time or --submit.");
+                    process::exit(1);
+                }
+                let is_forwarded = |flag: &str| forwarded_args.iter().any(|x| x == flag);
+                if is_forwarded("--heap-profile")
+                    && (flamegraph || time || submit.is_some() || is_forwarded("--json"))
+                {
+                    eprintln!(
+                        "--heap-profile cannot be used with --flamegraph, --time, --submit or --json."
+                    );
+                    process::exit(1);
+                }
+
+                Arguments::Solve {
+                    release,
+                    submit,
+                    part,
+                    input,
+                    time,
+                    watch,
+                    test_first,
+                    flamegraph,
+                    forwarded_args,
+                    day,
+                }
+            }
+            Some("submit") => {
+                let wait = args.contains("--wait");
+                let day = args.free_from_str()?;
+                let part = args.free_from_str()?;
+                if !(1..=2).contains(&part) {
+                    eprintln!("The part must be 1 or 2.");
+                    process::exit(1);
+                }
+                Arguments::Submit {
+                    day,
+                    part,
+                    answer: args.free_from_str()?,
+                    wait,
+                }
+            }
+            Some("test") => Arguments::Test {
+                day: match args.opt_free_from_str::<String>()?.as_deref() {
+                    Some("all") => None,
+                    Some(day) => Some(day.parse()?),
+                    None => Some(day_or_today(None)?),
+                },
+            },
+            Some("time") => Arguments::Time {
+                day: args.opt_free_from_str()?,
+            },
+            Some("token") => match args.free_from_str::<String>()?.as_str() {
+                "check" => Arguments::TokenCheck,
+                "set" => Arguments::TokenSet {
+                    token: args.free_from_str()?,
+                },
+                "show" => Arguments::TokenShow,
+                x => {
+                    eprintln!("Unknown token command: {x}. Use set, show or check.");
+                    process::exit(1);
+                }
+            },
+            Some("verify") => Arguments::Verify {
+                day: match args.opt_free_from_str::<String>()?.as_deref() {
+                    Some("all") | None => None,
+                    Some(day) => Some(day.parse()?),
+                },
+            },
+            Some("wait") => Arguments::Wait {
+                day: args.opt_free_from_str()?,
             },
             Some(x) => {
                 eprintln!("Unknown command: {x}");
@@ -65,6 +312,84 @@ mod args {
 
         Ok(app_args)
     }
+
+    /// The day given as a free argument, or failing that the day of the event it is today.
+    fn day_or_today(day: Option<u8>) -> Result<u8, Box<dyn std::error::Error>> {
+        match day {
+            Some(day) => Ok(day),
+            None => current_day()
+                .ok_or_else(|| "no day given, and it is not December 1st to 25th in EST".into()),
+        }
+    }
+
+    fn current_day() -> Option<u8> {
+        advent_day(SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs())
+    }
+
+    /// The day of the event at a Unix timestamp, if it falls between the 1st and 25th of
+    /// December in EST (UTC-5), the timezone in which puzzles unlock at midnight.
+    fn advent_day(timestamp: u64) -> Option<u8> {
+        let date = dates::est(timestamp)?;
+        (date.month == 12 && date.day <= 25).then_some(date.day)
+    }
+
+    /// Parse a selection of days, such as `5-12`, `1,3,9` or a mix like `1-3,9`, into the days
+    /// it covers, in order and without repeats.
+    fn parse_days(selection: &str) -> Result<Vec<usize>, String> {
+        let mut days = BTreeSet::new();
+        for item in selection.split(',') {
+            let (first, last) = match item.split_once('-') {
+                Some((first, last)) => (parse_day(first)?, parse_day(last)?),
+                None => (parse_day(item)?, parse_day(item)?),
+            };
+            if first > last {
+                return Err(format!("day range {item} is backwards"));
+            }
+            days.extend(first..=last);
+        }
+        Ok(days.into_iter().collect())
+    }
+
+    fn parse_day(day: &str) -> Result<usize, String> {
+        match day.trim().parse() {
+            Ok(day @ 1..=25) => Ok(day),
+            _ => Err(format!("{day:?} is not a day from 1 to 25")),
+        }
+    }
+
+    #[cfg(feature = "test_lib")]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_advent_day() {
+            // midnight EST on 2019-12-01, and a second before it.
+            assert_eq!(advent_day(1_575_176_400), Some(1));
+            assert_eq!(advent_day(1_575_176_399), None);
+            // late in the evening of 2024-12-25 EST, which is already the 26th in UTC.
+            assert_eq!(advent_day(1_735_182_000), Some(25));
+            assert_eq!(advent_day(1_735_189_200), None);
+            // 2020-02-29
+            assert_eq!(advent_day(1_582_977_600), None);
+        }
+
+        #[test]
+        fn test_parse_days() {
+            assert_eq!(parse_days("5-12"), Ok((5..=12).collect()));
+            assert_eq!(parse_days("1,3,9"), Ok(vec![1, 3, 9]));
+            assert_eq!(parse_days("9, 1-3,2"), Ok(vec![1, 2, 3, 9]));
+            assert_eq!(parse_days("25"), Ok(vec![25]));
+        }
+
+        #[test]
+        fn test_parse_days_invalid() {
+            assert!(parse_days("0-3").is_err());
+            assert!(parse_days("20-26").is_err());
+            assert!(parse_days("12-5").is_err());
+            assert!(parse_days("1,,3").is_err());
+            assert!(parse_days("five").is_err());
+        }
+    }
 }
 
 fn main() {
@@ -74,16 +399,75 @@ fn main() {
             std::process::exit(1);
         }
         Ok(args) => match args {
-            args::Arguments::All { release, time } => commands::all::handler(release, time),
-            args::Arguments::Download { day } => commands::download::handler(day),
-            args::Arguments::Read { day } => commands::read::handler(day),
-            args::Arguments::Scaffold { day } => commands::scaffold::handler(day),
+            args::Arguments::All {
+                days,
+                release,
+                time,
+                parallel,
+                json,
+            } => commands::all::handler(&days, release, time, parallel, json),
+            args::Arguments::Benchmarks => commands::benchmarks::handler(),
+            args::Arguments::Calendar { local } => commands::calendar::handler(local),
+            args::Arguments::Completions { shell } => commands::completions::handler(&shell),
+            args::Arguments::Download { day, force } => commands::download::handler(day, force),
+            args::Arguments::Intcode { path, input } => {
+                commands::intcode::handler(&path, input.as_deref());
+            }
+            args::Arguments::IntcodeBench => commands::intcode::bench_handler(),
+            args::Arguments::IntcodeDot { path } => commands::intcode::dot_handler(&path),
+            args::Arguments::Leaderboard { id } => commands::leaderboard::handler(id),
+            args::Arguments::Read { day, refresh } => commands::read::handler(day, refresh),
+            args::Arguments::Scaffold { day, download } => {
+                commands::scaffold::handler(day, download);
+            }
             args::Arguments::Solve {
                 day,
                 release,
                 time,
                 submit,
-            } => commands::solve::handler(day, release, time, submit),
+                part,
+                input,
+                watch,
+                test_first,
+                flamegraph,
+                forwarded_args,
+            } => {
+                let solve = || {
+                    if flamegraph {
+                        commands::flamegraph::handler(day, part, &input, &forwarded_args);
+                    } else {
+                        commands::solve::handler(
+                            day,
+                            release,
+                            time,
+                            submit,
+                            part,
+                            &input,
+                            &forwarded_args,
+                        );
+                    }
+                };
+                if watch {
+                    commands::watch::handler(day, test_first, solve);
+                } else if !test_first || commands::test::tests_pass(day) {
+                    solve();
+                }
+            }
+            args::Arguments::Submit {
+                day,
+                part,
+                answer,
+                wait,
+            } => {
+                commands::submit::handler(day, part, &answer, wait);
+            }
+            args::Arguments::Test { day } => commands::test::handler(day),
+            args::Arguments::Time { day } => commands::time::handler(day),
+            args::Arguments::TokenCheck => commands::token::check_handler(),
+            args::Arguments::TokenSet { token } => commands::token::set_handler(&token),
+            args::Arguments::TokenShow => commands::token::show_handler(),
+            args::Arguments::Verify { day } => commands::verify::handler(day),
+            args::Arguments::Wait { day } => commands::wait::handler(day),
         },
     };
 }
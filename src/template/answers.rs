@@ -0,0 +1,576 @@
+/// Module that keeps every answer submitted, with the verdict it got, in `data/answers.tsv`, so
+/// that `submit` can refuse to send an answer which is already known to be wrong, or any answer
+/// for a part which is already solved. Each wrong answer costs a cooldown before the next.
+///
+/// The accepted answers are also kept on their own in `data/expected.tsv`, which `verify` checks
+/// the solutions against.
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::time::Duration;
+
+static ANSWERS_PATH: &str = "./data/answers.tsv";
+static EXPECTED_PATH: &str = "./data/expected.tsv";
+
+const EXPECTED_HEADER: &str = "\
+# The answers each day should give for its puzzle input, checked by `cargo verify`.
+# One tab-separated line per part: day, part, answer. Write any newlines in an answer as \\n.
+";
+
+/// What adventofcode.com made of a submitted answer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Incorrect,
+    /// The part had been solved already, so the answer was not checked.
+    AlreadySolved,
+}
+
+impl Verdict {
+    /// Read the verdict from the response to a submission, as printed by aoc-cli. A response
+    /// which gives none, such as being asked to wait before answering again, gives `None`.
+    #[must_use]
+    pub fn from_response(response: &str) -> Option<Self> {
+        if response.contains("That's the right answer") {
+            Some(Self::Correct)
+        } else if response.contains("That's not the right answer") {
+            if response.contains("too high") {
+                Some(Self::TooHigh)
+            } else if response.contains("too low") {
+                Some(Self::TooLow)
+            } else {
+                Some(Self::Incorrect)
+            }
+        } else if response.contains("Did you already complete it") {
+            Some(Self::AlreadySolved)
+        } else {
+            None
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Correct => "correct",
+            Self::TooHigh => "too-high",
+            Self::TooLow => "too-low",
+            Self::Incorrect => "incorrect",
+            Self::AlreadySolved => "already-solved",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        [
+            Self::Correct,
+            Self::TooHigh,
+            Self::TooLow,
+            Self::Incorrect,
+            Self::AlreadySolved,
+        ]
+        .into_iter()
+        .find(|verdict| verdict.as_str() == s)
+    }
+}
+
+/// How long is left of the cooldown that follows a wrong answer, if the response to a submission
+/// says it was refused for being too soon, as in "You have 4m 32s left to wait".
+#[must_use]
+pub fn cooldown(response: &str) -> Option<Duration> {
+    if !response.contains("You gave an answer too recently") {
+        return None;
+    }
+
+    let (_, after) = response.split_once("You have ")?;
+    let (remaining, _) = after.split_once(" left to wait")?;
+    let mut seconds = 0;
+    for amount in remaining.split_whitespace() {
+        let (number, unit) = amount.split_at(amount.len() - 1);
+        let number: u64 = number.parse().ok()?;
+        seconds += match unit {
+            "h" => number * 3600,
+            "m" => number * 60,
+            "s" => number,
+            _ => return None,
+        };
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+/// One answer sent for a part, and its verdict.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Submission {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+impl Submission {
+    /// The submission as a line of `data/answers.tsv`: day, part, verdict and answer, with any
+    /// newlines and tabs in the answer written as `\n` and `\t`.
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.day,
+            self.part,
+            self.verdict.as_str(),
+            self.answer.replace('\n', "\\n").replace('\t', "\\t")
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(4, '\t');
+        Some(Self {
+            day: fields.next()?.parse().ok()?,
+            part: fields.next()?.parse().ok()?,
+            verdict: Verdict::parse(fields.next()?)?,
+            answer: fields.next()?.replace("\\t", "\t").replace("\\n", "\n"),
+        })
+    }
+}
+
+/// What the wrong answers to a part show about the right one: that it is greater than `above`
+/// and less than `below`, where these are known.
+#[derive(Debug, Default, PartialEq)]
+pub struct Bounds {
+    pub above: Option<i64>,
+    pub below: Option<i64>,
+}
+
+impl Bounds {
+    #[must_use]
+    pub fn is_known(&self) -> bool {
+        self.above.is_some() || self.below.is_some()
+    }
+
+    #[must_use]
+    pub fn contains(&self, number: i64) -> bool {
+        self.above.is_none_or(|above| number > above)
+            && self.below.is_none_or(|below| number < below)
+    }
+}
+
+impl Display for Bounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.above, self.below) {
+            (Some(above), Some(below)) => write!(f, "greater than {above} and less than {below}"),
+            (Some(above), None) => write!(f, "greater than {above}"),
+            (None, Some(below)) => write!(f, "less than {below}"),
+            (None, None) => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Why an answer should not be submitted.
+#[derive(Debug, PartialEq)]
+pub enum Refusal {
+    /// The part is solved, with the answer given if it is known.
+    Solved(Option<String>),
+    /// The same answer was submitted before and was wrong.
+    AlreadyWrong(Verdict),
+    /// Answers at least as high (or as low) have already been too high (or too low).
+    OutOfBounds(Bounds),
+}
+
+impl Display for Refusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Refusal::Solved(Some(answer)) => {
+                write!(f, "this part is already solved, with the answer {answer}")
+            }
+            Refusal::Solved(None) => write!(f, "this part is already solved"),
+            Refusal::AlreadyWrong(Verdict::TooHigh) => {
+                write!(f, "this answer was already submitted, and was too high")
+            }
+            Refusal::AlreadyWrong(Verdict::TooLow) => {
+                write!(f, "this answer was already submitted, and was too low")
+            }
+            Refusal::AlreadyWrong(_) => {
+                write!(f, "this answer was already submitted, and was wrong")
+            }
+            Refusal::OutOfBounds(bounds) => {
+                write!(f, "earlier answers show the answer is {bounds}")
+            }
+        }
+    }
+}
+
+/// Every answer submitted so far, oldest first.
+#[derive(Debug, Default)]
+pub struct AnswerLog {
+    submissions: Vec<Submission>,
+}
+
+impl AnswerLog {
+    /// Read the log from `data/answers.tsv`. A missing file gives an empty log; lines which cannot
+    /// be parsed are skipped, after a warning.
+    #[must_use]
+    pub fn load() -> Self {
+        let Ok(tsv) = fs::read_to_string(ANSWERS_PATH) else {
+            return Self::default();
+        };
+        Self::from_tsv(&tsv)
+    }
+
+    fn from_tsv(tsv: &str) -> Self {
+        let submissions = tsv
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                let submission = Submission::from_line(line);
+                if submission.is_none() {
+                    eprintln!("Could not parse line of {ANSWERS_PATH}: {line}");
+                }
+                submission
+            })
+            .collect();
+        Self { submissions }
+    }
+
+    /// The submissions made for one part, oldest first.
+    pub fn submissions(&self, day: u8, part: u8) -> impl Iterator<Item = &Submission> {
+        self.submissions
+            .iter()
+            .filter(move |submission| submission.day == day && submission.part == part)
+    }
+
+    /// The answer to a part which was accepted, if there was one.
+    #[must_use]
+    pub fn accepted(&self, day: u8, part: u8) -> Option<&str> {
+        self.submissions(day, part)
+            .find(|submission| submission.verdict == Verdict::Correct)
+            .map(|submission| submission.answer.as_str())
+    }
+
+    /// The bounds on a part's answer set by the earlier answers which were too high or too low.
+    #[must_use]
+    pub fn bounds(&self, day: u8, part: u8) -> Bounds {
+        let mut bounds = Bounds::default();
+        for submission in self.submissions(day, part) {
+            let Ok(number) = submission.answer.trim().parse::<i64>() else {
+                continue;
+            };
+            match submission.verdict {
+                Verdict::TooLow => bounds.above = bounds.above.max(Some(number)),
+                Verdict::TooHigh => {
+                    bounds.below = Some(bounds.below.map_or(number, |below| below.min(number)));
+                }
+                _ => {}
+            }
+        }
+        bounds
+    }
+
+    /// Check an answer against what is known about the part: refuse it if the part is solved,
+    /// if the same answer was wrong before, or if it is a number beyond one already too high or
+    /// too low.
+    ///
+    /// # Errors
+    ///
+    /// Will return the `Refusal` explaining why the answer should not be sent.
+    pub fn check(&self, day: u8, part: u8, answer: &str) -> Result<(), Refusal> {
+        if let Some(accepted) = self.accepted(day, part) {
+            return Err(Refusal::Solved(Some(accepted.to_string())));
+        }
+        if self
+            .submissions(day, part)
+            .any(|submission| submission.verdict == Verdict::AlreadySolved)
+        {
+            return Err(Refusal::Solved(None));
+        }
+
+        if let Some(wrong) = self
+            .submissions(day, part)
+            .find(|submission| submission.answer == answer)
+        {
+            return Err(Refusal::AlreadyWrong(wrong.verdict));
+        }
+
+        let bounds = self.bounds(day, part);
+        match answer.trim().parse::<i64>() {
+            Ok(number) if !bounds.contains(number) => Err(Refusal::OutOfBounds(bounds)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a submission to the log, and to the end of `data/answers.tsv`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if the file cannot be written.
+    pub fn record(&mut self, submission: Submission) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(ANSWERS_PATH)?;
+        writeln!(file, "{}", submission.to_line())?;
+        self.submissions.push(submission);
+        Ok(())
+    }
+}
+
+/// The answers shown on a puzzle description for the parts already solved, in order, as in
+/// "Your puzzle answer was `3421505`."
+#[must_use]
+pub fn parse_puzzle_answers(puzzle: &str) -> Vec<String> {
+    puzzle
+        .split("Your puzzle answer was")
+        .skip(1)
+        .filter_map(|after| {
+            let answer = after.lines().next()?.trim().trim_end_matches('.');
+            let answer = answer
+                .trim_start_matches("<code>")
+                .trim_end_matches("</code>")
+                .trim_matches('`')
+                .trim();
+            (!answer.is_empty()).then(|| answer.to_string())
+        })
+        .collect()
+}
+
+/// The accepted answer to each part, by day and part, as kept in `data/expected.tsv`.
+#[derive(Debug, Default)]
+pub struct ExpectedAnswers {
+    answers: BTreeMap<(u8, u8), String>,
+}
+
+impl ExpectedAnswers {
+    /// Read the answers from `data/expected.tsv`. A missing file gives no answers; comments and
+    /// blank lines are skipped, as are lines which cannot be parsed, after a warning.
+    #[must_use]
+    pub fn load() -> Self {
+        let Ok(tsv) = fs::read_to_string(EXPECTED_PATH) else {
+            return Self::default();
+        };
+        Self::from_tsv(&tsv)
+    }
+
+    pub(crate) fn from_tsv(tsv: &str) -> Self {
+        let answers = tsv
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let day = fields.next()?.parse().ok();
+                let part = fields.next().and_then(|part| part.parse().ok());
+                let answer = fields.next().map(|answer| answer.replace("\\n", "\n"));
+                match (day, part, answer) {
+                    (Some(day), Some(part), Some(answer)) => Some(((day, part), answer)),
+                    _ => {
+                        eprintln!("Could not parse line of {EXPECTED_PATH}: {line}");
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { answers }
+    }
+
+    fn to_tsv(&self) -> String {
+        let mut tsv = EXPECTED_HEADER.to_string();
+        for ((day, part), answer) in &self.answers {
+            tsv.push_str(&format!("{day}\t{part}\t{}\n", answer.replace('\n', "\\n")));
+        }
+        tsv
+    }
+
+    /// The accepted answer to a part, if it is known.
+    #[must_use]
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+
+    /// The parts of a day with known answers, and those answers.
+    pub fn parts(&self, day: u8) -> impl Iterator<Item = (u8, &str)> {
+        self.answers
+            .range((day, 0)..=(day, u8::MAX))
+            .map(|(&(_, part), answer)| (part, answer.as_str()))
+    }
+
+    /// Keep the accepted answer to a part, rewriting `data/expected.tsv` in order of day and part.
+    /// Returns whether the answer was new.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if the file cannot be written.
+    pub fn record(&mut self, day: u8, part: u8, answer: &str) -> io::Result<bool> {
+        if self.get(day, part) == Some(answer) {
+            return Ok(false);
+        }
+        self.answers.insert((day, part), answer.to_string());
+        fs::write(EXPECTED_PATH, self.to_tsv())?;
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    fn submission(part: u8, answer: &str, verdict: Verdict) -> Submission {
+        Submission {
+            day: 4,
+            part,
+            answer: answer.into(),
+            verdict,
+        }
+    }
+
+    fn log(submissions: Vec<Submission>) -> AnswerLog {
+        AnswerLog { submissions }
+    }
+
+    #[test]
+    fn test_from_response() {
+        assert_eq!(
+            Verdict::from_response("That's the right answer! You are one gold star closer."),
+            Some(Verdict::Correct)
+        );
+        assert_eq!(
+            Verdict::from_response("That's not the right answer; your answer is too high."),
+            Some(Verdict::TooHigh)
+        );
+        assert_eq!(
+            Verdict::from_response("That's not the right answer. If you're stuck..."),
+            Some(Verdict::Incorrect)
+        );
+        assert_eq!(
+            Verdict::from_response(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            ),
+            Some(Verdict::AlreadySolved)
+        );
+        assert_eq!(
+            Verdict::from_response("You gave an answer too recently."),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let response = "You gave an answer too recently; you have to wait after submitting an \
+            answer before trying again.  You have 4m 32s left to wait. [Return to Day 1]";
+        assert_eq!(cooldown(response), Some(Duration::from_secs(272)));
+        assert_eq!(
+            cooldown("You gave an answer too recently. You have 45s left to wait."),
+            Some(Duration::from_secs(45))
+        );
+        assert_eq!(cooldown("That's not the right answer."), None);
+    }
+
+    #[test]
+    fn test_tsv_roundtrip() {
+        let first = submission(1, "1234", Verdict::TooLow);
+        let second = submission(2, "a\tb", Verdict::Correct);
+        let tsv = format!("{}\n\n{}\nnot a line\n", first.to_line(), second.to_line());
+        assert_eq!(first.to_line(), "4\t1\ttoo-low\t1234");
+        assert_eq!(AnswerLog::from_tsv(&tsv).submissions, vec![first, second]);
+    }
+
+    #[test]
+    fn test_tsv_multiline_answer() {
+        let art = submission(2, "#..#\n####\n#..#", Verdict::Incorrect);
+        assert_eq!(art.to_line(), "4\t2\tincorrect\t#..#\\n####\\n#..#");
+
+        let answers = AnswerLog::from_tsv(&format!("{}\n", art.to_line()));
+        assert_eq!(answers.submissions, vec![art]);
+        assert_eq!(
+            answers.check(4, 2, "#..#\n####\n#..#"),
+            Err(Refusal::AlreadyWrong(Verdict::Incorrect))
+        );
+    }
+
+    #[test]
+    fn test_check_solved() {
+        let answers = log(vec![
+            submission(1, "10", Verdict::Incorrect),
+            submission(1, "12", Verdict::Correct),
+            submission(2, "3", Verdict::AlreadySolved),
+        ]);
+        assert_eq!(
+            answers.check(4, 1, "13"),
+            Err(Refusal::Solved(Some("12".into())))
+        );
+        assert_eq!(answers.check(4, 2, "13"), Err(Refusal::Solved(None)));
+        assert_eq!(answers.check(5, 1, "12"), Ok(()));
+    }
+
+    #[test]
+    fn test_check_wrong() {
+        let answers = log(vec![
+            submission(1, "100", Verdict::TooHigh),
+            submission(1, "20", Verdict::TooLow),
+            submission(1, "abc", Verdict::Incorrect),
+        ]);
+        assert_eq!(
+            answers.check(4, 1, "abc"),
+            Err(Refusal::AlreadyWrong(Verdict::Incorrect))
+        );
+        assert_eq!(
+            answers.check(4, 1, "100"),
+            Err(Refusal::AlreadyWrong(Verdict::TooHigh))
+        );
+        assert_eq!(answers.check(4, 1, "50"), Ok(()));
+        assert_eq!(answers.check(4, 2, "100"), Ok(()));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let answers = log(vec![
+            submission(1, "100", Verdict::TooHigh),
+            submission(1, "20", Verdict::TooLow),
+            submission(1, "80", Verdict::TooHigh),
+            submission(1, "10", Verdict::TooLow),
+            submission(2, "7", Verdict::TooLow),
+        ]);
+        let bounds = answers.bounds(4, 1);
+        assert_eq!(
+            bounds,
+            Bounds {
+                above: Some(20),
+                below: Some(80)
+            }
+        );
+        assert_eq!(bounds.to_string(), "greater than 20 and less than 80");
+        assert_eq!(answers.bounds(4, 2).to_string(), "greater than 7");
+        assert!(!answers.bounds(5, 1).is_known());
+
+        assert_eq!(answers.check(4, 1, "79"), Ok(()));
+        assert_eq!(answers.check(4, 1, "90"), Err(Refusal::OutOfBounds(bounds)));
+        assert!(matches!(
+            answers.check(4, 1, "20"),
+            Err(Refusal::AlreadyWrong(Verdict::TooLow))
+        ));
+        assert!(matches!(
+            answers.check(4, 1, "-3"),
+            Err(Refusal::OutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_puzzle_answers() {
+        let puzzle = "## --- Part Two ---\n\nYour puzzle answer was `3421505`.\n\n\
+            Your puzzle answer was <code>5129386</code>.\n";
+        assert_eq!(parse_puzzle_answers(puzzle), vec!["3421505", "5129386"]);
+        assert!(parse_puzzle_answers("## --- Day 1: The Tyranny ---\n").is_empty());
+    }
+
+    #[test]
+    fn test_expected_answers() {
+        let expected = ExpectedAnswers::from_tsv(
+            "# day\tpart\tanswer\n1\t1\t3421505\n\n8\t2\t#..#\\n#..#\nbad\n",
+        );
+        assert_eq!(expected.get(1, 1), Some("3421505"));
+        assert_eq!(expected.get(8, 2), Some("#..#\n#..#"));
+        assert_eq!(expected.get(8, 1), None);
+        assert_eq!(
+            expected.parts(8).collect::<Vec<_>>(),
+            vec![(2, "#..#\n#..#")]
+        );
+        assert!(expected
+            .to_tsv()
+            .ends_with("\n1\t1\t3421505\n8\t2\t#..#\\n#..#\n"));
+    }
+}
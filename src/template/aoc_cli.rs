@@ -1,15 +1,36 @@
 /// Wrapper module around the "aoc-cli" command-line.
 use std::{
+    env,
     fmt::Display,
+    fs,
     process::{Command, Output, Stdio},
+    thread,
+    time::Duration,
 };
 
+use crate::template::session::{Session, TokenError, SESSION_VAR};
+use crate::template::{config, profile};
+
+/// The least time to leave between requests to adventofcode.com, to keep the load on it light.
+pub const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Identifies requests made directly to adventofcode.com, as the site asks automated tools to.
+/// aoc-cli sends its own.
+pub const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (github.com/andypymont/advent2019-rust)"
+);
+
 #[derive(Debug)]
 pub enum CliError {
     CommandNotFound,
     CommandNotCallable,
     BadExitStatus(Output),
     IoError,
+    /// The token of the current input profile could not be loaded.
+    Session(TokenError),
 }
 
 impl Display for CliError {
@@ -21,6 +42,7 @@ impl Display for CliError {
                 write!(f, "aoc-cli exited with a non-zero status.")
             }
             CliError::IoError => write!(f, "could not write output files to file system."),
+            CliError::Session(e) => write!(f, "{e}"),
         }
     }
 }
@@ -37,17 +59,53 @@ pub fn check() -> Result<(), CliError> {
     Ok(())
 }
 
+/// Wait until `MIN_REQUEST_INTERVAL` has passed since the last request to adventofcode.com made
+/// by any of these commands, then note the time of this one. The time is kept as the modification
+/// time of a file in the temporary directory, so it holds across separate runs.
+pub fn throttle() {
+    let path = env::temp_dir().join("advent-of-code-last-request");
+    let elapsed = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok());
+
+    if let Some(wait) = elapsed.and_then(|elapsed| MIN_REQUEST_INTERVAL.checked_sub(elapsed)) {
+        println!("Waiting {wait:.1?} before the next request to adventofcode.com...");
+        thread::sleep(wait);
+    }
+    let _ = fs::write(&path, "");
+}
+
+/// Whether the input for a day has already been downloaded. The empty file left by `scaffold`
+/// does not count.
+#[must_use]
+pub fn input_exists(day: u8) -> bool {
+    fs::metadata(get_input_path(day)).is_ok_and(|metadata| metadata.len() > 0)
+}
+
+/// The puzzle description saved by an earlier `download` or `read`, if there is one.
+#[must_use]
+pub fn cached_puzzle(day: u8) -> Option<String> {
+    fs::read_to_string(get_puzzle_path(day))
+        .ok()
+        .filter(|puzzle| !puzzle.trim().is_empty())
+}
+
+/// Fetch the puzzle description alone and save it, replacing any earlier copy.
+///
 /// # Errors
 ///
 /// Will return `CliError` if aoc-cli is not present in environment, could not be called, count
 /// not write output files to the file system, or exists with a non-zero status.
-pub fn read(day: u8) -> Result<Output, CliError> {
+pub fn download_puzzle(day: u8) -> Result<Output, CliError> {
     let puzzle_path = get_puzzle_path(day);
+    fs::create_dir_all("data/puzzles").map_err(|_| CliError::IoError)?;
 
     let args = build_args(
-        "read",
+        "download",
         &[
-            "--description-only".into(),
+            "--puzzle-only".into(),
+            "--overwrite".into(),
             "--puzzle-file".into(),
             puzzle_path,
         ],
@@ -64,6 +122,8 @@ pub fn read(day: u8) -> Result<Output, CliError> {
 pub fn download(day: u8) -> Result<Output, CliError> {
     let input_path = get_input_path(day);
     let puzzle_path = get_puzzle_path(day);
+    fs::create_dir_all(format!("data/{}", profile::inputs_folder()))
+        .map_err(|_| CliError::IoError)?;
 
     let args = build_args(
         "download",
@@ -84,6 +144,9 @@ pub fn download(day: u8) -> Result<Output, CliError> {
     Ok(output)
 }
 
+/// Submit an answer. The response is captured in the returned `Output` rather than printed, so
+/// that the verdict can be read from it.
+///
 /// # Errors
 ///
 /// Will return `CliError` if aoc-cli is not present in environment, could not be called, count
@@ -93,31 +156,22 @@ pub fn submit(day: u8, part: u8, result: &str) -> Result<Output, CliError> {
     let mut args = build_args("submit", &[], day);
     args.push(part.to_string());
     args.push(result.to_string());
-    call_aoc_cli(&args)
+    call_aoc_cli_with(&args, Stdio::piped())
 }
 
 fn get_input_path(day: u8) -> String {
-    format!("data/inputs/{day:02}.txt")
+    format!("data/{}/{day:02}.txt", profile::inputs_folder())
 }
 
-fn get_puzzle_path(day: u8) -> String {
+pub(crate) fn get_puzzle_path(day: u8) -> String {
     format!("data/puzzles/{day:02}.md")
 }
 
-fn get_year() -> Option<u16> {
-    match std::env::var("AOC_YEAR") {
-        Ok(x) => x.parse().ok().or(None),
-        Err(_) => None,
-    }
-}
-
 fn build_args(command: &str, args: &[String], day: u8) -> Vec<String> {
     let mut cmd_args = args.to_vec();
 
-    if let Some(year) = get_year() {
-        cmd_args.push("--year".into());
-        cmd_args.push(year.to_string());
-    }
+    cmd_args.push("--year".into());
+    cmd_args.push(config::year().to_string());
 
     cmd_args.append(&mut vec!["--day".into(), day.to_string(), command.into()]);
 
@@ -129,10 +183,22 @@ fn build_args(command: &str, args: &[String], day: u8) -> Vec<String> {
 /// Will return `CliError` if aoc-cli is not present in environment, could not be called, count
 /// not write output files to the file system, or exists with a non-zero status.
 fn call_aoc_cli(args: &[String]) -> Result<Output, CliError> {
+    call_aoc_cli_with(args, Stdio::inherit())
+}
+
+fn call_aoc_cli_with(args: &[String], stdout: Stdio) -> Result<Output, CliError> {
     // println!("Calling >aoc with: {}", args.join(" "));
-    let output = Command::new("aoc")
+    let mut cmd = Command::new("aoc");
+    // aoc-cli knows only the main account's token, so is given the profile's in its place.
+    if profile::current().is_some() {
+        let session = Session::load().map_err(CliError::Session)?;
+        cmd.env(SESSION_VAR, session.token());
+    }
+
+    throttle();
+    let output = cmd
         .args(args)
-        .stdout(Stdio::inherit())
+        .stdout(stdout)
         .stderr(Stdio::inherit())
         .output()
         .map_err(|_| CliError::CommandNotCallable)?;
@@ -1,38 +1,87 @@
 use std::io;
 
 use crate::template::{
+    paint, profile,
     readme_benchmarks::{self, Timings},
-    ANSI_BOLD, ANSI_ITALIC, ANSI_RESET,
+    timings::TimingRecord,
+    ANSI_BOLD, ANSI_DIM,
 };
 
-pub fn handler(is_release: bool, is_timed: bool) {
+/// Run each of `days` in turn, or with `is_parallel` several days at once (see
+/// `child_commands::run_solutions_parallel`). Timings taken in parallel are skewed by the other
+/// days running alongside them, so they are not recorded nor written to the README; nor are
+/// those of a run covering only some of the days written to the README. Timings for the inputs
+/// of a profile other than the main one are not kept either.
+///
+/// With `is_json`, only the lines of JSON printed by each day for `--json` are output.
+pub fn handler(days: &[usize], is_release: bool, is_timed: bool, is_parallel: bool, is_json: bool) {
     let mut timings: Vec<Timings> = vec![];
+    // debug builds are too slow to compare against the record.
+    let profile = profile::current();
+    let is_recorded = is_timed && is_release && !is_parallel && !is_json && profile.is_none();
+    let mut record = is_recorded.then(TimingRecord::load);
+
+    // mirror `--time`, `--json` and `--profile` flags to child invocations.
+    let mut flags: Vec<&str> = [("--time", is_timed), ("--json", is_json)]
+        .into_iter()
+        .filter_map(|(flag, is_set)| is_set.then_some(flag))
+        .collect();
+    if let Some(profile) = &profile {
+        flags.extend(["--profile", profile]);
+    }
 
-    (1..=25).for_each(|day| {
-        if day > 1 {
-            println!();
+    let mut collect = |day: usize, output: &[String]| {
+        if is_json {
+            return;
         }
-
-        println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
-        println!("------");
-
-        let output = child_commands::run_solution(day, is_timed, is_release)
-            .expect("panic during executing day {day}");
-
         if output.is_empty() {
             println!("Not solved.");
         } else {
-            let val = child_commands::parse_exec_time(&output, day);
+            let val = child_commands::parse_exec_time(output, day);
+            if let Some(record) = &mut record {
+                record.update(&val);
+            }
             timings.push(val);
         }
-    });
+    };
 
-    if is_timed {
-        let total_millis = timings.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64;
+    if is_parallel {
+        child_commands::run_solutions_parallel(days, is_release, &flags, |day, output, errors| {
+            if !is_json {
+                print_header(day, day == days[0]);
+            }
+            output.iter().for_each(|line| println!("{line}"));
+            eprint!("{errors}");
+            collect(day, &output);
+        })
+        .expect("panic during executing solutions");
+    } else {
+        days.iter().for_each(|&day| {
+            if !is_json {
+                print_header(day, day == days[0]);
+            }
+            let output = child_commands::run_solution(day, is_release, &flags)
+                .expect("panic during executing day {day}");
+            collect(day, &output);
+        });
+    }
 
-        println!("\n{ANSI_BOLD}Total:{ANSI_RESET} {ANSI_ITALIC}{total_millis:.2}ms{ANSI_RESET}");
+    if is_timed && !is_json {
+        let total_millis = timings.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64;
 
-        if is_release {
+        println!(
+            "\n{} {}",
+            paint(ANSI_BOLD, "Total:"),
+            paint(ANSI_DIM, format!("{total_millis:.2}ms"))
+        );
+
+        if is_parallel {
+            println!("Timings from a parallel run are not recorded.");
+        } else if profile.is_some() {
+            println!("Timings for a profile's inputs are not recorded.");
+        } else if is_release && days.len() < 25 {
+            println!("Only some days were run, so the README benchmarks were not updated.");
+        } else if is_release {
             match readme_benchmarks::update(timings, total_millis) {
                 Ok(_) => println!("Successfully updated README with benchmarks."),
                 Err(_) => {
@@ -41,11 +90,28 @@ pub fn handler(is_release: bool, is_timed: bool) {
             }
         }
     }
+
+    if let Some(record) = record {
+        if record.save().is_err() {
+            eprintln!("Failed to save timings.");
+        }
+    }
+}
+
+fn print_header(day: usize, is_first: bool) {
+    if !is_first {
+        println!();
+    }
+
+    println!("{}", paint(ANSI_BOLD, format!("Day {day}")));
+    println!("------");
 }
 
 #[derive(Debug)]
 pub enum Error {
     BrokenPipe,
+    /// The solution bins did not compile; cargo has already printed why.
+    BuildFailed,
     Parser(String),
     IO(io::Error),
 }
@@ -63,45 +129,60 @@ pub fn get_path_for_bin(day: usize) -> String {
 
 /// All solutions live in isolated binaries.
 /// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
-mod child_commands {
+pub(crate) mod child_commands {
     use super::{get_path_for_bin, Error};
+    use crate::template::progress::ProgressBar;
+    use crate::template::timings::parse_duration;
+    use crate::template::{inherit_color, strip_ansi};
     use std::{
+        collections::BTreeMap,
         io::{BufRead, BufReader},
+        num::NonZeroUsize,
         path::Path,
         process::{Command, Stdio},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc,
+        },
         thread,
     };
 
-    /// Run the solution bin for a given day
+    fn cargo_args(day: usize, is_release: bool, flags: &[&str]) -> Vec<String> {
+        let mut args = vec![
+            "run".into(),
+            "--quiet".into(),
+            "--bin".into(),
+            format!("{day:02}"),
+        ];
+
+        if is_release {
+            args.push("--release".into());
+        }
+
+        if !flags.is_empty() {
+            args.push("--".into());
+            args.extend(flags.iter().map(|flag| (*flag).to_string()));
+        }
+
+        args
+    }
+
+    /// Run the solution bin for a given day, passing `flags` on to it.
     pub fn run_solution(
         day: usize,
-        is_timed: bool,
         is_release: bool,
+        flags: &[&str],
     ) -> Result<Vec<String>, Error> {
-        let day_padded = format!("{day:02}");
-
         // skip command invocation for days that have not been scaffolded yet.
         if !Path::new(&get_path_for_bin(day)).exists() {
             return Ok(vec![]);
         }
 
-        let mut args = vec!["run", "--quiet", "--bin", &day_padded];
-
-        if is_release {
-            args.push("--release");
-        }
-
-        if is_timed {
-            // mirror `--time` flag to child invocations.
-            args.push("--");
-            args.push("--time");
-        }
-
         // spawn child command with piped stdout/stderr.
         // forward output to stdout/stderr while grabbing stdout lines.
 
-        let mut cmd = Command::new("cargo")
-            .args(&args)
+        let mut cmd = inherit_color(&mut Command::new("cargo"))
+            .args(cargo_args(day, is_release, flags))
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()?;
@@ -129,6 +210,91 @@ mod child_commands {
         Ok(output)
     }
 
+    /// Run the solution bin for a given day without forwarding its output, returning its stdout
+    /// lines and its stderr.
+    pub fn capture_solution(
+        day: usize,
+        is_release: bool,
+        flags: &[&str],
+    ) -> Result<(Vec<String>, String), Error> {
+        if !Path::new(&get_path_for_bin(day)).exists() {
+            return Ok((vec![], String::new()));
+        }
+
+        let output = inherit_color(&mut Command::new("cargo"))
+            .args(cargo_args(day, is_release, flags))
+            .output()?;
+
+        Ok((
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(String::from)
+                .collect(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))
+    }
+
+    /// Run the solution bins for `days` concurrently, no more of them at once than there are
+    /// CPUs. `report` is called with each day's stdout lines and stderr in the order of `days`,
+    /// as soon as that day and every day before it have finished. Meanwhile, a progress bar counts
+    /// the days finished.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Error::BuildFailed`, without running any day, if the bins do not compile.
+    pub fn run_solutions_parallel(
+        days: &[usize],
+        is_release: bool,
+        flags: &[&str],
+        mut report: impl FnMut(usize, Vec<String>, String),
+    ) -> Result<(), Error> {
+        // build every bin up front, so the runs do not queue on cargo's lock to compile them.
+        let mut build = vec!["build", "--quiet", "--bins"];
+        if is_release {
+            build.push("--release");
+        }
+        if !Command::new("cargo").args(build).status()?.success() {
+            return Err(Error::BuildFailed);
+        }
+
+        let workers = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let next_ix = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                let sender = sender.clone();
+                let next_ix = &next_ix;
+                scope.spawn(move || loop {
+                    let ix = next_ix.fetch_add(1, Ordering::Relaxed);
+                    let Some(&day) = days.get(ix) else {
+                        break;
+                    };
+                    let result = capture_solution(day, is_release, flags);
+                    if sender.send((ix, result)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            let mut progress = ProgressBar::new(days.len(), "days");
+            let mut finished = BTreeMap::new();
+            let mut expected = 0;
+            for (ix, result) in receiver {
+                finished.insert(ix, result);
+                progress.clear();
+                while let Some(result) = finished.remove(&expected) {
+                    let (output, errors) = result?;
+                    report(days[expected], output, errors);
+                    expected += 1;
+                }
+                progress.advance();
+            }
+            Ok(())
+        })
+    }
+
     pub fn parse_exec_time(output: &[String], day: usize) -> super::Timings {
         let mut timings = super::Timings {
             day,
@@ -137,6 +303,9 @@ mod child_commands {
             total_nanos: 0_f64,
         };
 
+        // the output is styled when it is forwarded to a terminal.
+        let output: Vec<String> = output.iter().map(|l| strip_ansi(l)).collect();
+
         output
             .iter()
             .filter_map(|l| {
@@ -165,29 +334,23 @@ mod child_commands {
         timings
     }
 
-    fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
-        s.split(postfix).next()?.parse().ok()
-    }
-
     fn parse_time(line: &str) -> Option<(&str, f64)> {
-        // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
         let str_timing = line
             .split(" samples)")
             .next()?
             .split('(')
-            .last()?
+            .next_back()?
             .split('@')
             .next()?
             .trim();
 
-        let parsed_timing = match str_timing {
-            s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
-            s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1_000_f64),
-            s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
-            s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
-        }?;
+        // `--median` reports "median X, IQR Y–Z"; only the median is kept.
+        let str_timing = match str_timing.strip_prefix("median ") {
+            Some(median) => median.split(',').next()?,
+            None => str_timing,
+        };
 
-        Some((str_timing, parsed_timing))
+        Some((str_timing, parse_duration(str_timing)?))
     }
 
     /// copied from: https://github.com/rust-lang/rust/blob/1.64.0/library/std/src/macros.rs#L328-L333
@@ -238,6 +401,16 @@ mod child_commands {
             assert_eq!(res.part_2.unwrap(), "100ms");
         }
 
+        #[test]
+        fn test_median() {
+            let res = parse_exec_time(
+                &["Part 1: 4 (median 1.5ms, IQR 1.2ms–2.0ms @ 100 samples)".into()],
+                1,
+            );
+            assert_approx_eq!(res.total_nanos, 1_500_000_f64);
+            assert_eq!(res.part_1.unwrap(), "1.5ms");
+        }
+
         #[test]
         fn test_missing_parts() {
             let res = parse_exec_time(
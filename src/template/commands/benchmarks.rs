@@ -0,0 +1,21 @@
+use crate::template::readme_benchmarks;
+use crate::template::timings::TimingRecord;
+
+/// Rewrite the benchmarks table in README.md from the timings recorded in `data/timings.json`
+/// by `time` and `all --time`, adding the table if the README does not have one yet.
+pub fn handler() {
+    let timings = TimingRecord::load().timings();
+    if timings.is_empty() {
+        eprintln!("No timings recorded yet, run `cargo time` first.");
+        std::process::exit(1);
+    }
+
+    let total_millis = timings.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64;
+    match readme_benchmarks::regenerate(timings, total_millis) {
+        Ok(()) => println!("Successfully updated README with benchmarks."),
+        Err(_) => {
+            eprintln!("Failed to update readme with benchmarks.");
+            std::process::exit(1);
+        }
+    }
+}
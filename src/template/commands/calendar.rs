@@ -0,0 +1,111 @@
+use std::path::Path;
+use std::process;
+
+use crate::template::commands::all::get_path_for_bin;
+use crate::template::commands::test;
+use crate::template::session::{Session, TokenError};
+use crate::template::{config, paint, ANSI_BOLD, ANSI_DIM, ANSI_GREEN, ANSI_RED, ANSI_YELLOW};
+
+/// The stars earned on each day, read from the `aria-label` of each day's link on the calendar
+/// page, such as `Day 1, two stars`. Days without stars have a label of just `Day 3`.
+fn parse_stars(calendar: &str) -> [u8; 25] {
+    let mut stars = [0; 25];
+    for label in calendar.split("aria-label=\"Day ").skip(1) {
+        let Some((label, _)) = label.split_once('"') else {
+            continue;
+        };
+        let (day, earned) = label.split_once(", ").unwrap_or((label, ""));
+        let Some(ix) = day
+            .parse::<usize>()
+            .ok()
+            .and_then(|day| day.checked_sub(1))
+            .filter(|ix| *ix < 25)
+        else {
+            continue;
+        };
+        stars[ix] = match earned {
+            "one star" => 1,
+            "two stars" => 2,
+            _ => 0,
+        };
+    }
+    stars
+}
+
+fn format_stars(earned: u8) -> String {
+    let gold = "★".repeat(usize::from(earned));
+    let missing = "☆".repeat(usize::from(2 - earned.min(2)));
+    format!("{}{}", paint(ANSI_YELLOW, gold), paint(ANSI_DIM, missing))
+}
+
+/// Whether a day has a solution and, if it does, whether its tests pass.
+fn local_status(day: u8) -> String {
+    if !Path::new(&get_path_for_bin(usize::from(day))).exists() {
+        return paint(ANSI_DIM, "not started");
+    }
+
+    match test::run_tests(day) {
+        Ok(report) if report.succeeded() => {
+            format!("solved, {}", paint(ANSI_GREEN, "tests pass"))
+        }
+        Ok(report) => format!(
+            "solved, {}",
+            paint(ANSI_RED, format!("{} tests fail", report.failures.len()))
+        ),
+        Err(_) => "solved, tests could not run".into(),
+    }
+}
+
+/// Print the stars earned on each day of the event, as shown on the site's calendar for the
+/// logged-in user, with the total. With `local`, each day also shows whether it has a solution
+/// here and whether that solution's tests pass.
+pub fn handler(local: bool) {
+    let year = config::year();
+    let calendar = Session::load().and_then(|session| {
+        let page = session.fetch(&format!("/{year}"))?;
+        if page.contains("/auth/login") {
+            Err(TokenError::LoggedOut)
+        } else {
+            Ok(page)
+        }
+    });
+    let stars = match calendar {
+        Ok(calendar) => parse_stars(&calendar),
+        Err(e) => {
+            eprintln!("failed to fetch the calendar: {e}");
+            process::exit(1);
+        }
+    };
+
+    println!("{}", paint(ANSI_BOLD, format!("Advent of Code {year}")));
+    for (day, earned) in (1..=25).zip(stars) {
+        if local {
+            println!("{day:>2} {}  {}", format_stars(earned), local_status(day));
+        } else {
+            println!("{day:>2} {}", format_stars(earned));
+        }
+    }
+
+    let total: u8 = stars.iter().sum();
+    println!("\n{} {total}/50 stars", paint(ANSI_BOLD, "Total:"));
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stars() {
+        let calendar = "<a aria-label=\"Day 1, two stars\" href=\"/2019/day/1\" \
+            class=\"calendar-day1 calendar-verycomplete\">\
+            <a aria-label=\"Day 2, one star\" href=\"/2019/day/2\" class=\"calendar-day2\">\
+            <a aria-label=\"Day 3\" href=\"/2019/day/3\" class=\"calendar-day3\">\
+            <a aria-label=\"Day 25, two stars\" href=\"/2019/day/25\">\
+            <a aria-label=\"Day 26, two stars\">";
+
+        let stars = parse_stars(calendar);
+        assert_eq!(stars[..3], [2, 1, 0]);
+        assert_eq!(stars[24], 2);
+        assert_eq!(stars.iter().map(|x| u32::from(*x)).sum::<u32>(), 5);
+    }
+}
@@ -0,0 +1,243 @@
+use std::process;
+
+/// The name completions are registered for: the binary behind the cargo aliases.
+const BIN: &str = "advent_of_code";
+
+struct Subcommand {
+    name: &'static str,
+    flags: &'static [&'static str],
+    /// Other words which can follow the subcommand, such as those of `token`.
+    words: &'static [&'static str],
+    takes_day: bool,
+}
+
+const fn subcommand(
+    name: &'static str,
+    flags: &'static [&'static str],
+    words: &'static [&'static str],
+    takes_day: bool,
+) -> Subcommand {
+    Subcommand {
+        name,
+        flags,
+        words,
+        takes_day,
+    }
+}
+
+/// Every subcommand, as parsed in `main`, with what can follow it.
+const SUBCOMMANDS: &[Subcommand] = &[
+    subcommand(
+        "all",
+        &["--release", "--time", "--parallel", "--json", "--days"],
+        &[],
+        false,
+    ),
+    subcommand("benchmarks", &[], &[], false),
+    subcommand("calendar", &["--local"], &[], false),
+    subcommand("completions", &[], &["bash", "zsh", "fish"], false),
+    subcommand("download", &["--force"], &[], true),
+    subcommand("intcode", &["--input", "--dot"], &["bench"], false),
+    subcommand("leaderboard", &[], &[], false),
+    subcommand("read", &["--refresh"], &[], true),
+    subcommand("scaffold", &["--download"], &[], true),
+    subcommand(
+        "solve",
+        &[
+            "--release",
+            "--time",
+            "--submit",
+            "--part",
+            "--input",
+            "--example",
+            "--stdin",
+            "--json",
+            "--watch",
+            "--test",
+            "--wait",
+            "--flamegraph",
+            "--heap-profile",
+            "--debug-intcode",
+            "--interactive",
+            "--transcript",
+            "--record",
+            "--replay",
+        ],
+        &[],
+        true,
+    ),
+    subcommand("submit", &["--wait"], &[], true),
+    subcommand("test", &[], &["all"], true),
+    subcommand("time", &[], &[], true),
+    subcommand("token", &[], &["set", "show", "check"], false),
+    subcommand("verify", &[], &["all"], true),
+    subcommand("wait", &[], &[], true),
+];
+
+/// Flags which every subcommand understands.
+const GLOBAL_FLAGS: &[&str] = &["--no-color", "--profile"];
+
+fn days() -> Vec<String> {
+    (1..=25).map(|day| day.to_string()).collect()
+}
+
+/// The words which can follow a subcommand, flags first.
+fn candidates(subcommand: &Subcommand) -> Vec<String> {
+    let mut words: Vec<String> = subcommand
+        .flags
+        .iter()
+        .chain(GLOBAL_FLAGS)
+        .chain(subcommand.words)
+        .map(|word| (*word).to_string())
+        .collect();
+    if subcommand.takes_day {
+        words.extend(days());
+    }
+    words
+}
+
+fn names() -> String {
+    SUBCOMMANDS
+        .iter()
+        .map(|subcommand| subcommand.name)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bash() -> String {
+    let cases: String = SUBCOMMANDS
+        .iter()
+        .map(|subcommand| {
+            format!(
+                "        {}) words=\"{}\" ;;\n",
+                subcommand.name,
+                candidates(subcommand).join(" ")
+            )
+        })
+        .collect();
+
+    format!(
+        "_{BIN}() {{
+    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    local words
+    if [ \"$COMP_CWORD\" -eq 1 ]; then
+        words=\"{}\"
+    else
+        case \"${{COMP_WORDS[1]}}\" in
+{cases}        *) words=\"\" ;;
+        esac
+    fi
+    COMPREPLY=($(compgen -W \"$words\" -- \"$cur\"))
+}}
+complete -F _{BIN} {BIN}
+",
+        names()
+    )
+}
+
+fn zsh() -> String {
+    let cases: String = SUBCOMMANDS
+        .iter()
+        .map(|subcommand| {
+            format!(
+                "        {}) compadd -- {} ;;\n",
+                subcommand.name,
+                candidates(subcommand).join(" ")
+            )
+        })
+        .collect();
+
+    format!(
+        "#compdef {BIN}
+_{BIN}() {{
+    if (( CURRENT == 2 )); then
+        compadd -- {}
+        return
+    fi
+    case $words[2] in
+{cases}    esac
+}}
+compdef _{BIN} {BIN}
+",
+        names()
+    )
+}
+
+fn fish() -> String {
+    let mut lines = vec![
+        format!("complete -c {BIN} -f"),
+        format!(
+            "complete -c {BIN} -n __fish_use_subcommand -a \"{}\"",
+            names()
+        ),
+    ];
+
+    for subcommand in SUBCOMMANDS {
+        let condition = format!("-n \"__fish_seen_subcommand_from {}\"", subcommand.name);
+        for flag in subcommand.flags.iter().chain(GLOBAL_FLAGS) {
+            let long = flag.trim_start_matches('-');
+            lines.push(format!("complete -c {BIN} {condition} -l {long}"));
+        }
+        let mut words: Vec<String> = subcommand.words.iter().map(|w| (*w).to_string()).collect();
+        if subcommand.takes_day {
+            words.extend(days());
+        }
+        if !words.is_empty() {
+            lines.push(format!(
+                "complete -c {BIN} {condition} -a \"{}\"",
+                words.join(" ")
+            ));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Print a completion script for `shell`, covering every subcommand, its flags and day numbers.
+pub fn handler(shell: &str) {
+    let script = match shell {
+        "bash" => bash(),
+        "zsh" => zsh(),
+        "fish" => fish(),
+        _ => {
+            eprintln!("Unknown shell: {shell}. Use bash, zsh or fish.");
+            process::exit(1);
+        }
+    };
+    print!("{script}");
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bash() {
+        let script = bash();
+        assert!(script.contains("words=\"all benchmarks calendar completions download"));
+        assert!(
+            script.contains("        token) words=\"--no-color --profile set show check\" ;;\n")
+        );
+        assert!(script.contains("        time) words=\"--no-color --profile 1 2 3 4"));
+        assert!(script.ends_with("complete -F _advent_of_code advent_of_code\n"));
+    }
+
+    #[test]
+    fn test_zsh() {
+        let script = zsh();
+        assert!(script.starts_with("#compdef advent_of_code\n"));
+        assert!(
+            script.contains("        scaffold) compadd -- --download --no-color --profile 1 2 3")
+        );
+    }
+
+    #[test]
+    fn test_fish() {
+        let script = fish();
+        assert!(script
+            .contains("complete -c advent_of_code -n \"__fish_seen_subcommand_from all\" -l days"));
+        assert!(script.contains(
+            "complete -c advent_of_code -n \"__fish_seen_subcommand_from test\" -a \"all 1 2"
+        ));
+    }
+}
@@ -1,7 +1,14 @@
-use crate::template::aoc_cli;
-use std::process;
+use crate::template::{aoc_cli, examples};
+use std::{fs, process};
+
+/// Download the input and puzzle for a day, unless its input is already there and `force` is
+/// not set, and save the examples found in the puzzle.
+pub fn handler(day: u8, force: bool) {
+    if !force && aoc_cli::input_exists(day) {
+        println!("🎄 Input for day {day} is already downloaded. Use --force to download it again.");
+        return;
+    }
 
-pub fn handler(day: u8) {
     if aoc_cli::check().is_err() {
         eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
         process::exit(1);
@@ -9,6 +16,34 @@ pub fn handler(day: u8) {
 
     if let Err(e) = aoc_cli::download(day) {
         eprintln!("failed to call aoc-cli: {e}");
+        if matches!(e, aoc_cli::CliError::BadExitStatus(_)) {
+            eprintln!("Run \"cargo token check\" to check that your session token is valid.");
+        }
         process::exit(1);
     };
+
+    save_examples(day, force);
+}
+
+/// Save the code blocks of the puzzle as examples. Examples which have already been filled in are
+/// kept unless `force` is set, as they may have been edited.
+fn save_examples(day: u8, force: bool) {
+    let Ok(puzzle) = fs::read_to_string(aoc_cli::get_puzzle_path(day)) else {
+        return;
+    };
+
+    let blocks = examples::extract(&puzzle);
+    if blocks.is_empty() {
+        println!("No examples found in the puzzle.");
+        return;
+    }
+
+    match examples::save(day, &blocks, force) {
+        Ok(paths) => {
+            for path in paths {
+                println!("🎄 Successfully wrote example to \"{path}\".");
+            }
+        }
+        Err(e) => eprintln!("failed to write examples: {e}"),
+    }
 }
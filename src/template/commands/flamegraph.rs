@@ -0,0 +1,74 @@
+use std::fs;
+use std::process::{self, Command};
+
+use crate::template::commands::solve::{solution_args, Input};
+use crate::template::inherit_color;
+
+/// Where flamegraphs are written: one per day, or per part if just one part is profiled.
+fn get_flamegraph_path(day: u8, part: Option<u8>) -> String {
+    match part {
+        Some(part) => format!("data/profiles/{day:02}-part{part}.svg"),
+        None => format!("data/profiles/{day:02}.svg"),
+    }
+}
+
+/// Run a day's solution once under cargo-flamegraph, which samples it with `perf` (or `dtrace`),
+/// and write the flamegraph to `data/profiles/`. The release build is profiled, with debug
+/// symbols so that the frames are named.
+pub fn handler(day: u8, part: Option<u8>, input: &Input, forwarded_args: &[String]) {
+    let is_installed = Command::new("cargo")
+        .args(["flamegraph", "--version"])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !is_installed {
+        eprintln!("cargo-flamegraph not found or not callable. Try running \"cargo install flamegraph\" to install it.");
+        process::exit(1);
+    }
+
+    if let Err(e) = fs::create_dir_all("data/profiles") {
+        eprintln!("Failed to create data/profiles: {e}");
+        process::exit(1);
+    }
+
+    let path = get_flamegraph_path(day, part);
+    let mut cmd_args = vec![
+        "flamegraph".to_string(),
+        "--bin".to_string(),
+        format!("{day:02}"),
+        "--output".to_string(),
+        path.clone(),
+        "--".to_string(),
+    ];
+    cmd_args.extend(solution_args(None, part, input, false, forwarded_args));
+
+    let status = inherit_color(&mut Command::new("cargo"))
+        .args(&cmd_args)
+        .env("CARGO_PROFILE_RELEASE_DEBUG", "true")
+        .status();
+
+    match status {
+        Ok(status) if status.success() => println!("🎄 Wrote flamegraph to \"{path}\"."),
+        Ok(_) => {
+            eprintln!("cargo-flamegraph exited with a non-zero status.");
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("failed to run cargo-flamegraph: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_flamegraph_path() {
+        assert_eq!(get_flamegraph_path(7, None), "data/profiles/07.svg");
+        assert_eq!(
+            get_flamegraph_path(12, Some(2)),
+            "data/profiles/12-part2.svg"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{fs, io, process};
+
+use crate::intcode::asm::assemble;
+use crate::intcode::control_flow::ControlFlowGraph;
+use crate::intcode::{
+    catch_interrupts, Backend, Computer, Debugger, Engine, ExecutionError, Interrupts, Memory,
+    RunState,
+};
+
+fn parse_inputs(text: &str) -> Result<Vec<i64>, std::num::ParseIntError> {
+    text.split(',')
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| value.trim().parse())
+        .collect()
+}
+
+fn read_program(path: &str) -> String {
+    match fs::read_to_string(path) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("could not read {path}: {e}");
+            process::exit(1);
+        }
+    }
+}
+
+pub fn handler(path: &str, input: Option<&str>) {
+    let program = read_program(path);
+    let Ok(mut computer) = program.parse::<Computer>() else {
+        eprintln!("{path} is not a valid Intcode program");
+        process::exit(1);
+    };
+    match parse_inputs(input.unwrap_or_default()) {
+        Ok(values) => values
+            .into_iter()
+            .for_each(|value| computer.push_input(value)),
+        Err(e) => {
+            eprintln!("invalid input value: {e}");
+            process::exit(1);
+        }
+    }
+
+    let interrupts = catch_interrupts()
+        .inspect_err(|e| eprintln!("Ctrl-C will not break into the debugger: {e}"))
+        .ok();
+    let interrupt = interrupts.as_ref().map(Interrupts::flag);
+    loop {
+        if let Some(flag) = interrupt
+            .as_ref()
+            .filter(|flag| flag.swap(false, Ordering::Relaxed))
+        {
+            computer = break_in(computer, flag);
+        }
+        match computer.step() {
+            Ok(Some(RunState::Output(value))) => println!("{value}"),
+            Ok(None) => {}
+            Ok(Some(RunState::Halted)) => break,
+            Ok(Some(RunState::NeedsInput)) => {
+                eprintln!("the program needs more input than was given");
+                process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("execution failed: {e}");
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Hand a program stopped by Ctrl-C to the debugger, returning it to carry on running once the
+/// user quits, or exiting if they abort.
+fn break_in(computer: Computer, flag: &Arc<AtomicBool>) -> Computer {
+    eprintln!("interrupted: `q` resumes the program, `a` aborts it, `h` lists other commands");
+    let mut debugger = Debugger::new(computer);
+    debugger.break_on_interrupt(Arc::clone(flag));
+    if let Err(e) = debugger.repl(io::stdin().lock(), io::stdout()) {
+        eprintln!("could not run the debugger: {e}");
+        process::exit(1);
+    }
+    if debugger.aborted() {
+        process::exit(130);
+    }
+    debugger.into_computer()
+}
+
+/// Print the control-flow graph of a program as Graphviz DOT, e.g. for `dot -Tsvg`.
+pub fn dot_handler(path: &str) {
+    let Ok(memory) = read_program(path).parse::<Memory>() else {
+        eprintln!("{path} is not a valid Intcode program");
+        process::exit(1);
+    };
+    print!("{}", ControlFlowGraph::analyze(&memory).to_dot());
+}
+
+const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+const COUNTDOWN: &str = "
+        loop:   add  n #-1 n
+                jnz  n #loop
+                hlt
+        n:      data 3000000
+";
+
+/// A benchmark: a program and a way of running it, which returns the number of instructions
+/// executed.
+struct Workload {
+    name: &'static str,
+    program: String,
+    run: fn(&Memory, Engine) -> Result<usize, ExecutionError>,
+}
+
+fn computer(memory: &Memory, engine: Engine) -> Computer {
+    let mut computer = Computer::new(memory.clone());
+    computer.set_engine(engine);
+    computer
+}
+
+/// Run a fresh computer until it halts, returning the number of instructions it executed.
+fn run_to_halt(mut computer: Computer) -> Result<usize, ExecutionError> {
+    loop {
+        match computer.run_until_event()? {
+            RunState::Output(_) => {}
+            RunState::NeedsInput => return Err(ExecutionError::InputExhausted),
+            RunState::Halted => return Ok(computer.steps()),
+        }
+    }
+}
+
+fn workloads() -> Vec<Workload> {
+    vec![
+        Workload {
+            name: "day 2 brute force",
+            program: "1,9,10,3,2,3,11,0,99,30,40,50".to_string(),
+            run: |memory, engine| {
+                let mut steps = 0;
+                for _ in 0..100 {
+                    for noun in 0..12 {
+                        for verb in 0..12 {
+                            let mut computer = computer(memory, engine);
+                            computer.memory_mut().patch(&[(1, noun), (2, verb)]);
+                            steps += run_to_halt(computer)?;
+                        }
+                    }
+                }
+                Ok(steps)
+            },
+        },
+        Workload {
+            name: "day 9 quine",
+            program: QUINE.to_string(),
+            run: |memory, engine| {
+                (0..2000)
+                    .map(|_| run_to_halt(computer(memory, engine)))
+                    .sum()
+            },
+        },
+        Workload {
+            name: "day 9 large output",
+            program: "1102,34915192,34915192,7,4,7,99,0,104,1125899906842624,99".to_string(),
+            run: |memory, engine| {
+                (0..20_000)
+                    .map(|_| run_to_halt(computer(memory, engine)))
+                    .sum()
+            },
+        },
+        Workload {
+            name: "countdown loop",
+            program: assemble(COUNTDOWN).unwrap_or_default(),
+            run: |memory, engine| run_to_halt(computer(memory, engine)),
+        },
+    ]
+}
+
+/// Format a rate like `12.3M`.
+fn format_rate(steps: usize, elapsed: Duration) -> String {
+    let rate = steps as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    match rate {
+        r if r >= 1e9 => format!("{:.1}G", r / 1e9),
+        r if r >= 1e6 => format!("{:.1}M", r / 1e6),
+        r if r >= 1e3 => format!("{:.1}K", r / 1e3),
+        r => format!("{r:.0}"),
+    }
+}
+
+/// Run each workload on every combination of memory backend and execution engine, reporting
+/// the number of instructions executed per second.
+pub fn bench_handler() {
+    println!(
+        "{:<20} {:<8} {:<12} {:>10} {:>10} {:>8}",
+        "workload", "memory", "engine", "steps", "time", "instr/s"
+    );
+    for workload in workloads() {
+        let Ok(memory) = workload.program.parse::<Memory>() else {
+            eprintln!("{} is not a valid Intcode program", workload.name);
+            process::exit(1);
+        };
+        for backend in [Backend::Dense, Backend::Sparse, Backend::Paged] {
+            let memory = memory.clone().with_backend(backend);
+            for engine in [Engine::Interpreted, Engine::Compiled, Engine::Optimized] {
+                let start = Instant::now();
+                let steps = match (workload.run)(&memory, engine) {
+                    Ok(steps) => steps,
+                    Err(e) => {
+                        eprintln!("{} failed: {e}", workload.name);
+                        process::exit(1);
+                    }
+                };
+                let elapsed = start.elapsed();
+                println!(
+                    "{:<20} {:<8} {:<12} {:>10} {:>10} {:>8}",
+                    workload.name,
+                    format!("{backend:?}"),
+                    format!("{engine:?}"),
+                    steps,
+                    format!("{:.1?}", elapsed),
+                    format_rate(steps, elapsed)
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_inputs() {
+        assert_eq!(parse_inputs("1, 2,-3"), Ok(vec![1, 2, -3]));
+        assert_eq!(parse_inputs(""), Ok(vec![]));
+        assert!(parse_inputs("1,x").is_err());
+    }
+
+    #[test]
+    fn test_workloads() {
+        for workload in workloads() {
+            let memory = workload.program.parse().unwrap();
+            let steps = (workload.run)(&memory, Engine::Interpreted).unwrap();
+            assert!(steps > 0, "{}", workload.name);
+            assert_eq!((workload.run)(&memory, Engine::Optimized), Ok(steps));
+        }
+    }
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(format_rate(1_500_000, Duration::from_secs(1)), "1.5M");
+        assert_eq!(format_rate(500, Duration::from_millis(500)), "1.0K");
+        assert_eq!(format_rate(12, Duration::from_secs(1)), "12");
+    }
+}
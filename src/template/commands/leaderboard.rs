@@ -0,0 +1,181 @@
+use std::time::Duration;
+use std::{fs, process};
+
+use crate::template::json::{self, Value};
+use crate::template::session::Session;
+use crate::template::{config, dates, paint, ANSI_BOLD, ANSI_DIM, ANSI_YELLOW};
+
+/// The site asks that private leaderboards are fetched no more often than this.
+const CACHE_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+/// Where a leaderboard is cached, apart for each year as each year has its own.
+fn get_cache_path(year: u16, id: u32) -> String {
+    format!("data/leaderboard-{year}-{id}.json")
+}
+
+/// One member of a private leaderboard.
+#[derive(Debug, PartialEq)]
+struct Member {
+    name: String,
+    local_score: u64,
+    stars: u64,
+    /// When the member last earned a star, as a Unix timestamp; zero if they have none.
+    last_star_ts: u64,
+}
+
+/// The members of a leaderboard, from the JSON the site serves for it, ranked by local score and
+/// then by who got their last star first. Members without a name show as the site shows them.
+fn parse_members(leaderboard: &Value) -> Option<Vec<Member>> {
+    let mut members = leaderboard
+        .get("members")?
+        .as_object()?
+        .values()
+        .map(|member| {
+            let number = |key| member.get(key).and_then(Value::as_u64);
+            let name = member.get("name").and_then(Value::as_str).map_or_else(
+                || format!("(anonymous user #{})", number("id").unwrap_or_default()),
+                String::from,
+            );
+            Some(Member {
+                name,
+                local_score: number("local_score")?,
+                stars: number("stars")?,
+                last_star_ts: number("last_star_ts").unwrap_or_default(),
+            })
+        })
+        .collect::<Option<Vec<Member>>>()?;
+
+    members.sort_by(|a, b| {
+        b.local_score
+            .cmp(&a.local_score)
+            .then(a.last_star_ts.cmp(&b.last_star_ts))
+    });
+    Some(members)
+}
+
+/// The leaderboard, from the copy saved by an earlier run if it is recent enough, or otherwise
+/// fetched again and saved. Returns how old the copy is alongside it.
+fn load(id: u32) -> Result<(Value, Duration), String> {
+    let year = config::year();
+    let path = get_cache_path(year, id);
+    let cached_age = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .filter(|age| *age < CACHE_LIFETIME);
+    if let Some(age) = cached_age {
+        if let Some(leaderboard) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| json::parse(&json))
+        {
+            return Ok((leaderboard, age));
+        }
+    }
+
+    let json = Session::load()
+        .and_then(|session| session.fetch(&format!("/{year}/leaderboard/private/view/{id}.json")))
+        .map_err(|e| e.to_string())?;
+    // the site answers with its login page, not JSON, for a leaderboard the user cannot see.
+    let leaderboard = json::parse(&json)
+        .ok_or("it is not JSON; check the leaderboard's id and that you are a member of it.")?;
+
+    if fs::write(&path, &json).is_err() {
+        eprintln!("Failed to save the leaderboard to \"{path}\".");
+    }
+    Ok((leaderboard, Duration::ZERO))
+}
+
+/// Print the members of a private leaderboard with their local scores, stars and when they got
+/// their last star. The leaderboard is fetched at most once every `CACHE_LIFETIME`, as the site
+/// asks, and saved in `data/` in between.
+pub fn handler(id: u32) {
+    let (leaderboard, age) = match load(id) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("failed to fetch the leaderboard: {e}");
+            process::exit(1);
+        }
+    };
+    let Some(members) = parse_members(&leaderboard) else {
+        eprintln!("The leaderboard is not in the expected format.");
+        process::exit(1);
+    };
+
+    println!(
+        "{}",
+        paint(
+            ANSI_BOLD,
+            format!(
+                "{:>3}  {:>5}  {:>5}  {:<16}  Name",
+                "#", "Score", "Stars", "Last star"
+            )
+        )
+    );
+    for (ix, member) in members.iter().enumerate() {
+        let last_star = dates::est(member.last_star_ts)
+            .filter(|_| member.last_star_ts > 0)
+            .map_or_else(|| "-".to_string(), |date| date.to_string());
+        println!(
+            "{:>3}  {:>5}  {}  {}  {}",
+            ix + 1,
+            member.local_score,
+            paint(ANSI_YELLOW, format!("{:>4}★", member.stars)),
+            paint(ANSI_DIM, format!("{last_star:<16}")),
+            member.name
+        );
+    }
+
+    println!("---");
+    if age.is_zero() {
+        println!("🎄 Fetched just now; times are in EST.");
+    } else {
+        println!(
+            "🎄 Fetched {} minutes ago; times are in EST. It is refreshed at most every {} minutes.",
+            age.as_secs() / 60,
+            CACHE_LIFETIME.as_secs() / 60
+        );
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_cache_path() {
+        assert_eq!(get_cache_path(2019, 123), "data/leaderboard-2019-123.json");
+        assert_ne!(get_cache_path(2019, 123), get_cache_path(2020, 123));
+    }
+
+    #[test]
+    fn test_parse_members() {
+        let leaderboard = json::parse(
+            r#"{"owner_id": 1, "event": "2019", "members": {
+                "1": {"id": 1, "name": "Ada", "local_score": 40, "stars": 6,
+                      "last_star_ts": 1575500000, "completion_day_level": {}},
+                "2": {"id": 2, "name": null, "local_score": 52, "stars": 8,
+                      "last_star_ts": 1575600000, "completion_day_level": {}},
+                "3": {"id": 3, "name": "Grace", "local_score": 40, "stars": 6,
+                      "last_star_ts": 1575400000, "completion_day_level": {}},
+                "4": {"id": 4, "name": "New", "local_score": 0, "stars": 0,
+                      "last_star_ts": 0, "completion_day_level": {}}
+            }}"#,
+        )
+        .unwrap();
+
+        let members = parse_members(&leaderboard).unwrap();
+        let names: Vec<&str> = members.iter().map(|member| member.name.as_str()).collect();
+        assert_eq!(names, ["(anonymous user #2)", "Grace", "Ada", "New"]);
+        assert_eq!(
+            members[0],
+            Member {
+                name: "(anonymous user #2)".into(),
+                local_score: 52,
+                stars: 8,
+                last_star_ts: 1_575_600_000,
+            }
+        );
+
+        assert_eq!(parse_members(&json::parse("{}").unwrap()), None);
+    }
+}
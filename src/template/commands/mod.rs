@@ -1,5 +1,18 @@
 pub mod all;
+pub mod benchmarks;
+pub mod calendar;
+pub mod completions;
 pub mod download;
+pub mod flamegraph;
+pub mod intcode;
+pub mod leaderboard;
 pub mod read;
 pub mod scaffold;
 pub mod solve;
+pub mod submit;
+pub mod test;
+pub mod time;
+pub mod token;
+pub mod verify;
+pub mod wait;
+pub mod watch;
@@ -1,15 +1,47 @@
 use std::process;
 
-use crate::template::aoc_cli;
+use crate::template::{aoc_cli, color_enabled, markdown, strip_ansi};
 
-pub fn handler(day: u8) {
+/// Show the puzzle description, from the copy saved by `download` or an earlier `read` unless
+/// `refresh` asks for it to be fetched again, as is needed to see part two once it unlocks.
+pub fn handler(day: u8, refresh: bool) {
+    let cached = aoc_cli::cached_puzzle(day).filter(|_| !refresh);
+    let fetched = cached.is_none();
+    let Some(puzzle) = cached.or_else(|| fetch(day)) else {
+        eprintln!("aoc-cli did not save the puzzle.");
+        process::exit(1);
+    };
+
+    let rendered = markdown::render(&puzzle, markdown::terminal_width());
+    if color_enabled() {
+        print!("{rendered}");
+    } else {
+        print!("{}", strip_ansi(&rendered));
+    }
+    println!("---");
+    if fetched {
+        println!(
+            "🎄 Successfully wrote puzzle to \"{}\".",
+            aoc_cli::get_puzzle_path(day)
+        );
+    } else {
+        println!(
+            "🎄 Read from \"{}\". Use --refresh to fetch it again.",
+            aoc_cli::get_puzzle_path(day)
+        );
+    }
+}
+
+/// Fetch and save the puzzle, returning the saved copy.
+fn fetch(day: u8) -> Option<String> {
     if aoc_cli::check().is_err() {
         eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
         process::exit(1);
     }
 
-    if let Err(e) = aoc_cli::read(day) {
+    if let Err(e) = aoc_cli::download_puzzle(day) {
         eprintln!("failed to call aoc-cli: {e}");
         process::exit(1);
     };
+    aoc_cli::cached_puzzle(day)
 }
@@ -1,48 +1,42 @@
+use crate::template::commands::download;
+use crate::template::config;
 use std::{
-    fs::{File, OpenOptions},
+    fs::{self, File, OpenOptions},
     io::Write,
     process,
 };
 
-const MODULE_TEMPLATE: &str = r#"#[must_use]
-pub fn part_one(input: &str) -> Option<u32> {
-    None
-}
-
-#[must_use]
-pub fn part_two(input: &str) -> Option<u32> {
-    None
-}
+/// Where `scaffold` looks for the template of a day's module, which can be edited to change
+/// what new days start with.
+const MODULE_TEMPLATE_PATH: &str = "templates/day.rs.tpl";
 
-advent_of_code::main!(DAY);
+/// The template as it was at build time, used if the file has since been removed.
+const DEFAULT_MODULE_TEMPLATE: &str = include_str!("../../../templates/day.rs.tpl");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_part_one() {
-        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
-    }
-
-    #[test]
-    fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
-    }
+/// Fill in the placeholders of a module template: `{{day}}` with the day number,
+/// `{{day_padded}}` with the day number padded to two digits, and `{{year}}` with the year.
+fn render_template(template: &str, day: u8, year: u16) -> String {
+    template
+        .replace("{{year}}", &year.to_string())
+        .replace("{{day_padded}}", &format!("{day:02}"))
+        .replace("{{day}}", &day.to_string())
 }
-"#;
 
 fn safe_create_file(path: &str) -> Result<File, std::io::Error> {
     OpenOptions::new().write(true).create_new(true).open(path)
 }
 
 fn create_file(path: &str) -> Result<File, std::io::Error> {
-    OpenOptions::new().write(true).create(true).open(path)
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
 }
 
-pub fn handler(day: u8) {
+/// Create the module, input and example files for a day, then with `download` fetch its input,
+/// puzzle and examples too.
+pub fn handler(day: u8, download: bool) {
     let day_padded = format!("{day:02}");
 
     let input_path = format!("data/inputs/{day_padded}.txt");
@@ -57,7 +51,10 @@ pub fn handler(day: u8) {
         }
     };
 
-    match file.write_all(MODULE_TEMPLATE.replace("DAY", &day.to_string()).as_bytes()) {
+    let template = fs::read_to_string(MODULE_TEMPLATE_PATH)
+        .unwrap_or_else(|_| DEFAULT_MODULE_TEMPLATE.to_string());
+
+    match file.write_all(render_template(&template, day, config::year()).as_bytes()) {
         Ok(_) => {
             println!("Created module file \"{}\"", &module_path);
         }
@@ -87,9 +84,41 @@ pub fn handler(day: u8) {
         }
     }
 
+    if download {
+        println!("---");
+        download::handler(day, false);
+    }
+
     println!("---");
     println!(
         "🎄 Type `cargo solve {}` to run your solution.",
         &day_padded
     );
 }
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{render_template, DEFAULT_MODULE_TEMPLATE};
+
+    #[test]
+    fn test_render_template() {
+        assert_eq!(
+            render_template(
+                "main!({{day}}); // {{year}}: data/inputs/{{day_padded}}.txt",
+                7,
+                2019
+            ),
+            "main!(7); // 2019: data/inputs/07.txt"
+        );
+    }
+
+    #[test]
+    fn test_default_template() {
+        let module = render_template(DEFAULT_MODULE_TEMPLATE, 12, 2019);
+        assert!(module.starts_with(
+            "//! Advent of Code 2019, day 12: https://adventofcode.com/2019/day/12\n"
+        ));
+        assert!(module.contains("advent_of_code::main!(12);"));
+        assert!(!module.contains("{{"));
+    }
+}
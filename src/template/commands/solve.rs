@@ -1,6 +1,72 @@
 use std::process::{Command, Stdio};
 
-pub fn handler(day: u8, release: bool, time: bool, submit_part: Option<u8>) {
+use crate::template::{inherit_color, profile};
+
+/// Where a solution reads its puzzle input from.
+pub enum Input {
+    /// The downloaded input, `data/inputs/XX.txt`.
+    Puzzle,
+    /// Another file, given with `--input <path>`.
+    File(String),
+    /// An example from `data/examples/`, given with `--example [n]`: `XX.txt`, or `XX-n.txt` if
+    /// the day has several examples.
+    Example(Option<u8>),
+    /// Standard input, given with `--stdin` or `-`.
+    Stdin,
+}
+
+/// The arguments for a day's solution binary: which part to submit or run, where to read the
+/// input from, and the flags forwarded to it.
+pub(crate) fn solution_args(
+    submit_part: Option<u8>,
+    part: Option<u8>,
+    input: &Input,
+    time: bool,
+    forwarded_args: &[String],
+) -> Vec<String> {
+    let mut args = vec![];
+
+    if let Some(submit_part) = submit_part {
+        args.push("--submit".to_string());
+        args.push(submit_part.to_string());
+    }
+
+    if let Some(part) = part {
+        args.push("--part".to_string());
+        args.push(part.to_string());
+    }
+
+    match input {
+        Input::Puzzle => {}
+        Input::File(path) => {
+            args.push("--input".to_string());
+            args.push(path.clone());
+        }
+        Input::Example(number) => {
+            args.push("--example".to_string());
+            args.extend(number.map(|number| number.to_string()));
+        }
+        Input::Stdin => args.push("--stdin".to_string()),
+    }
+
+    if time {
+        args.push("--time".to_string());
+    }
+
+    args.extend_from_slice(forwarded_args);
+    profile::forward(&mut args);
+    args
+}
+
+pub fn handler(
+    day: u8,
+    release: bool,
+    time: bool,
+    submit_part: Option<u8>,
+    part: Option<u8>,
+    input: &Input,
+    forwarded_args: &[String],
+) {
     let day_padded = format!("{day:02}");
 
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day_padded];
@@ -9,18 +75,21 @@ pub fn handler(day: u8, release: bool, time: bool, submit_part: Option<u8>) {
         cmd_args.push("--release".to_string());
     }
 
-    cmd_args.push("--".to_string());
-
-    if let Some(submit_part) = submit_part {
-        cmd_args.push("--submit".to_string());
-        cmd_args.push(submit_part.to_string());
+    // dhat only sees allocations when it is the global allocator, which takes a feature.
+    if forwarded_args.iter().any(|x| x == "--heap-profile") {
+        cmd_args.extend(["--features".to_string(), "dhat-heap".to_string()]);
     }
 
-    if time {
-        cmd_args.push("--time".to_string());
-    }
+    cmd_args.push("--".to_string());
+    cmd_args.extend(solution_args(
+        submit_part,
+        part,
+        input,
+        time,
+        forwarded_args,
+    ));
 
-    let mut cmd = Command::new("cargo")
+    let mut cmd = inherit_color(&mut Command::new("cargo"))
         .args(&cmd_args)
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
@@ -0,0 +1,132 @@
+use crate::template::answers::{self, AnswerLog, ExpectedAnswers, Refusal, Submission, Verdict};
+use crate::template::{aoc_cli, profile, progress};
+use std::process;
+use std::time::Duration;
+
+/// Submit an answer through aoc-cli, which reports whether it was accepted. The verdict is kept
+/// in the answer log, which is checked first: an answer is not sent for a part which is already
+/// solved, nor if it is known to be wrong: either submitted before, or outside the bounds set by
+/// the answers which were too high or too low.
+///
+/// An accepted answer is also kept in `data/expected.tsv`, for `verify`.
+///
+/// A submission made during the cooldown after a wrong answer is refused by the server; with
+/// `wait`, the cooldown is counted down and the answer sent again.
+pub fn handler(day: u8, part: u8, answer: &str, wait: bool) {
+    if let Some(name) = profile::current() {
+        eprintln!("Answers are only submitted for the main account, not profile {name}.");
+        process::exit(1);
+    }
+
+    let mut expected = ExpectedAnswers::load();
+    let mut answers = AnswerLog::load();
+    let checked = match expected.get(day, part) {
+        Some(accepted) => Err(Refusal::Solved(Some(accepted.to_string()))),
+        None => answers.check(day, part, answer),
+    };
+    if let Err(refusal) = checked {
+        eprintln!("Not submitting day {day} part {part}: {refusal}.");
+        process::exit(1);
+    }
+
+    if aoc_cli::check().is_err() {
+        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
+        process::exit(1);
+    }
+
+    let response = loop {
+        println!("Submitting result via aoc-cli...");
+        let output = match aoc_cli::submit(day, part, answer) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("failed to call aoc-cli: {e}");
+                process::exit(1);
+            }
+        };
+
+        let response = String::from_utf8_lossy(&output.stdout).into_owned();
+        print!("{response}");
+
+        match answers::cooldown(&response) {
+            None => break response,
+            Some(remaining) if wait => {
+                // a second's margin, as the server rounds the time left down.
+                progress::countdown("Cooldown, retrying in", remaining + Duration::from_secs(1));
+            }
+            Some(remaining) => {
+                eprintln!(
+                    "The answer was not checked: there is {} left of the cooldown. \
+                    Submit again then, or pass --wait to retry automatically.",
+                    progress::format_remaining(remaining.as_secs())
+                );
+                process::exit(1);
+            }
+        }
+    };
+
+    let Some(verdict) = Verdict::from_response(&response) else {
+        return;
+    };
+    let submission = Submission {
+        day,
+        part,
+        answer: answer.to_string(),
+        verdict,
+    };
+    if answers.record(submission).is_err() {
+        eprintln!("Failed to record the answer.");
+    }
+
+    match verdict {
+        Verdict::Correct => keep_answer(&mut expected, day, part, answer),
+        Verdict::AlreadySolved => {
+            if let Some(accepted) = solved_answer(&answers, day, part) {
+                keep_answer(&mut expected, day, part, &accepted);
+            }
+        }
+        _ => {}
+    }
+
+    let bounds = answers.bounds(day, part);
+    if matches!(verdict, Verdict::TooHigh | Verdict::TooLow) && bounds.is_known() {
+        println!("The answer to day {day} part {part} is {bounds}.");
+    }
+
+    if part == 1 && verdict == Verdict::Correct {
+        println!("Run \"cargo read {day} --refresh\" to see part two.");
+    }
+}
+
+/// The accepted answer to a part which is already solved: from the answer log, or otherwise from
+/// the saved puzzle description, which shows the answer to each part solved.
+fn solved_answer(answers: &AnswerLog, day: u8, part: u8) -> Option<String> {
+    if let Some(accepted) = answers.accepted(day, part) {
+        return Some(accepted.to_string());
+    }
+    let puzzle = aoc_cli::cached_puzzle(day)?;
+    answers::parse_puzzle_answers(&puzzle)
+        .into_iter()
+        .nth(usize::from(part).checked_sub(1)?)
+}
+
+/// Keep the accepted answer to a part in `data/expected.tsv`, saying so if it was not there.
+fn keep_answer(expected: &mut ExpectedAnswers, day: u8, part: u8, answer: &str) {
+    match expected.record(day, part, answer) {
+        Ok(true) => println!("Kept the answer to day {day} part {part} for \"cargo verify\"."),
+        Ok(false) => {}
+        Err(e) => eprintln!("Failed to keep the answer to day {day} part {part}: {e}"),
+    }
+}
+
+/// Keep the answer to a part which is already solved in `data/expected.tsv`, if it is not there
+/// yet. This is called whenever a part is run on the puzzle input, so that the answers to parts
+/// solved before they were kept are picked up too.
+pub fn keep_solved_answer(day: u8, part: u8) {
+    let mut expected = ExpectedAnswers::load();
+    if expected.get(day, part).is_some() {
+        return;
+    }
+    if let Some(accepted) = solved_answer(&AnswerLog::load(), day, part) {
+        keep_answer(&mut expected, day, part, &accepted);
+    }
+}
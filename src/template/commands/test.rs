@@ -0,0 +1,136 @@
+use std::path::Path;
+use std::process::{self, Command};
+
+use crate::template::commands::all::get_path_for_bin;
+use crate::template::{paint, ANSI_BOLD, ANSI_DIM, ANSI_GREEN, ANSI_RED};
+
+/// The outcome of running one day's unit tests.
+pub struct TestReport {
+    pub passed: usize,
+    /// The names of the tests which failed.
+    pub failures: Vec<String>,
+    /// Everything the tests printed, which explains any failures.
+    pub output: String,
+}
+
+impl TestReport {
+    #[must_use]
+    pub fn succeeded(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Run the unit tests of a day's solution binary.
+///
+/// # Errors
+///
+/// Will return `io::Error` if cargo cannot be run.
+pub fn run_tests(day: u8) -> std::io::Result<TestReport> {
+    let output = Command::new("cargo")
+        .args(["test", "--bin", &format!("{day:02}"), "--color", "never"])
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+
+    let mut passed = 0;
+    let mut failures = vec![];
+    for line in stdout.lines() {
+        if let Some(test) = line.strip_prefix("test ") {
+            match test.rsplit_once(" ... ") {
+                Some((_, "ok")) => passed += 1,
+                Some((name, "FAILED")) => failures.push(name.to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    // a test binary which fails to build has no results to show for it.
+    if !output.status.success() && failures.is_empty() {
+        failures.push("(build)".into());
+    }
+
+    Ok(TestReport {
+        passed,
+        failures,
+        output: stdout + &String::from_utf8_lossy(&output.stderr),
+    })
+}
+
+/// Print how a day's tests fared, followed by their output if any failed.
+pub fn print_report(day: u8, report: &TestReport) {
+    if report.succeeded() {
+        println!(
+            "{}: {} {} passed",
+            paint(ANSI_BOLD, format!("Day {day}")),
+            paint(ANSI_GREEN, "✔"),
+            report.passed
+        );
+    } else {
+        println!(
+            "{}: {} {} passed, {} failed: {}",
+            paint(ANSI_BOLD, format!("Day {day}")),
+            paint(ANSI_RED, "✖"),
+            report.passed,
+            report.failures.len(),
+            report.failures.join(", ")
+        );
+        println!("{}", paint(ANSI_DIM, report.output.trim_end()));
+    }
+}
+
+/// Run a day's tests and print how they fared, returning whether they all passed.
+#[must_use]
+pub fn tests_pass(day: u8) -> bool {
+    match run_tests(day) {
+        Ok(report) => {
+            print_report(day, &report);
+            report.succeeded()
+        }
+        Err(e) => {
+            eprintln!("failed to run cargo test: {e}");
+            false
+        }
+    }
+}
+
+/// Run the tests of one day, or of every day which has been scaffolded, reporting how each day
+/// fared and the output of any failures. Exits with an error if any tests failed.
+pub fn handler(day: Option<u8>) {
+    let days: Vec<u8> = match day {
+        Some(day) if !Path::new(&get_path_for_bin(usize::from(day))).exists() => {
+            eprintln!("Day {day} has not been scaffolded.");
+            process::exit(1);
+        }
+        Some(day) => vec![day],
+        None => (1..=25)
+            .filter(|day| Path::new(&get_path_for_bin(usize::from(*day))).exists())
+            .collect(),
+    };
+
+    let mut failed_days = vec![];
+    for day in &days {
+        let report = match run_tests(*day) {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!("failed to run cargo test: {e}");
+                process::exit(1);
+            }
+        };
+
+        print_report(*day, &report);
+        if !report.succeeded() {
+            failed_days.push(day);
+        }
+    }
+
+    if days.len() > 1 {
+        println!(
+            "\n{} {} of {} days passed",
+            paint(ANSI_BOLD, "Total:"),
+            days.len() - failed_days.len(),
+            days.len()
+        );
+    }
+    if !failed_days.is_empty() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use crate::template::commands::all::{child_commands, get_path_for_bin};
+use crate::template::timings::TimingRecord;
+use crate::template::{paint, profile, ANSI_BOLD};
+
+/// Bench one day, or every day which has been scaffolded, in release mode, reporting the median
+/// and spread of each part's run time (see `--median` in the runner). The medians are compared
+/// with, and then replace, those in `data/timings.json`, unless the inputs of a profile other
+/// than the main one were timed.
+pub fn handler(day: Option<u8>) {
+    let days: Vec<usize> = match day {
+        Some(day) => vec![usize::from(day)],
+        None => (1..=25)
+            .filter(|day| Path::new(&get_path_for_bin(*day)).exists())
+            .collect(),
+    };
+    let profile = profile::current();
+    let mut record = profile.is_none().then(TimingRecord::load);
+
+    // mirror the `--profile` flag to child invocations.
+    let mut flags = vec!["--median"];
+    if let Some(profile) = &profile {
+        flags.extend(["--profile", profile]);
+    }
+
+    for (ix, day) in days.into_iter().enumerate() {
+        if ix > 0 {
+            println!();
+        }
+        println!("{}", paint(ANSI_BOLD, format!("Day {day}")));
+        println!("------");
+
+        let output = child_commands::run_solution(day, true, &flags)
+            .unwrap_or_else(|e| panic!("panic during executing day {day}: {e:?}"));
+
+        if output.is_empty() {
+            println!("Not solved.");
+        } else if let Some(record) = &mut record {
+            record.update(&child_commands::parse_exec_time(&output, day));
+        }
+    }
+
+    match record {
+        Some(record) => {
+            if record.save().is_err() {
+                eprintln!("Failed to save timings.");
+            }
+        }
+        None => println!("\nTimings for a profile's inputs are not recorded."),
+    }
+}
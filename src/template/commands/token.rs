@@ -0,0 +1,72 @@
+use std::process;
+use std::time::Duration;
+
+use crate::template::session::{self, Session, Source, SESSION_LIFETIME};
+
+fn load() -> Session {
+    match Session::load() {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+}
+
+fn days(duration: Duration) -> u64 {
+    duration.as_secs() / (24 * 60 * 60)
+}
+
+/// Print when the session file was saved and when the session should expire, as the site does
+/// not say.
+fn print_expiry(session: &Session) {
+    let Some(age) = session.age() else {
+        return;
+    };
+
+    println!("Saved {} days ago.", days(age));
+    match SESSION_LIFETIME.checked_sub(age) {
+        Some(left) => println!(
+            "Sessions last about a month, so it should expire in about {} days.",
+            days(left)
+        ),
+        None => println!("Sessions last about a month, so it has probably expired."),
+    }
+}
+
+pub fn set_handler(token: &str) {
+    match session::save(token) {
+        Ok(path) => println!("🎄 Saved session token to \"{}\".", path.display()),
+        Err(e) => {
+            eprintln!("failed to save session token: {e}");
+            process::exit(1);
+        }
+    }
+
+    if matches!(load().source(), Source::Environment(_)) {
+        println!("Note that the token in the environment will be used instead while it is set.");
+    }
+}
+
+pub fn show_handler() {
+    let session = load();
+    println!(
+        "Session token {} from {}.",
+        session.masked(),
+        session.source()
+    );
+    print_expiry(&session);
+}
+
+pub fn check_handler() {
+    let session = load();
+    match session.validate() {
+        Ok(()) => println!("🎄 Session token {} is valid.", session.masked()),
+        Err(e) => {
+            eprintln!("Session token {} could not be used: {e}", session.masked());
+            print_expiry(&session);
+            process::exit(1);
+        }
+    }
+    print_expiry(&session);
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::process;
+
+use crate::template::answers::ExpectedAnswers;
+use crate::template::commands::all::{child_commands, get_path_for_bin};
+use crate::template::json;
+use crate::template::{paint, profile, ANSI_BOLD, ANSI_DIM, ANSI_GREEN, ANSI_RED};
+
+/// A part whose answer differs from the expected one, or which gave no answer at all.
+#[derive(Debug, PartialEq)]
+struct Mismatch {
+    part: u8,
+    expected: String,
+    actual: Option<String>,
+}
+
+/// The answer given by each part, from the lines of JSON a day prints for `--json`. A part which
+/// failed has no answer.
+fn parse_answers(output: &[String]) -> BTreeMap<u8, Option<String>> {
+    output
+        .iter()
+        .filter_map(|line| json::parse(line))
+        .filter_map(|result| {
+            let part = u8::try_from(result.get("part")?.as_u64()?).ok()?;
+            let answer = result.get("answer")?.as_str().map(String::from);
+            Some((part, answer))
+        })
+        .collect()
+}
+
+/// The parts of `day` whose answers do not match those expected of them.
+fn compare(
+    day: u8,
+    expected: &ExpectedAnswers,
+    answers: &BTreeMap<u8, Option<String>>,
+) -> Vec<Mismatch> {
+    expected
+        .parts(day)
+        .filter_map(|(part, expected)| {
+            let actual = answers.get(&part).cloned().flatten();
+            (actual.as_deref() != Some(expected)).then(|| Mismatch {
+                part,
+                expected: expected.to_string(),
+                actual,
+            })
+        })
+        .collect()
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.actual {
+            Some(actual) => write!(
+                f,
+                "Part {} gave {actual:?}, but {:?} was expected",
+                self.part, self.expected
+            ),
+            None => write!(
+                f,
+                "Part {} gave no answer, but {:?} was expected",
+                self.part, self.expected
+            ),
+        }
+    }
+}
+
+/// Run one day, or every day with expected answers, in release mode and check that each part
+/// still gives the answer recorded for it in `data/expected.tsv`. Exits with an error if any
+/// answer differs, so that a change to shared code can be checked against every day solved.
+pub fn handler(day: Option<u8>) {
+    if profile::current().is_some() {
+        eprintln!("Expected answers are only kept for the main account's puzzle inputs.");
+        process::exit(1);
+    }
+
+    let expected = ExpectedAnswers::load();
+
+    let days: Vec<u8> = match day {
+        Some(day) if !Path::new(&get_path_for_bin(usize::from(day))).exists() => {
+            eprintln!("Day {day} has not been scaffolded.");
+            process::exit(1);
+        }
+        Some(day) => vec![day],
+        None => (1..=25)
+            .filter(|day| expected.parts(*day).next().is_some())
+            .filter(|day| Path::new(&get_path_for_bin(usize::from(*day))).exists())
+            .collect(),
+    };
+    if days.is_empty() {
+        println!("No days have expected answers in data/expected.tsv.");
+        return;
+    }
+
+    let mut failed_days = vec![];
+    let bins: Vec<usize> = days.iter().copied().map(usize::from).collect();
+    child_commands::run_solutions_parallel(&bins, true, &["--json"], |day, output, errors| {
+        let day = u8::try_from(day).unwrap_or_default();
+        let checked = expected.parts(day).count();
+        let mismatches = compare(day, &expected, &parse_answers(&output));
+        let header = paint(ANSI_BOLD, format!("Day {day}"));
+
+        if checked == 0 {
+            println!("{header}: {}", paint(ANSI_DIM, "no expected answers"));
+        } else if mismatches.is_empty() {
+            println!(
+                "{header}: {} {checked} of {checked} answers match",
+                paint(ANSI_GREEN, "✔")
+            );
+        } else {
+            println!(
+                "{header}: {} {} of {checked} answers match",
+                paint(ANSI_RED, "✖"),
+                checked - mismatches.len()
+            );
+            mismatches
+                .iter()
+                .for_each(|mismatch| println!("  {mismatch}"));
+            if !errors.trim().is_empty() {
+                println!("{}", paint(ANSI_DIM, errors.trim_end()));
+            }
+            failed_days.push(day);
+        }
+    })
+    .expect("panic during executing solutions");
+
+    if days.len() > 1 {
+        println!(
+            "\n{} {} of {} days verified",
+            paint(ANSI_BOLD, "Total:"),
+            days.len() - failed_days.len(),
+            days.len()
+        );
+    }
+    if !failed_days.is_empty() {
+        process::exit(1);
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let expected = ExpectedAnswers::from_tsv("2\t1\t3101844\n2\t2\t8478\n3\t1\t5\n");
+        let output = vec![
+            "{\"day\":2,\"part\":1,\"answer\":\"3101844\",\"duration_nanos\":1,\"success\":true}"
+                .to_string(),
+            "{\"day\":2,\"part\":2,\"answer\":null,\"duration_nanos\":1,\"success\":false}"
+                .to_string(),
+        ];
+        let mismatches = compare(2, &expected, &parse_answers(&output));
+        assert_eq!(
+            mismatches,
+            vec![Mismatch {
+                part: 2,
+                expected: "8478".into(),
+                actual: None,
+            }]
+        );
+        assert_eq!(
+            mismatches[0].to_string(),
+            "Part 2 gave no answer, but \"8478\" was expected"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::template::commands::all::get_path_for_bin;
+use crate::template::commands::{download, scaffold};
+use crate::template::session::Session;
+use crate::template::{aoc_cli, config, dates, progress};
+
+/// How long after the unlock to make the first request, so as not to arrive a moment early.
+const UNLOCK_MARGIN: Duration = Duration::from_secs(2);
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs())
+}
+
+/// The day to wait for and when it unlocks: the given day of the event's year, or otherwise the
+/// puzzle which unlocks at the next midnight EST.
+fn next_unlock(day: Option<u8>, year: u16) -> Result<(u8, u64), String> {
+    match day {
+        Some(day @ 1..=25) => return Ok((day, dates::unlock_time(u64::from(year), day))),
+        Some(day) => return Err(format!("there is no puzzle for day {day}.")),
+        None => {}
+    }
+
+    let unlock = dates::next_midnight(now());
+    match dates::est(unlock) {
+        Some(date) if date.month == 12 && date.day <= 25 && date.year == u64::from(year) => {
+            Ok((date.day, unlock))
+        }
+        Some(date) if date.month == 12 && date.day <= 25 => Err(format!(
+            "the next puzzle is for {}, but this workspace is for {year}.",
+            date.year
+        )),
+        _ => Err("no puzzle unlocks at the next midnight EST. Give a day to wait for.".into()),
+    }
+}
+
+/// Count down to the unlock of a day's puzzle, the next one if no day is given, then scaffold
+/// the day and download its input and puzzle straight away. A day which has already unlocked
+/// is fetched at once.
+pub fn handler(day: Option<u8>) {
+    let year = config::year();
+    let (day, unlock) = match next_unlock(day, year) {
+        Ok(next) => next,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+
+    // find out about anything missing now, rather than at midnight.
+    if aoc_cli::check().is_err() {
+        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
+        process::exit(1);
+    }
+    if let Err(e) = Session::load() {
+        eprintln!("{e}");
+        process::exit(1);
+    }
+
+    let start = unlock + UNLOCK_MARGIN.as_secs();
+    if now() < start {
+        progress::countdown(
+            &format!("Day {day} unlocks in"),
+            Duration::from_secs(start.saturating_sub(now())),
+        );
+        // the countdown can run a little short over a long wait.
+        while now() < start {
+            thread::sleep(Duration::from_secs(start.saturating_sub(now())));
+        }
+        println!("🎄 Day {day} has unlocked.");
+    }
+
+    if Path::new(&get_path_for_bin(usize::from(day))).exists() {
+        download::handler(day, false);
+    } else {
+        scaffold::handler(day, true);
+    }
+}
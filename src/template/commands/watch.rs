@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use std::{fs, thread};
+
+use crate::template::commands::test;
+use crate::template::{paint, ANSI_BOLD, ANSI_ITALIC};
+
+/// How often the sources are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Collect the Rust files under `dir`, leaving out `src/bin` but for the day being watched.
+fn collect_sources(dir: &Path, day_bin: &Path, sources: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if path.ends_with("bin") {
+                sources.push(day_bin.to_path_buf());
+            } else {
+                collect_sources(&path, day_bin, sources);
+            }
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            sources.push(path);
+        }
+    }
+}
+
+/// The day's solution and the shared modules it builds on, with when each last changed.
+fn snapshot(day: u8) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let day_bin = PathBuf::from(format!("src/bin/{day:02}.rs"));
+    let mut sources = vec![];
+    collect_sources(Path::new("src"), &day_bin, &mut sources);
+    sources.sort();
+
+    sources
+        .into_iter()
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+/// Run `solve` for a day, and again every time its solution or any shared module changes. With
+/// `test_first`, the day's tests run first each time, and a failure skips solving.
+pub fn handler(day: u8, test_first: bool, solve: impl Fn()) {
+    let mut sources = snapshot(day);
+
+    loop {
+        if !test_first || test::tests_pass(day) {
+            solve();
+        }
+
+        println!("{}", paint(ANSI_ITALIC, "Watching for changes..."));
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let latest = snapshot(day);
+            if latest != sources {
+                let changed = latest
+                    .iter()
+                    .find(|source| !sources.contains(source))
+                    .map_or_else(
+                        || "a source file".into(),
+                        |(path, _)| path.display().to_string(),
+                    );
+                sources = latest;
+                println!("\n{}", paint(ANSI_BOLD, format!("{changed} changed")));
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+/// Module that reads which year's puzzles this workspace is for, so that the same template can be
+/// used for any year. The year comes from the `AOC_YEAR` environment variable if it is set, or
+/// otherwise from `year = ...` in `aoc.toml`; failing both, it is that of the latest event.
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs};
+
+use crate::template::dates;
+
+static YEAR_VAR: &str = "AOC_YEAR";
+static CONFIG_PATH: &str = "aoc.toml";
+
+/// The value of `key` in a simple TOML document of `key = value` lines, with any quotes removed.
+/// Tables, comments and blank lines are skipped.
+fn toml_value(toml: &str, key: &str) -> Option<String> {
+    toml.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter_map(|line| line.split_once('='))
+        .find(|(name, _)| name.trim() == key)
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+}
+
+/// The year of the most recent event at `timestamp`: this year's from December, or otherwise
+/// last year's.
+fn latest_event(timestamp: u64) -> Option<u16> {
+    let date = dates::est(timestamp)?;
+    let year = u16::try_from(date.year).ok()?;
+    Some(if date.month == 12 { year } else { year - 1 })
+}
+
+/// The year whose puzzles are downloaded, read, submitted and scaffolded.
+#[must_use]
+pub fn year() -> u16 {
+    env::var(YEAR_VAR)
+        .ok()
+        .and_then(|year| year.trim().parse().ok())
+        .or_else(|| {
+            let toml = fs::read_to_string(CONFIG_PATH).ok()?;
+            toml_value(&toml, "year")?.parse().ok()
+        })
+        .or_else(|| {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+            latest_event(now.as_secs())
+        })
+        .unwrap_or(2015)
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_value() {
+        let toml = "# settings\n[puzzles]\nyear = 2019 # the event\nname = \"a = b\"\n";
+        assert_eq!(toml_value(toml, "year"), Some("2019".into()));
+        assert_eq!(toml_value(toml, "name"), Some("a = b".into()));
+        assert_eq!(toml_value(toml, "day"), None);
+    }
+
+    #[test]
+    fn test_latest_event() {
+        assert_eq!(latest_event(dates::unlock_time(2019, 1)), Some(2019));
+        assert_eq!(latest_event(dates::unlock_time(2019, 1) - 1), Some(2018));
+    }
+}
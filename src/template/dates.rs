@@ -0,0 +1,114 @@
+/// Module that turns Unix timestamps into dates and times in EST (UTC-5), the timezone in which
+/// puzzles unlock at midnight, and so in which the days of the event are counted.
+use std::fmt::Display;
+
+const EST_OFFSET_SECS: u64 = 5 * 60 * 60;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, PartialEq)]
+pub struct DateTime {
+    pub year: u64,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+/// The date and time in EST at a Unix timestamp, if it is not before the epoch there.
+#[must_use]
+pub fn est(timestamp: u64) -> Option<DateTime> {
+    let local = timestamp.checked_sub(EST_OFFSET_SECS)?;
+    let days = local / SECS_PER_DAY;
+    let minutes = (local % SECS_PER_DAY) / 60;
+
+    // the civil date `days` after 1970-01-01, following
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    Some(DateTime {
+        year,
+        month: u8::try_from(month).ok()?,
+        day: u8::try_from(day).ok()?,
+        hour: u8::try_from(minutes / 60).ok()?,
+        minute: u8::try_from(minutes % 60).ok()?,
+    })
+}
+
+/// The Unix timestamp at which the puzzle for a day of December unlocks: midnight in EST.
+#[must_use]
+pub fn unlock_time(year: u64, day: u8) -> u64 {
+    // the days from 1970-01-01 to December `day` of `year`, following
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let era = year / 400;
+    let year_of_era = year % 400;
+    // December is the tenth month counting from March.
+    let day_of_year = (153 * 9 + 2) / 5 + u64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146_097 + day_of_era).saturating_sub(719_468);
+
+    days * SECS_PER_DAY + EST_OFFSET_SECS
+}
+
+/// The Unix timestamp of the first midnight in EST after `timestamp`.
+#[must_use]
+pub fn next_midnight(timestamp: u64) -> u64 {
+    let days = timestamp.saturating_sub(EST_OFFSET_SECS) / SECS_PER_DAY;
+    (days + 1) * SECS_PER_DAY + EST_OFFSET_SECS
+}
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{:02}-{:02} {:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_est() {
+        // 2019-12-01 05:00 UTC, when the first puzzle unlocked.
+        assert_eq!(est(1_575_176_400).unwrap().to_string(), "2019-12-01 00:00");
+        assert_eq!(est(1_575_176_399).unwrap().to_string(), "2019-11-30 23:59");
+        assert_eq!(est(1_582_977_600).unwrap().to_string(), "2020-02-29 07:00");
+        assert_eq!(est(18_000).unwrap().to_string(), "1970-01-01 00:00");
+        assert_eq!(est(17_999), None);
+    }
+
+    #[test]
+    fn test_unlock_time() {
+        assert_eq!(unlock_time(2019, 1), 1_575_176_400);
+        assert_eq!(
+            est(unlock_time(2019, 25)).unwrap().to_string(),
+            "2019-12-25 00:00"
+        );
+        assert_eq!(
+            est(unlock_time(2024, 3)).unwrap().to_string(),
+            "2024-12-03 00:00"
+        );
+    }
+
+    #[test]
+    fn test_next_midnight() {
+        assert_eq!(next_midnight(1_575_176_399), 1_575_176_400);
+        assert_eq!(next_midnight(1_575_176_400), 1_575_176_400 + SECS_PER_DAY);
+    }
+}
@@ -0,0 +1,147 @@
+/// Module that pulls the example inputs out of a downloaded puzzle description, so that they do
+/// not need copying over by hand.
+use std::{fs, io};
+
+/// Replace the HTML entities which appear in puzzle text with the characters they stand for.
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Remove tags such as the `<em>` used to highlight parts of an example.
+fn strip_tags(s: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for ch in s.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            ch if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn extract_html(puzzle: &str) -> Vec<String> {
+    puzzle
+        .split("<pre><code>")
+        .skip(1)
+        .filter_map(|block| block.split("</code></pre>").next())
+        .map(|block| decode_entities(&strip_tags(block)))
+        .collect()
+}
+
+fn extract_markdown(puzzle: &str) -> Vec<String> {
+    let mut blocks = vec![];
+    let mut block: Option<String> = None;
+
+    for line in puzzle.lines() {
+        if line.trim_start().starts_with("```") {
+            match block.take() {
+                Some(finished) => blocks.push(finished),
+                None => block = Some(String::new()),
+            }
+        } else if let Some(block) = &mut block {
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+
+    blocks
+}
+
+/// The contents of each code block in a puzzle description, in order. The descriptions saved by
+/// aoc-cli are Markdown, in which the `<pre><code>` blocks of the page become fenced code blocks;
+/// the page's HTML is understood too.
+#[must_use]
+pub fn extract(puzzle: &str) -> Vec<String> {
+    let blocks = if puzzle.contains("<pre><code>") {
+        extract_html(puzzle)
+    } else {
+        extract_markdown(puzzle)
+    };
+    blocks
+        .into_iter()
+        .filter(|block| !block.trim().is_empty())
+        .collect()
+}
+
+/// Where the `n`th example for a day is kept: the first in `XX.txt`, and the others in
+/// `XX-n.txt`, as read by `solve --example [n]`.
+#[must_use]
+pub fn get_example_path(day: u8, n: usize) -> String {
+    if n == 1 {
+        format!("data/examples/{day:02}.txt")
+    } else {
+        format!("data/examples/{day:02}-{n}.txt")
+    }
+}
+
+/// Write each block to its example file, returning the paths written. Files which already have
+/// something in them are left alone, unless `force` is set.
+///
+/// # Errors
+///
+/// Will return `io::Error` if an example file cannot be written.
+pub fn save(day: u8, blocks: &[String], force: bool) -> io::Result<Vec<String>> {
+    let mut written = vec![];
+    for (ix, block) in blocks.iter().enumerate() {
+        let path = get_example_path(day, ix + 1);
+        let is_filled = fs::metadata(&path).is_ok_and(|metadata| metadata.len() > 0);
+        if force || !is_filled {
+            fs::write(&path, block)?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_markdown() {
+        let puzzle = [
+            "## --- Day 3: Crossed Wires ---",
+            "",
+            "For example:",
+            "",
+            "```",
+            "R8,U5,L5,D3",
+            "U7,R6,D4,L4",
+            "```",
+            "",
+            "Here are a few more examples:",
+            "",
+            "```",
+            "R75,D30",
+            "```",
+            "",
+            "```",
+            "```",
+        ]
+        .join("\n");
+        assert_eq!(
+            extract(&puzzle),
+            vec!["R8,U5,L5,D3\nU7,R6,D4,L4\n", "R75,D30\n"]
+        );
+    }
+
+    #[test]
+    fn test_extract_html() {
+        let puzzle = "<p>For example:</p>\n<pre><code>#..\n.<em>#</em>&lt;\n</code></pre>\n\
+            <p>Then:</p><pre><code>1 &amp; 2\n</code></pre>";
+        assert_eq!(extract(puzzle), vec!["#..\n.#<\n", "1 & 2\n"]);
+    }
+
+    #[test]
+    fn test_get_example_path() {
+        assert_eq!(get_example_path(3, 1), "data/examples/03.txt");
+        assert_eq!(get_example_path(3, 2), "data/examples/03-2.txt");
+    }
+}
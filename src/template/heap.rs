@@ -0,0 +1,99 @@
+/// Module for `--heap-profile`, which runs each part once under dhat to count what it allocates.
+/// It needs the `dhat-heap` feature, with which `main!` makes dhat's allocator the global one.
+use std::fmt::Display;
+use std::fs;
+
+pub use dhat::Alloc;
+
+/// What a part allocated while it ran.
+#[derive(Debug, PartialEq)]
+pub struct HeapUsage {
+    /// The most bytes allocated at once, and in how many blocks.
+    pub peak_bytes: u64,
+    pub peak_blocks: u64,
+    /// Every allocation made, and how many bytes they added up to.
+    pub total_bytes: u64,
+    pub total_blocks: u64,
+}
+
+/// Where dhat writes the profile of a part, to be opened in its viewer.
+#[must_use]
+pub fn get_profile_path(day: u8, part: u8) -> String {
+    format!("data/profiles/dhat-{day:02}-part{part}.json")
+}
+
+/// Run `func` under a heap profiler, writing its profile to `path`.
+///
+/// # Panics
+///
+/// Will panic if a heap profiler is already running.
+pub fn profile<T>(func: impl FnOnce() -> T, path: &str) -> (T, HeapUsage) {
+    let _ = fs::create_dir_all("data/profiles");
+    let profiler = dhat::Profiler::builder().file_name(path).build();
+    let result = func();
+    let stats = dhat::HeapStats::get();
+    drop(profiler);
+
+    let usage = HeapUsage {
+        peak_bytes: stats.max_bytes as u64,
+        peak_blocks: stats.max_blocks as u64,
+        total_bytes: stats.total_bytes,
+        total_blocks: stats.total_blocks,
+    };
+    (result, usage)
+}
+
+/// A number of bytes in the largest binary unit it makes at least one of.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+impl Display for HeapUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "peak {} in {} blocks, {} allocations of {} in total",
+            format_bytes(self.peak_bytes),
+            self.peak_blocks,
+            self.total_blocks,
+            format_bytes(self.total_bytes)
+        )
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn test_display() {
+        let usage = HeapUsage {
+            peak_bytes: 2048,
+            peak_blocks: 3,
+            total_bytes: 100,
+            total_blocks: 7,
+        };
+        assert_eq!(
+            usage.to_string(),
+            "peak 2.0 KiB in 3 blocks, 7 allocations of 100 B in total"
+        );
+    }
+}
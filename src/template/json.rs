@@ -0,0 +1,231 @@
+/// Module with a small JSON parser, for the documents fetched from adventofcode.com such as
+/// private leaderboards. It reads any valid JSON, but keeps numbers as `f64`.
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    /// The member `key` of an object.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.get(key),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// A number which is a whole, non-negative value.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_f64()
+            .filter(|number| *number >= 0_f64 && number.fract() == 0_f64)
+            .map(|number| number as u64)
+    }
+
+    #[must_use]
+    pub fn as_object(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a JSON document, returning `None` if it is not valid JSON.
+#[must_use]
+pub fn parse(json: &str) -> Option<Value> {
+    let mut chars = json.chars().peekable();
+    let value = parse_value(&mut chars)?;
+    skip_whitespace(&mut chars);
+    chars.peek().is_none().then_some(value)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|ch| ch.is_ascii_whitespace()).is_some() {}
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Option<Value> {
+    skip_whitespace(chars);
+    match chars.peek()? {
+        '{' => parse_object(chars),
+        '[' => parse_array(chars),
+        '"' => parse_string(chars).map(Value::String),
+        't' => parse_literal(chars, "true", Value::Bool(true)),
+        'f' => parse_literal(chars, "false", Value::Bool(false)),
+        'n' => parse_literal(chars, "null", Value::Null),
+        _ => parse_number(chars),
+    }
+}
+
+fn parse_literal(chars: &mut Peekable<Chars>, literal: &str, value: Value) -> Option<Value> {
+    for expected in literal.chars() {
+        chars.next_if_eq(&expected)?;
+    }
+    Some(value)
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> Option<Value> {
+    let mut number = String::new();
+    while let Some(ch) = chars.next_if(|ch| matches!(ch, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')) {
+        number.push(ch);
+    }
+    number.parse().ok().map(Value::Number)
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Option<String> {
+    chars.next_if_eq(&'"')?;
+    let mut s = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => s.push(match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'u' => parse_unicode_escape(chars)?,
+                ch => ch,
+            }),
+            ch => s.push(ch),
+        }
+    }
+}
+
+/// The character of a `\uXXXX` escape, which for characters outside the Basic Multilingual Plane
+/// is followed by a second escape with the low surrogate.
+fn parse_unicode_escape(chars: &mut Peekable<Chars>) -> Option<char> {
+    let hex = |chars: &mut Peekable<Chars>| {
+        let digits: String = chars.by_ref().take(4).collect();
+        u32::from_str_radix(&digits, 16).ok()
+    };
+
+    let high = hex(chars)?;
+    if !(0xd800..0xdc00).contains(&high) {
+        return char::from_u32(high);
+    }
+    chars.next_if_eq(&'\\')?;
+    chars.next_if_eq(&'u')?;
+    let low = hex(chars)?;
+    char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low.checked_sub(0xdc00)?))
+}
+
+fn parse_array(chars: &mut Peekable<Chars>) -> Option<Value> {
+    chars.next_if_eq(&'[')?;
+    let mut values = vec![];
+    skip_whitespace(chars);
+    if chars.next_if_eq(&']').is_some() {
+        return Some(Value::Array(values));
+    }
+    loop {
+        values.push(parse_value(chars)?);
+        skip_whitespace(chars);
+        match chars.next()? {
+            ',' => {}
+            ']' => return Some(Value::Array(values)),
+            _ => return None,
+        }
+    }
+}
+
+fn parse_object(chars: &mut Peekable<Chars>) -> Option<Value> {
+    chars.next_if_eq(&'{')?;
+    let mut members = BTreeMap::new();
+    skip_whitespace(chars);
+    if chars.next_if_eq(&'}').is_some() {
+        return Some(Value::Object(members));
+    }
+    loop {
+        skip_whitespace(chars);
+        let key = parse_string(chars)?;
+        skip_whitespace(chars);
+        chars.next_if_eq(&':')?;
+        members.insert(key, parse_value(chars)?);
+        skip_whitespace(chars);
+        match chars.next()? {
+            ',' => {}
+            '}' => return Some(Value::Object(members)),
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let value = parse(
+            r#" {"name": "a\"bé🎄", "stars": 12, "score": -1.5e2,
+                "days": [true, false, null, []], "empty": {}} "#,
+        )
+        .unwrap();
+
+        assert_eq!(value.get("name").and_then(Value::as_str), Some("a\"bé🎄"));
+        assert_eq!(value.get("stars").and_then(Value::as_u64), Some(12));
+        assert_eq!(value.get("score").and_then(Value::as_f64), Some(-150_f64));
+        assert_eq!(value.get("score").and_then(Value::as_u64), None);
+        assert_eq!(
+            value.get("days"),
+            Some(&Value::Array(vec![
+                Value::Bool(true),
+                Value::Bool(false),
+                Value::Null,
+                Value::Array(vec![])
+            ]))
+        );
+        assert_eq!(
+            value
+                .get("empty")
+                .and_then(Value::as_object)
+                .map(BTreeMap::len),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_parse_escapes() {
+        assert_eq!(
+            parse(r#""\u00e9\ud83c\udf84\n\\""#),
+            Some(Value::String("é🎄\n\\".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("{\"a\": 1,}"), None);
+        assert_eq!(parse("[1 2]"), None);
+        assert_eq!(parse("\"open"), None);
+        assert_eq!(parse("{} {}"), None);
+        assert_eq!(parse("nul"), None);
+    }
+}
@@ -0,0 +1,276 @@
+/// Module that renders the Markdown puzzle descriptions saved by aoc-cli for the terminal:
+/// headings and emphasis are styled, paragraphs and lists are wrapped to fit, and code blocks
+/// keep their layout with numbers highlighted.
+use std::env;
+
+use crate::template::{strip_ansi, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET, ANSI_YELLOW};
+
+const ANSI_UNDERLINE: &str = "\x1b[4m";
+const ANSI_CYAN: &str = "\x1b[36m";
+
+/// The widest that paragraphs are wrapped to, even on a wider terminal.
+const MAX_WIDTH: usize = 100;
+
+/// The width to wrap text to: the terminal's, as far as `COLUMNS` tells, up to `MAX_WIDTH`.
+#[must_use]
+pub fn terminal_width() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(80)
+        .min(MAX_WIDTH)
+}
+
+#[derive(Default)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    code: bool,
+    link: bool,
+}
+
+impl Style {
+    /// The escape codes which switch from any style to this one.
+    fn codes(&self) -> String {
+        let mut codes = String::from(ANSI_RESET);
+        for (is_set, code) in [
+            (self.bold, ANSI_BOLD),
+            (self.italic, ANSI_ITALIC),
+            (self.code, ANSI_CYAN),
+            (self.link, ANSI_UNDERLINE),
+        ] {
+            if is_set {
+                codes.push_str(code);
+            }
+        }
+        codes
+    }
+}
+
+/// Style the inline Markdown of a paragraph: `**bold**`, `*emphasis*`, `` `code` `` and
+/// `[links](url)`, which show just their text. Backslash escapes are removed.
+fn render_inline(text: &str) -> String {
+    let mut rendered = String::new();
+    let mut style = Style::default();
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' if !style.code => rendered.extend(chars.next()),
+            '`' => {
+                style.code = !style.code;
+                rendered.push_str(&style.codes());
+            }
+            '*' if !style.code => {
+                if chars.next_if_eq(&'*').is_some() {
+                    style.bold = !style.bold;
+                } else {
+                    style.italic = !style.italic;
+                }
+                rendered.push_str(&style.codes());
+            }
+            '[' if !style.code => {
+                style.link = true;
+                rendered.push_str(&style.codes());
+            }
+            ']' if style.link && chars.peek() == Some(&'(') => {
+                // skip the target of the link.
+                for ch in chars.by_ref() {
+                    if ch == ')' {
+                        break;
+                    }
+                }
+                style.link = false;
+                rendered.push_str(&style.codes());
+            }
+            ch => rendered.push(ch),
+        }
+    }
+
+    rendered.push_str(ANSI_RESET);
+    rendered
+}
+
+/// How many columns a string takes up, leaving out escape codes.
+fn visible_width(s: &str) -> usize {
+    strip_ansi(s).chars().count()
+}
+
+/// Wrap styled text to `width` columns, starting the first line with `first` and the rest with
+/// `indent`, which should be as wide.
+fn wrap(text: &str, width: usize, first: &str, indent: &str) -> String {
+    let mut lines = vec![];
+    let mut line = String::from(first);
+    let mut line_width = visible_width(first);
+    let mut is_empty = true;
+
+    for word in text.split_whitespace() {
+        let word_width = visible_width(word);
+        if !is_empty && line_width + 1 + word_width > width {
+            lines.push(line);
+            line = String::from(indent);
+            line_width = visible_width(indent);
+            is_empty = true;
+        }
+        if !is_empty {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(word);
+        line_width += word_width;
+        is_empty = false;
+    }
+
+    lines.push(line);
+    lines.join("\n")
+}
+
+/// Highlight the numbers in a line of a code block.
+fn highlight_code(line: &str) -> String {
+    let mut highlighted = String::new();
+    let mut in_number = false;
+    for ch in line.chars() {
+        if ch.is_ascii_digit() != in_number {
+            in_number = !in_number;
+            highlighted.push_str(if in_number { ANSI_YELLOW } else { ANSI_RESET });
+        }
+        highlighted.push(ch);
+    }
+    if in_number {
+        highlighted.push_str(ANSI_RESET);
+    }
+    highlighted
+}
+
+/// Render a Markdown document for a terminal `width` columns wide.
+#[must_use]
+pub fn render(markdown: &str, width: usize) -> String {
+    let mut blocks: Vec<String> = vec![];
+    let mut paragraph: Vec<&str> = vec![];
+    let mut code: Option<Vec<String>> = None;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            blocks.push(wrap(&render_inline(&paragraph.join(" ")), width, "", ""));
+            paragraph.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            flush(&mut paragraph, &mut blocks);
+            match code.take() {
+                Some(lines) => blocks.push(lines.join("\n")),
+                None => code = Some(vec![]),
+            }
+            continue;
+        }
+        if let Some(lines) = &mut code {
+            lines.push(format!("    {}", highlight_code(line)));
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some(heading) = trimmed.strip_prefix('#') {
+            flush(&mut paragraph, &mut blocks);
+            let heading = heading.trim_start_matches('#').trim();
+            blocks.push(format!("{ANSI_BOLD}{heading}{ANSI_RESET}"));
+        } else if let Some(item) = trimmed
+            .strip_prefix("* ")
+            .or_else(|| trimmed.strip_prefix("- "))
+        {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(wrap(&render_inline(item), width, "  • ", "    "));
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+
+    flush(&mut paragraph, &mut blocks);
+    if let Some(lines) = code {
+        blocks.push(lines.join("\n"));
+    }
+
+    // list items follow one another without a gap, like the page shows them.
+    let mut rendered = String::new();
+    for (ix, block) in blocks.iter().enumerate() {
+        if ix > 0 {
+            let is_list = |block: &str| block.starts_with("  • ");
+            rendered.push_str(if is_list(block) && is_list(&blocks[ix - 1]) {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        rendered.push_str(block);
+    }
+    rendered.push('\n');
+    rendered
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_inline() {
+        assert_eq!(
+            render_inline("a **b** *c* `d*e` [f](/g) 1\\-2"),
+            format!(
+                "a {ANSI_RESET}{ANSI_BOLD}b{ANSI_RESET} {ANSI_RESET}{ANSI_ITALIC}c{ANSI_RESET} \
+                {ANSI_RESET}{ANSI_CYAN}d*e{ANSI_RESET} {ANSI_RESET}{ANSI_UNDERLINE}f{ANSI_RESET} \
+                1-2{ANSI_RESET}"
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_layout() {
+        let markdown = [
+            "## --- Day 1: The Tyranny of the Rocket Equation ---",
+            "",
+            "Fuel required to launch a given module is based on its",
+            "*mass*. Specifically:",
+            "",
+            "* For a mass of `12`, divide by three and round down.",
+            "* For a mass of `14`, the fuel required is also `2`.",
+            "",
+            "```",
+            "  12",
+            "1969",
+            "```",
+        ]
+        .join("\n");
+
+        assert_eq!(
+            strip_ansi(&render(&markdown, 30)),
+            [
+                "--- Day 1: The Tyranny of the Rocket Equation ---",
+                "",
+                "Fuel required to launch a",
+                "given module is based on its",
+                "mass. Specifically:",
+                "",
+                "  • For a mass of 12, divide",
+                "    by three and round down.",
+                "  • For a mass of 14, the fuel",
+                "    required is also 2.",
+                "",
+                "      12",
+                "    1969",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_highlight_code() {
+        assert_eq!(
+            highlight_code("R75,D30"),
+            format!("R{ANSI_YELLOW}75{ANSI_RESET},D{ANSI_YELLOW}30{ANSI_RESET}")
+        );
+    }
+}
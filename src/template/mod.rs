@@ -1,13 +1,90 @@
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Read};
+use std::process::Command;
+use std::sync::OnceLock;
 use std::{env, fs};
 
+pub mod answers;
 pub mod aoc_cli;
 pub mod commands;
+pub mod config;
+pub mod dates;
+pub mod examples;
+#[cfg(feature = "dhat-heap")]
+pub mod heap;
+pub mod json;
+pub mod markdown;
+pub mod profile;
+pub mod progress;
 pub mod readme_benchmarks;
 pub mod runner;
+pub mod session;
+pub mod timings;
 
 pub const ANSI_ITALIC: &str = "\x1b[3m";
 pub const ANSI_BOLD: &str = "\x1b[1m";
 pub const ANSI_RESET: &str = "\x1b[0m";
+pub const ANSI_DIM: &str = "\x1b[2m";
+pub const ANSI_GREEN: &str = "\x1b[32m";
+pub const ANSI_RED: &str = "\x1b[31m";
+pub const ANSI_YELLOW: &str = "\x1b[33m";
+
+/// Whether output is styled with escape codes. It is unless `--no-color` is passed or `NO_COLOR`
+/// is set, or stdout is not a terminal and `CLICOLOR_FORCE` does not insist.
+#[must_use]
+pub fn color_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        let is_set = |name: &str| env::var_os(name).is_some_and(|value| !value.is_empty());
+        if env::args().any(|x| x == "--no-color") || is_set("NO_COLOR") {
+            false
+        } else {
+            is_set("CLICOLOR_FORCE") || io::stdout().is_terminal()
+        }
+    })
+}
+
+/// Whether stdout is a terminal that can be redrawn, as spinners and progress bars need.
+#[must_use]
+pub fn is_interactive() -> bool {
+    color_enabled() && io::stdout().is_terminal()
+}
+
+/// Wrap `text` in the escape code `code`, if output is styled.
+#[must_use]
+pub fn paint(code: &str, text: impl Display) -> String {
+    if color_enabled() {
+        format!("{code}{text}{ANSI_RESET}")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Remove the escape codes from styled text.
+#[must_use]
+pub fn strip_ansi(s: &str) -> String {
+    let mut text = String::new();
+    let mut in_escape = false;
+    for ch in s.chars() {
+        match ch {
+            '\x1b' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if in_escape => {}
+            ch => text.push(ch),
+        }
+    }
+    text
+}
+
+/// Pass the choice of styling on to a child process whose output is piped back, where it could
+/// not tell for itself whether it ends up on a terminal.
+pub fn inherit_color(cmd: &mut Command) -> &mut Command {
+    if color_enabled() {
+        cmd.env("CLICOLOR_FORCE", "1")
+    } else {
+        cmd.env("NO_COLOR", "1")
+    }
+}
 
 /// Helper function that reads a text file to a string.
 ///
@@ -22,13 +99,54 @@ pub fn read_file(folder: &str, day: u8) -> String {
     f.expect("could not open input file")
 }
 
-/// main! produces a block setting up the input and runner for each part.
+/// Reads the puzzle input for a day: the file passed with `--input <path>` if there is one, so
+/// that other inputs can be tried without replacing the downloaded one, standard input with
+/// `--stdin`, the example chosen with `--example [n]`, or otherwise `data/inputs/XX.txt`, or
+/// `data/inputs/<name>/XX.txt` for the input profile chosen with `--profile <name>`.
+///
+/// # Panics
+///
+/// Will panic if the input file cannot be read.
+#[must_use]
+pub fn read_input(day: u8) -> String {
+    let args: Vec<String> = env::args().collect();
+    let value_of = |name: &str| args.iter().skip_while(|x| *x != name).nth(1);
+
+    if let Some(path) = value_of("--input") {
+        return fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("could not open input file {path}: {e}"));
+    }
+
+    if args.iter().any(|x| x == "--stdin") {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .expect("could not read input from stdin");
+        return input;
+    }
+
+    if !args.iter().any(|x| x == "--example") {
+        return read_file(&profile::inputs_folder(), day);
+    }
+    match value_of("--example").and_then(|number| number.parse::<u8>().ok()) {
+        Some(number) => fs::read_to_string(examples::get_example_path(day, usize::from(number)))
+            .expect("could not open example file"),
+        None => read_file("examples", day),
+    }
+}
+
+/// main! produces a block setting up the input and runner for each part. With the `dhat-heap`
+/// feature, it also makes dhat's allocator the global one, for `--heap-profile`.
 #[macro_export]
 macro_rules! main {
     ($day:expr) => {
+        #[cfg(feature = "dhat-heap")]
+        #[global_allocator]
+        static ALLOC: advent_of_code::template::heap::Alloc = advent_of_code::template::heap::Alloc;
+
         fn main() {
             use advent_of_code::template::runner::*;
-            let input = advent_of_code::template::read_file("inputs", $day);
+            let input = advent_of_code::template::read_input($day);
             run_part(part_one, &input, $day, 1);
             run_part(part_two, &input, $day, 2);
         }
@@ -0,0 +1,53 @@
+/// Module for input profiles, which let solutions be checked against the inputs of more than one
+/// account. With `--profile <name>`, inputs are kept in `data/inputs/<name>/` and downloaded with
+/// that profile's own session token; without it, the main account's are used as ever.
+use std::env;
+
+/// The profile chosen with `--profile <name>`, if any.
+#[must_use]
+pub fn current() -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    args.iter()
+        .position(|x| x == "--profile")
+        .and_then(|ix| args.get(ix + 1))
+        .cloned()
+}
+
+/// Whether `name` can be used for a profile, as it becomes part of paths and variable names.
+#[must_use]
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+}
+
+/// The folder under `data/` that inputs are kept in for the current profile.
+#[must_use]
+pub fn inputs_folder() -> String {
+    match current() {
+        Some(name) => format!("inputs/{name}"),
+        None => "inputs".into(),
+    }
+}
+
+/// Add the current profile, if there is one, to the arguments of a child command.
+pub fn forward(args: &mut Vec<String>) {
+    if let Some(name) = current() {
+        args.extend(["--profile".into(), name]);
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("alt"));
+        assert!(is_valid_name("work_2-b"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../alt"));
+        assert!(!is_valid_name("a b"));
+    }
+}
@@ -0,0 +1,199 @@
+/// Module that shows something moving while the user waits: a spinner beside a part which is
+/// still running, a bar counting the days finished by `all --parallel`, and a countdown through
+/// a submission cooldown. These are drawn only when stdout is a terminal, and clear themselves
+/// away before anything else is printed.
+use std::io::{stderr, stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::template::{is_interactive, paint, ANSI_DIM};
+
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(80);
+
+/// How long a spinner waits before it shows, so that quick runs do not flicker.
+const SPINNER_DELAY: Duration = Duration::from_millis(250);
+
+const BAR_WIDTH: usize = 30;
+
+/// Erases the current line and returns to its start.
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+/// A spinner drawn on stdout after a label, from a thread of its own, until it is dropped.
+pub struct Spinner {
+    is_done: Arc<AtomicBool>,
+    thread: Option<JoinHandle<bool>>,
+}
+
+impl Spinner {
+    /// Start spinning after `label`, if stdout is a terminal.
+    #[must_use]
+    pub fn start(label: String) -> Self {
+        let is_done = Arc::new(AtomicBool::new(false));
+        let thread = is_interactive().then(|| {
+            let is_done = Arc::clone(&is_done);
+            thread::spawn(move || {
+                thread::sleep(SPINNER_DELAY);
+                let mut has_drawn = false;
+                for frame in SPINNER_FRAMES.iter().cycle() {
+                    if is_done.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    print!("{CLEAR_LINE}{label}: {}", paint(ANSI_DIM, frame));
+                    let _ = stdout().flush();
+                    has_drawn = true;
+                    thread::sleep(SPINNER_INTERVAL);
+                }
+                has_drawn
+            })
+        });
+        Self { is_done, thread }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.is_done.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().unwrap_or(false) {
+                print!("{CLEAR_LINE}");
+                let _ = stdout().flush();
+            }
+        }
+    }
+}
+
+/// Format a whole number of seconds as hours, minutes and seconds, such as `4m 05s`.
+#[must_use]
+pub fn format_remaining(seconds: u64) -> String {
+    if seconds >= 3600 {
+        format!(
+            "{}h {:02}m {:02}s",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        )
+    } else if seconds >= 60 {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Sleep for `duration`, counting down the time left after `label` each second if stdout is a
+/// terminal, or otherwise saying once how long the wait is.
+pub fn countdown(label: &str, duration: Duration) {
+    let seconds = duration.as_secs();
+    if !is_interactive() {
+        println!("{label}: {}", format_remaining(seconds));
+        thread::sleep(duration);
+        return;
+    }
+
+    for left in (1..=seconds).rev() {
+        print!(
+            "{CLEAR_LINE}{label}: {}",
+            paint(ANSI_DIM, format_remaining(left))
+        );
+        let _ = stdout().flush();
+        thread::sleep(Duration::from_secs(1));
+    }
+    print!("{CLEAR_LINE}");
+    let _ = stdout().flush();
+}
+
+/// Draw a bar for `done` out of `total`, such as `[#####-----] 3/6`.
+fn bar(done: usize, total: usize, unit: &str) -> String {
+    let filled = (BAR_WIDTH * done).checked_div(total).unwrap_or(BAR_WIDTH);
+    format!(
+        "[{}{}] {done}/{total} {unit}",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled)
+    )
+}
+
+/// A bar on stderr counting finished tasks, redrawn by `advance`.
+pub struct ProgressBar {
+    done: usize,
+    total: usize,
+    unit: &'static str,
+    is_shown: bool,
+}
+
+impl ProgressBar {
+    /// Show an empty bar for `total` tasks, if stdout is a terminal; `unit` names what they are.
+    #[must_use]
+    pub fn new(total: usize, unit: &'static str) -> Self {
+        let mut progress = Self {
+            done: 0,
+            total,
+            unit,
+            is_shown: is_interactive(),
+        };
+        progress.draw();
+        progress
+    }
+
+    fn draw(&mut self) {
+        if self.is_shown {
+            eprint!(
+                "{CLEAR_LINE}{}",
+                paint(ANSI_DIM, bar(self.done, self.total, self.unit))
+            );
+            let _ = stderr().flush();
+        }
+    }
+
+    /// Count one more task as finished.
+    pub fn advance(&mut self) {
+        self.done += 1;
+        self.draw();
+    }
+
+    /// Erase the bar, so that a line can be printed in its place, and draw it again afterwards
+    /// with `advance` or `redraw`.
+    pub fn clear(&self) {
+        if self.is_shown {
+            eprint!("{CLEAR_LINE}");
+            let _ = stderr().flush();
+        }
+    }
+
+    pub fn redraw(&mut self) {
+        self.draw();
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(45), "45s");
+        assert_eq!(format_remaining(272), "4m 32s");
+        assert_eq!(format_remaining(605), "10m 05s");
+        assert_eq!(format_remaining(7_265), "2h 01m 05s");
+    }
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(
+            bar(0, 3, "days"),
+            format!("[{}] 0/3 days", "-".repeat(BAR_WIDTH))
+        );
+        assert_eq!(
+            bar(1, 3, "days"),
+            format!("[{}{}] 1/3 days", "#".repeat(10), "-".repeat(20))
+        );
+        assert_eq!(bar(0, 0, "days"), format!("[{}] 0/0 days", "#".repeat(30)));
+    }
+}
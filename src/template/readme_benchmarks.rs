@@ -92,6 +92,17 @@ fn update_content(s: &mut String, timings: Vec<Timings>, total_millis: f64) -> R
     Ok(())
 }
 
+/// Add an empty table to the end of the document if it does not have one, then fill it in.
+fn insert_content(s: &mut String, timings: Vec<Timings>, total_millis: f64) -> Result<(), Error> {
+    if !s.contains(MARKER) {
+        if !s.is_empty() && !s.ends_with('\n') {
+            s.push('\n');
+        }
+        s.push_str(&format!("\n{MARKER}\n{MARKER}\n"));
+    }
+    update_content(s, timings, total_millis)
+}
+
 /// # Errors
 ///
 /// Will return `Error` if README.md does not already exist.
@@ -103,9 +114,22 @@ pub fn update(timings: Vec<Timings>, total_millis: f64) -> Result<(), Error> {
     Ok(())
 }
 
+/// Like `update`, but adds the benchmarks section to the end of README.md if it is missing.
+///
+/// # Errors
+///
+/// Will return `Error` if README.md does not already exist.
+pub fn regenerate(timings: Vec<Timings>, total_millis: f64) -> Result<(), Error> {
+    let path = "README.md";
+    let mut readme = String::from_utf8_lossy(&fs::read(path)?).to_string();
+    insert_content(&mut readme, timings, total_millis)?;
+    fs::write(path, &readme)?;
+    Ok(())
+}
+
 #[cfg(feature = "test_lib")]
 mod tests {
-    use super::{update_content, Timings, MARKER};
+    use super::{insert_content, update_content, Timings, MARKER};
 
     fn get_mock_timings() -> Vec<Timings> {
         vec![
@@ -160,6 +184,22 @@ mod tests {
         assert_eq!(s.matches("## Benchmarks").collect::<Vec<&str>>().len(), 1);
     }
 
+    #[test]
+    fn inserts_missing_benchmarks() {
+        let mut s = "foo\nbar".to_string();
+        insert_content(&mut s, get_mock_timings(), 190.0).unwrap();
+        assert_eq!(s.matches(MARKER).collect::<Vec<&str>>().len(), 2);
+        assert_eq!(
+            s.starts_with("foo\nbar\n\n<!--- benchmarking table --->"),
+            true
+        );
+        assert_eq!(s.ends_with("<!--- benchmarking table --->\n"), true);
+
+        let inserted = s.clone();
+        insert_content(&mut s, get_mock_timings(), 190.0).unwrap();
+        assert_eq!(s, inserted);
+    }
+
     #[test]
     fn format_benchmarks() {
         let mut s = format!("foo\nbar\n{}\n{}\nbaz", MARKER, MARKER);
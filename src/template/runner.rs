@@ -1,66 +1,144 @@
 /// Encapsulates code that interacts with solution functions.
-use crate::template::{aoc_cli, ANSI_ITALIC, ANSI_RESET};
+use crate::template::commands::submit;
+#[cfg(feature = "dhat-heap")]
+use crate::template::heap;
+use crate::template::progress::Spinner;
+use crate::template::{paint, profile, ANSI_DIM, ANSI_GREEN, ANSI_ITALIC, ANSI_RED};
 use std::fmt::Display;
 use std::io::{stdout, Write};
-use std::process::Output;
 use std::time::{Duration, Instant};
 use std::{cmp, env, process};
 
 use super::ANSI_BOLD;
 
+/// Whether `part` should run: every part does unless `--part` selects just one of them.
+fn part_selected(part: u8) -> bool {
+    let args: Vec<String> = env::args().collect();
+    args.iter()
+        .position(|x| x == "--part")
+        .and_then(|ix| args.get(ix + 1))
+        .is_none_or(|selected| selected.parse() == Ok(part))
+}
+
+/// How many runs `--median` discards before timing, so that caches and the CPU clock settle.
+const WARMUP_RUNS: usize = 3;
+
+/// Whether `--json` asked for each part's result as a line of JSON instead of text.
+fn is_json() -> bool {
+    env::args().any(|x| x == "--json")
+}
+
+/// Whether the part runs on the main account's puzzle input, rather than one chosen with
+/// `--input`, `--example`, `--stdin` or `--profile`.
+fn is_puzzle_input() -> bool {
+    profile::current().is_none()
+        && !env::args().any(|x| ["--input", "--example", "--stdin"].contains(&x.as_str()))
+}
+
+/// Whether `--heap-profile` asked for what each part allocates, rather than how long it takes.
+fn is_heap_profile() -> bool {
+    env::args().any(|x| x == "--heap-profile")
+}
+
 pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: u8, part: u8) {
+    if !part_selected(part) {
+        return;
+    }
+
+    if is_heap_profile() {
+        run_heap_profiled(func, input, day, part);
+        return;
+    }
+
     let part_str = format!("Part {part}");
+    let is_json = is_json();
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let spinner_label = (!is_json).then(|| part_str.clone());
+    let (result, duration, duration_str) = run_timed(func, input, spinner_label, |result| {
+        if !is_json {
+            print_result(result, &part_str, "");
+        }
+    });
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+    if is_json {
+        print_json(result.as_ref(), day, part, &duration);
+    } else {
+        print_result(&result, &part_str, &duration_str);
+        if is_puzzle_input() {
+            submit::keep_solved_answer(day, part);
+        }
+    }
 
     if let Some(result) = result {
         submit_result(result, day, part);
     }
 }
 
+/// Run a solution part once under the heap profiler, and print its result with what it allocated.
+#[cfg(feature = "dhat-heap")]
+fn run_heap_profiled<I, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: u8, part: u8) {
+    let part_str = format!("Part {part}");
+    let (result, usage) = heap::profile(|| func(input), &heap::get_profile_path(day, part));
+    print_result(&result, &part_str, "");
+    println!(" {}", paint(ANSI_DIM, format!("({usage})")));
+}
+
+#[cfg(not(feature = "dhat-heap"))]
+fn run_heap_profiled<I, T: Display>(_: impl Fn(I) -> Option<T>, _: I, _: u8, _: u8) {
+    eprintln!("--heap-profile needs the dhat-heap feature. Try running \"cargo solve <day> --heap-profile\".");
+    process::exit(1);
+}
+
 /// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
 ///  1. in debug, the function is executed once.
 ///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
+///
+/// With `--median`, the function is benched after some warmup runs, and the median and
+/// interquartile range are reported rather than the mean.
+///
+/// A spinner is shown after `spinner_label` while the first run is underway, if there is one.
+///
+/// Returns the duration reported (the single run, mean or median) along with its description.
 fn run_timed<I: Clone, T>(
     func: impl Fn(I) -> T,
     input: I,
+    spinner_label: Option<String>,
     hook: impl Fn(&T),
-) -> (T, Duration, u128) {
+) -> (T, Duration, String) {
+    let spinner = spinner_label.map(Spinner::start);
     let timer = Instant::now();
     let result = func(input.clone());
     let base_time = timer.elapsed();
+    drop(spinner);
 
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
-        bench(func, input, &base_time)
+    let (duration, duration_str) = if std::env::args().any(|x| x == "--median") {
+        bench_median(func, input, &base_time)
+    } else if std::env::args().any(|x| x == "--time") {
+        let (duration, samples) = bench(func, input, &base_time);
+        (duration, format_duration(&duration, samples))
     } else {
-        (base_time, 1)
+        (base_time, format_duration(&base_time, 1))
     };
 
-    (result, run.0, run.1)
+    (result, duration, duration_str)
 }
 
-fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
-    let mut stdout = stdout();
-
-    print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
-    let _ = stdout.flush();
+fn bench_iterations(base_time: &Duration) -> u128 {
+    (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000)
+}
 
-    let bench_iterations = cmp::min(
-        10000,
-        cmp::max(
-            Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10),
-            10,
-        ),
-    );
+/// Time every run of `func`, in order.
+fn sample<I: Clone, T>(func: impl Fn(I) -> T, input: &I, iterations: u128) -> Vec<Duration> {
+    if !is_json() {
+        print!(" > {}", paint(ANSI_ITALIC, "benching"));
+        let _ = stdout().flush();
+    }
 
     let mut timers: Vec<Duration> = vec![];
 
-    for _ in 0..bench_iterations {
+    for _ in 0..iterations {
         // need a clone here to make the borrow checker happy.
         let cloned = input.clone();
         let timer = Instant::now();
@@ -68,12 +146,44 @@ fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) ->
         timers.push(timer.elapsed());
     }
 
+    timers
+}
+
+fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
+    let bench_iterations = bench_iterations(base_time);
+    let timers = sample(func, &input, bench_iterations);
+
     (
         Duration::from_nanos(u64::try_from(average_duration(&timers)).unwrap_or(u64::MAX)),
         bench_iterations,
     )
 }
 
+fn bench_median<I: Clone, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    base_time: &Duration,
+) -> (Duration, String) {
+    for _ in 0..WARMUP_RUNS {
+        func(input.clone());
+    }
+
+    let bench_iterations = bench_iterations(base_time);
+    let mut timers = sample(func, &input, bench_iterations);
+    timers.sort_unstable();
+
+    let quartile = |q: usize| timers[(timers.len() - 1) * q / 4];
+    (
+        quartile(2),
+        format!(
+            " (median {:.1?}, IQR {:.1?}–{:.1?} @ {bench_iterations} samples)",
+            quartile(2),
+            quartile(1),
+            quartile(3)
+        ),
+    )
+}
+
 fn average_duration(numbers: &[Duration]) -> u128 {
     numbers.iter().map(Duration::as_nanos).sum::<u128>() / numbers.len() as u128
 }
@@ -88,6 +198,11 @@ fn format_duration(duration: &Duration, samples: u128) -> String {
 
 fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) {
     let is_intermediate_result = duration_str.is_empty();
+    let duration_str = if is_intermediate_result {
+        String::new()
+    } else {
+        paint(ANSI_DIM, duration_str)
+    };
 
     match result {
         Some(result) => {
@@ -101,7 +216,8 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
                     println!("{str}");
                 }
             } else {
-                let str = format!("{part}: {ANSI_BOLD}{result}{ANSI_RESET}{duration_str}");
+                let result = paint(ANSI_GREEN, paint(ANSI_BOLD, result));
+                let str = format!("{part}: {result}{duration_str}");
                 if is_intermediate_result {
                     print!("{str}");
                 } else {
@@ -111,28 +227,53 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
             }
         }
         None => {
+            let cross = paint(ANSI_RED, "✖");
             if is_intermediate_result {
-                print!("{part}: ✖");
+                print!("{part}: {cross}");
             } else {
                 print!("\r");
-                println!("{part}: ✖             ");
+                println!("{part}: {cross}             ");
             }
         }
     }
 }
 
+/// Quote a string for JSON, escaping the characters which need it.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            ch if ch.is_control() => quoted.push_str(&format!("\\u{:04x}", u32::from(ch))),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Print a part's result as a single line of JSON, for `--json`.
+fn print_json<T: Display>(result: Option<&T>, day: u8, part: u8, duration: &Duration) {
+    let answer = result.map_or_else(|| "null".into(), |result| json_string(&result.to_string()));
+    println!(
+        "{{\"day\":{day},\"part\":{part},\"answer\":{answer},\"duration_nanos\":{},\"success\":{}}}",
+        duration.as_nanos(),
+        result.is_some()
+    );
+}
+
 /// Parse the arguments passed to `solve` and try to submit one part of the solution if:
 ///  1. we are in `--release` mode.
 ///  2. aoc-cli is installed.
-fn submit_result<T: Display>(
-    result: T,
-    day: u8,
-    part: u8,
-) -> Option<Result<Output, aoc_cli::CliError>> {
+fn submit_result<T: Display>(result: T, day: u8, part: u8) {
     let args: Vec<String> = env::args().collect();
 
     if !args.contains(&"--submit".into()) {
-        return None;
+        return;
     }
 
     if args.len() < 3 {
@@ -146,15 +287,22 @@ fn submit_result<T: Display>(
         process::exit(1);
     };
 
-    if part_submit != part {
-        return None;
+    if part_submit == part {
+        let wait = args.iter().any(|x| x == "--wait");
+        submit::handler(day, part, &result.to_string(), wait);
     }
+}
 
-    if aoc_cli::check().is_err() {
-        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
-        process::exit(1);
-    }
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::json_string;
 
-    println!("Submitting result via aoc-cli...");
-    Some(aoc_cli::submit(day, part, &result.to_string()))
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("658"), "\"658\"");
+        assert_eq!(
+            json_string("#..#\n\"a\\b\"\t\u{1}"),
+            "\"#..#\\n\\\"a\\\\b\\\"\\t\\u0001\""
+        );
+    }
 }
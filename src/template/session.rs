@@ -0,0 +1,253 @@
+/// Module that manages the Advent of Code session cookie which aoc-cli uses to download inputs
+/// and submit answers. It lives where aoc-cli looks for it, `~/.adventofcode.session`, unless
+/// the `ADVENT_OF_CODE_SESSION` environment variable overrides it. An input profile has its own
+/// token, in `~/.adventofcode.session.<name>` or `ADVENT_OF_CODE_SESSION_<NAME>`.
+use std::{
+    env,
+    fmt::Display,
+    fs, io,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::{Duration, SystemTime},
+};
+
+use crate::template::{aoc_cli, config, profile};
+
+pub(crate) static SESSION_VAR: &str = "ADVENT_OF_CODE_SESSION";
+static SESSION_FILE: &str = ".adventofcode.session";
+
+/// Roughly how long the site keeps a session alive after logging in.
+pub const SESSION_LIFETIME: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Debug)]
+pub enum TokenError {
+    NoHomeDirectory,
+    NotFound,
+    Malformed,
+    IO(io::Error),
+    CurlNotFound,
+    Unreachable,
+    /// The site did not accept the token, answering with this HTTP status.
+    Rejected(String),
+    /// The site answered, but as it would someone who is not logged in.
+    LoggedOut,
+}
+
+impl Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenError::NoHomeDirectory => write!(f, "could not find the home directory."),
+            TokenError::NotFound => match profile::current() {
+                Some(name) => write!(
+                    f,
+                    "no session token set for profile {name}. Run \"cargo token set <token> \
+                    --profile {name}\" to set one."
+                ),
+                None => write!(
+                    f,
+                    "no session token set. Run \"cargo token set <token>\" to set one."
+                ),
+            },
+            TokenError::Malformed => write!(
+                f,
+                "a session token is the hexadecimal value of the \"session\" cookie."
+            ),
+            TokenError::IO(e) => write!(f, "could not access the session file: {e}"),
+            TokenError::CurlNotFound => write!(f, "curl is not present in environment."),
+            TokenError::Unreachable => write!(f, "could not connect to adventofcode.com."),
+            TokenError::Rejected(status) => write!(
+                f,
+                "the token was not accepted (HTTP {status}); it may have expired."
+            ),
+            TokenError::LoggedOut => write!(
+                f,
+                "the site did not recognize the token as logged in; it may have expired."
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for TokenError {
+    fn from(e: io::Error) -> Self {
+        TokenError::IO(e)
+    }
+}
+
+/// Where the token came from.
+pub enum Source {
+    /// The environment variable of this name.
+    Environment(String),
+    File(PathBuf),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Environment(var) => write!(f, "the {var} environment variable"),
+            Source::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+pub struct Session {
+    token: String,
+    source: Source,
+    saved: Option<SystemTime>,
+}
+
+impl Session {
+    /// The token in use, from the environment if set there, or otherwise the session file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TokenError` if no token is set or the session file cannot be read.
+    pub fn load() -> Result<Self, TokenError> {
+        let var = session_var();
+        if let Ok(token) = env::var(&var) {
+            return Ok(Self {
+                token: token.trim().to_string(),
+                source: Source::Environment(var),
+                saved: None,
+            });
+        }
+
+        let path = session_path()?;
+        let token = match fs::read_to_string(&path) {
+            Ok(token) => token.trim().to_string(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(TokenError::NotFound),
+            Err(e) => return Err(e.into()),
+        };
+        let saved = fs::metadata(&path)?.modified().ok();
+
+        Ok(Self {
+            token,
+            source: Source::File(path),
+            saved,
+        })
+    }
+
+    #[must_use]
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// The token with all but its first few characters hidden, for display.
+    #[must_use]
+    pub fn masked(&self) -> String {
+        let shown: String = self.token.chars().take(8).collect();
+        format!("{shown}…")
+    }
+
+    pub(crate) fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// How long ago the token was saved, if it came from the session file.
+    #[must_use]
+    pub fn age(&self) -> Option<Duration> {
+        self.saved.and_then(|saved| saved.elapsed().ok())
+    }
+
+    /// Whether the site accepts the token, judged by asking it for a puzzle input.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TokenError` if curl cannot be called, the site cannot be reached, or the
+    /// token is rejected.
+    pub fn validate(&self) -> Result<(), TokenError> {
+        let year = config::year();
+        self.fetch(&format!("/{year}/day/1/input")).map(|_| ())
+    }
+
+    /// Fetch a page of adventofcode.com as the logged-in user, such as `/2019` for the calendar.
+    ///
+    /// # Errors
+    ///
+    /// Will return `TokenError` if curl cannot be called, the site cannot be reached, or the
+    /// token is rejected.
+    pub fn fetch(&self, path: &str) -> Result<String, TokenError> {
+        aoc_cli::throttle();
+        let mut curl = Command::new("curl")
+            .args([
+                "--silent",
+                "--write-out",
+                "\n%{http_code}",
+                "--user-agent",
+                aoc_cli::USER_AGENT,
+                // the cookie is read from stdin, so that it does not show up in the process list.
+                "--header",
+                "@-",
+                &format!("https://adventofcode.com{path}"),
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|_| TokenError::CurlNotFound)?;
+
+        if let Some(mut stdin) = curl.stdin.take() {
+            writeln!(stdin, "Cookie: session={}", self.token)?;
+        }
+        let output = curl.wait_with_output()?;
+        let output = String::from_utf8_lossy(&output.stdout);
+        let (body, status) = output.rsplit_once('\n').unwrap_or(("", &output));
+
+        match status {
+            "200" => Ok(body.to_string()),
+            // curl reports no status when it could not make the request at all.
+            "000" => Err(TokenError::Unreachable),
+            _ => Err(TokenError::Rejected(status.to_string())),
+        }
+    }
+}
+
+/// The environment variable which overrides the session file of the current profile.
+fn session_var() -> String {
+    match profile::current() {
+        Some(name) => format!("{SESSION_VAR}_{}", name.to_uppercase().replace('-', "_")),
+        None => SESSION_VAR.into(),
+    }
+}
+
+fn session_path() -> Result<PathBuf, TokenError> {
+    let file = match profile::current() {
+        Some(name) => format!("{SESSION_FILE}.{name}"),
+        None => SESSION_FILE.into(),
+    };
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(file))
+        .ok_or(TokenError::NoHomeDirectory)
+}
+
+/// Store a token in the session file, readable only by the current user. The token may be given
+/// as the bare cookie value or as `session=<value>`.
+///
+/// # Errors
+///
+/// Will return `TokenError` if the token is not hexadecimal or the file cannot be written.
+pub fn save(token: &str) -> Result<PathBuf, TokenError> {
+    let token = token.trim();
+    let token = token.strip_prefix("session=").unwrap_or(token);
+    if token.is_empty() || !token.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err(TokenError::Malformed);
+    }
+
+    let path = session_path()?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // restrict the file before the token is in it, whether it is new or was already there.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(token.as_bytes())?;
+    Ok(path)
+}
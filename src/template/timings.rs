@@ -0,0 +1,233 @@
+/// Module that keeps the latest run time of each part in `data/timings.json`, so that `time` and
+/// `all --time` can show how a run compares with the previous one and flag regressions.
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::{fs, io};
+
+use crate::template::readme_benchmarks::Timings;
+use crate::template::{paint, ANSI_BOLD, ANSI_DIM, ANSI_RED};
+
+static TIMINGS_PATH: &str = "./data/timings.json";
+
+/// How much slower than its previous record a part can get before it is reported as a regression.
+pub const REGRESSION_THRESHOLD: f64 = 0.1;
+
+fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
+    s.split(postfix).next()?.parse().ok()
+}
+
+/// Parse a duration as printed with `{:?}`, such as `74.13ms`, into nanoseconds.
+#[must_use]
+pub fn parse_duration(s: &str) -> Option<f64> {
+    // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
+    match s {
+        s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
+        s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1_000_f64),
+        s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
+        s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn format_nanos(nanos: f64) -> String {
+    format!("{:.1?}", Duration::from_nanos(nanos as u64))
+}
+
+/// The time taken by each part of each day, in nanoseconds, when it was last measured.
+#[derive(Debug, Default, PartialEq)]
+pub struct TimingRecord {
+    parts: BTreeMap<(usize, u8), f64>,
+}
+
+impl TimingRecord {
+    /// Read the record from `data/timings.json`. A missing file gives an empty record, as does
+    /// one which cannot be parsed, after a warning.
+    #[must_use]
+    pub fn load() -> Self {
+        let Ok(json) = fs::read_to_string(TIMINGS_PATH) else {
+            return Self::default();
+        };
+        Self::from_json(&json).unwrap_or_else(|| {
+            eprintln!("Could not parse {TIMINGS_PATH}, starting a new record.");
+            Self::default()
+        })
+    }
+
+    /// Write the record to `data/timings.json`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `io::Error` if the file cannot be written.
+    pub fn save(&self) -> io::Result<()> {
+        fs::write(TIMINGS_PATH, self.to_json())
+    }
+
+    #[must_use]
+    pub fn get(&self, day: usize, part: u8) -> Option<f64> {
+        self.parts.get(&(day, part)).copied()
+    }
+
+    /// The recorded times of every day, in the form used for the README benchmarks table.
+    #[must_use]
+    pub fn timings(&self) -> Vec<Timings> {
+        let mut timings: Vec<Timings> = vec![];
+        for ((day, part), nanos) in &self.parts {
+            if timings.last().is_none_or(|timing| timing.day != *day) {
+                timings.push(Timings {
+                    day: *day,
+                    part_1: None,
+                    part_2: None,
+                    total_nanos: 0_f64,
+                });
+            }
+
+            let timing = timings.last_mut().unwrap();
+            if *part == 1 {
+                timing.part_1 = Some(format_nanos(*nanos));
+            } else {
+                timing.part_2 = Some(format_nanos(*nanos));
+            }
+            timing.total_nanos += nanos;
+        }
+        timings
+    }
+
+    /// Store the times from a run of one day, printing how each part compares with its previous
+    /// record and warning about any part which got slower by more than `REGRESSION_THRESHOLD`.
+    pub fn update(&mut self, timings: &Timings) {
+        for (part, timing) in [(1, &timings.part_1), (2, &timings.part_2)] {
+            let Some(nanos) = timing.as_deref().and_then(parse_duration) else {
+                continue;
+            };
+
+            if let Some(previous) = self.parts.insert((timings.day, part), nanos) {
+                let change = (nanos - previous) / previous;
+                let comparison = format!(
+                    "{} (previously {}, {:+.1}%)",
+                    format_nanos(nanos),
+                    format_nanos(previous),
+                    change * 100_f64
+                );
+                println!("Part {part}: {}", paint(ANSI_DIM, comparison));
+                if change > REGRESSION_THRESHOLD {
+                    eprintln!(
+                        "{} day {} part {part} is {:.1}% slower than before.",
+                        paint(ANSI_RED, paint(ANSI_BOLD, "Warning:")),
+                        timings.day,
+                        change * 100_f64
+                    );
+                }
+            }
+        }
+    }
+
+    /// The record as a JSON object of days, each an object of part numbers to nanoseconds.
+    fn to_json(&self) -> String {
+        let mut days: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for ((day, part), nanos) in &self.parts {
+            days.entry(*day)
+                .or_default()
+                .push(format!("\"{part}\": {nanos}"));
+        }
+
+        let days: Vec<String> = days
+            .into_iter()
+            .map(|(day, parts)| format!("  \"{day}\": {{ {} }}", parts.join(", ")))
+            .collect();
+        format!("{{\n{}\n}}\n", days.join(",\n"))
+    }
+
+    /// Parse the JSON written by `to_json`. This only understands that shape of document.
+    fn from_json(json: &str) -> Option<Self> {
+        let compact: String = json.chars().filter(|ch| !ch.is_whitespace()).collect();
+        let mut rest = compact.strip_prefix('{')?.strip_suffix('}')?;
+        let mut record = Self::default();
+
+        while !rest.is_empty() {
+            let (day, after_day) = rest.strip_prefix('"')?.split_once("\":{")?;
+            let (parts, after_parts) = after_day.split_once('}')?;
+            let day = day.parse().ok()?;
+
+            for entry in parts.split(',').filter(|entry| !entry.is_empty()) {
+                let (part, nanos) = entry.split_once(':')?;
+                let part = part.strip_prefix('"')?.strip_suffix('"')?.parse().ok()?;
+                record.parts.insert((day, part), nanos.parse().ok()?);
+            }
+
+            rest = after_parts.strip_prefix(',').unwrap_or(after_parts);
+        }
+
+        Some(record)
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::*;
+
+    fn timings(day: usize, part_1: &str, part_2: &str) -> Timings {
+        Timings {
+            day,
+            part_1: Some(part_1.into()),
+            part_2: Some(part_2.into()),
+            total_nanos: 0_f64,
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("74.13ns"), Some(74.13));
+        assert_eq!(parse_duration("2µs"), Some(2_000_f64));
+        assert_eq!(parse_duration("1.5ms"), Some(1_500_000_f64));
+        assert_eq!(parse_duration("2s"), Some(2_000_000_000_f64));
+        assert_eq!(parse_duration("fast"), None);
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let mut record = TimingRecord::default();
+        record.update(&timings(1, "10ms", "20.5µs"));
+        record.update(&timings(12, "1s", "74.13ns"));
+
+        let json = record.to_json();
+        assert_eq!(
+            json,
+            "{\n  \"1\": { \"1\": 10000000, \"2\": 20500 },\n  \"12\": { \"1\": 1000000000, \"2\": 74.13 }\n}\n"
+        );
+        assert_eq!(TimingRecord::from_json(&json), Some(record));
+        assert_eq!(TimingRecord::from_json("{}"), Some(TimingRecord::default()));
+        assert_eq!(TimingRecord::from_json("[1, 2]"), None);
+    }
+
+    #[test]
+    fn test_update_replaces_previous() {
+        let mut record = TimingRecord::default();
+        record.update(&timings(3, "10ms", "20ms"));
+        record.update(&Timings {
+            part_2: None,
+            ..timings(3, "15ms", "")
+        });
+        assert_eq!(record.get(3, 1), Some(15_000_000_f64));
+        assert_eq!(record.get(3, 2), Some(20_000_000_f64));
+    }
+
+    #[test]
+    fn test_timings() {
+        let mut record = TimingRecord::default();
+        record.update(&timings(1, "10ms", "20.5µs"));
+        record.update(&Timings {
+            part_2: None,
+            ..timings(4, "1s", "")
+        });
+
+        let timings = record.timings();
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0].day, 1);
+        assert_eq!(timings[0].part_1.as_deref(), Some("10.0ms"));
+        assert_eq!(timings[0].part_2.as_deref(), Some("20.5µs"));
+        assert_eq!(timings[0].total_nanos, 10_020_500_f64);
+        assert_eq!(timings[1].day, 4);
+        assert_eq!(timings[1].part_1.as_deref(), Some("1.0s"));
+        assert_eq!(timings[1].part_2, None);
+    }
+}
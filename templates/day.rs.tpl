@@ -0,0 +1,30 @@
+//! Advent of Code {{year}}, day {{day}}: https://adventofcode.com/{{year}}/day/{{day}}
+
+#[must_use]
+pub fn part_one(input: &str) -> Option<u32> {
+    None
+}
+
+#[must_use]
+pub fn part_two(input: &str) -> Option<u32> {
+    None
+}
+
+advent_of_code::main!({{day}});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", {{day}}));
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&advent_of_code::template::read_file("examples", {{day}}));
+        assert_eq!(result, None);
+    }
+}